@@ -364,7 +364,80 @@ fn best_effort_shortcut_list(help: &ModeInfo, max_len: usize) -> LinePart {
     }
 }
 
+fn search_indication(search: &SearchInfo, palette: Palette) -> LinePart {
+    let white_color = match palette.white {
+        PaletteColor::Rgb((r, g, b)) => RGB(r, g, b),
+        PaletteColor::EightBit(color) => Fixed(color),
+    };
+    let green_color = match palette.green {
+        PaletteColor::Rgb((r, g, b)) => RGB(r, g, b),
+        PaletteColor::EightBit(color) => Fixed(color),
+    };
+    let prefix = " SEARCH: ";
+    let match_count = match search.active_match {
+        Some(active_match) => format!(" [{}/{}]", active_match + 1, search.match_count),
+        None => format!(" [{}]", search.match_count),
+    };
+    let mut options = vec![];
+    if search.case_sensitive {
+        options.push("case sensitive");
+    }
+    if search.regex {
+        options.push("regex");
+    }
+    let options = if options.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", options.join(", "))
+    };
+    let len = prefix.chars().count()
+        + search.query.chars().count()
+        + match_count.chars().count()
+        + options.chars().count();
+    LinePart {
+        part: format!(
+            "{}",
+            ANSIStrings(&[
+                Style::new().fg(white_color).bold().paint(prefix),
+                Style::new().fg(green_color).bold().paint(&search.query),
+                Style::new().fg(white_color).bold().paint(match_count),
+                Style::new().fg(white_color).paint(options),
+            ])
+        ),
+        len,
+    }
+}
+
+fn notice_line(notice: &str, palette: Palette, max_width: usize) -> LinePart {
+    let orange_color = match palette.orange {
+        PaletteColor::Rgb((r, g, b)) => RGB(r, g, b),
+        PaletteColor::EightBit(color) => Fixed(color),
+    };
+    let notice: String = format!(" {}", notice).chars().take(max_width).collect();
+    LinePart {
+        len: notice.chars().count(),
+        part: format!("{}", Style::new().fg(orange_color).bold().paint(notice)),
+    }
+}
+
 pub fn keybinds(help: &ModeInfo, max_width: usize) -> LinePart {
+    if let Some(notice) = help.notice.as_ref() {
+        return notice_line(notice, help.palette, max_width);
+    }
+    if let Some(search) = help.search.as_ref() {
+        let search_indication = search_indication(search, help.palette);
+        if help.mode == InputMode::Search && search_indication.len <= max_width {
+            let shortcut_list = shortcut_list(help, max_width - search_indication.len);
+            return LinePart {
+                part: format!("{}{}", search_indication, shortcut_list),
+                len: search_indication.len + shortcut_list.len,
+            };
+        }
+    }
+    shortcut_list(help, max_width)
+}
+
+fn shortcut_list(help: &ModeInfo, max_width: usize) -> LinePart {
     let full_shortcut_list = full_shortcut_list(help);
     if full_shortcut_list.len <= max_width {
         return full_shortcut_list;
@@ -1,16 +1,22 @@
 mod install;
+mod replay;
 mod sessions;
 #[cfg(test)]
 mod tests;
 
 use crate::install::populate_data_dir;
-use sessions::{assert_session, assert_session_ne, get_active_session, list_sessions};
+use crate::replay::replay;
+use sessions::{
+    assert_session, assert_session_ne, get_active_session, get_session_snapshot, kill_all_sessions,
+    kill_session_by_name, list_sessions, send_action_to_session,
+};
 use std::process;
 use zellij_client::{os_input_output::get_client_os_input, start_client, ClientInfo};
 use zellij_server::{os_input_output::get_server_os_input, start_server};
 use zellij_utils::{
     cli::{CliArgs, Command, Sessions},
     consts::{ZELLIJ_TMP_DIR, ZELLIJ_TMP_LOG_DIR},
+    ipc::ClientType,
     logging::*,
     setup::{get_default_data_dir, Setup},
     structopt::StructOpt,
@@ -20,8 +26,24 @@ pub fn main() {
     configure_logger();
     let opts = CliArgs::from_args();
 
-    if let Some(Command::Sessions(Sessions::ListSessions)) = opts.command {
-        list_sessions();
+    if let Some(Command::Sessions(Sessions::ListSessions { json })) = opts.command {
+        list_sessions(json);
+    }
+
+    if let Some(Command::Sessions(Sessions::KillSession { target_session })) = &opts.command {
+        kill_session_by_name(target_session);
+    }
+
+    if let Some(Command::Sessions(Sessions::KillAllSessions { yes })) = opts.command {
+        kill_all_sessions(yes);
+    }
+
+    if let Some(Command::Action(cli_action)) = opts.command.clone() {
+        send_action_to_session(opts.session.clone(), cli_action);
+    }
+
+    if let Some(Command::Replay { path, speed }) = &opts.command {
+        replay(path, *speed);
     }
 
     let (config, layout, config_options) = match Setup::from_options(&opts) {
@@ -53,20 +75,50 @@ pub fn main() {
         };
         if let Some(Command::Sessions(Sessions::Attach {
             mut session_name,
+            read_only,
             force,
+            resurrect,
         })) = opts.command.clone()
         {
+            if resurrect {
+                let session_name = session_name.unwrap_or_else(|| {
+                    eprintln!("Please specify the name of the session to resurrect.");
+                    process::exit(1);
+                });
+                assert_session_ne(&session_name);
+
+                let data_dir = opts.data_dir.clone().unwrap_or_else(get_default_data_dir);
+                let snapshot = get_session_snapshot(&session_name, &data_dir);
+                #[cfg(not(disable_automatic_asset_installation))]
+                populate_data_dir(&data_dir);
+
+                start_client(
+                    Box::new(os_input),
+                    opts,
+                    config,
+                    ClientInfo::Resurrect(session_name, snapshot),
+                    layout,
+                );
+                return;
+            }
+
             if let Some(session) = session_name.as_ref() {
                 assert_session(session);
             } else {
                 session_name = Some(get_active_session());
             }
 
+            let client_type = if read_only {
+                ClientType::Reader
+            } else {
+                ClientType::Writer
+            };
+
             start_client(
                 Box::new(os_input),
                 opts,
                 config,
-                ClientInfo::Attach(session_name.unwrap(), force, config_options),
+                ClientInfo::Attach(session_name.unwrap(), client_type, force, config_options),
                 None,
             );
         } else {
@@ -0,0 +1,69 @@
+//! Plays the recordings made with the `StartRecording` action, which are asciicast v2 files.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+use std::process;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Instant;
+use zellij_client::os_input_output::{get_client_os_input, ClientOsApi};
+use zellij_server::asciicast::{recorded_output, replay_delay};
+
+const CTRL_C: u8 = 3;
+
+/// Writes the output of the recording to the terminal at the pace it was recorded (sped up by
+/// `speed`), then waits for `q` or `Ctrl-c` to exit, which also stop the playback early.
+/// Resizes are not played, as the size of the terminal is not the recording's to set.
+pub(crate) fn replay(path: &Path, speed: f64) {
+    // the header holds the size and the metadata of the recording, which the playback ignores
+    let output = match File::open(path).and_then(|file| recorded_output(BufReader::new(file))) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("Failed to replay {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    };
+
+    let mut os_input = match get_client_os_input() {
+        Ok(os_input) => os_input,
+        Err(e) => {
+            eprintln!("failed to open terminal:\n{}", e);
+            process::exit(1);
+        }
+    };
+    os_input.set_raw_mode(0);
+    let (quit_sender, quit_receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for byte in io::stdin().bytes() {
+            match byte {
+                Ok(b'q') | Ok(CTRL_C) | Err(_) => break,
+                Ok(_) => {}
+            }
+        }
+        let _ = quit_sender.send(());
+    });
+
+    let started_at = Instant::now();
+    let mut stdout = io::stdout();
+    let mut quit = false;
+    for output in output {
+        let due_at = started_at + replay_delay(output.time, speed);
+        let wait = due_at.saturating_duration_since(Instant::now());
+        match quit_receiver.recv_timeout(wait) {
+            Err(RecvTimeoutError::Timeout) => {}
+            // `q`, `Ctrl-c` or the end of stdin
+            Ok(_) | Err(RecvTimeoutError::Disconnected) => {
+                quit = true;
+                break;
+            }
+        }
+        let _ = stdout.write_all(output.text.as_bytes());
+        let _ = stdout.flush();
+    }
+    if !quit {
+        let _ = quit_receiver.recv();
+    }
+    os_input.unset_raw_mode(0);
+    process::exit(0);
+}
@@ -1,20 +1,41 @@
+use std::io::Write;
 use std::os::unix::fs::FileTypeExt;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{fs, io, process};
+use zellij_utils::nix::{
+    self,
+    sys::{
+        socket::{
+            setsockopt,
+            sockopt::{ReceiveTimeout, SendTimeout},
+        },
+        time::{TimeVal, TimeValLike},
+    },
+};
 use zellij_utils::{
+    cli::CliAction,
     consts::ZELLIJ_SOCK_DIR,
+    input::{actions::Action, layout::SessionSnapshot},
     interprocess::local_socket::LocalSocketStream,
-    ipc::{ClientToServerMsg, IpcSenderWithContext},
+    ipc::{ClientToServerMsg, IpcSenderWithContext, ServerToClientMsg, SessionInfo},
 };
 
-fn get_sessions() -> Result<Vec<String>, io::ErrorKind> {
+/// How long a session has to answer a query before it is reported as unresponsive
+const SESSION_QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn get_sessions() -> Result<Vec<SessionInfo>, io::ErrorKind> {
     match fs::read_dir(&*ZELLIJ_SOCK_DIR) {
         Ok(files) => {
             let mut sessions = Vec::new();
             files.for_each(|file| {
                 let file = file.unwrap();
                 let file_name = file.file_name().into_string().unwrap();
-                if file.file_type().unwrap().is_socket() && assert_socket(&file_name) {
-                    sessions.push(file_name);
+                if file.file_type().unwrap().is_socket() {
+                    if let Some(session_info) = query_session_info(&file_name) {
+                        sessions.push(session_info);
+                    }
                 }
             });
             Ok(sessions)
@@ -29,41 +50,140 @@ fn get_sessions() -> Result<Vec<String>, io::ErrorKind> {
     }
 }
 
-fn assert_socket(name: &str) -> bool {
+fn get_session_names() -> Result<Vec<String>, io::ErrorKind> {
+    get_sessions().map(|sessions| sessions.into_iter().map(|s| s.name).collect())
+}
+
+/// Asks the server behind the socket `name` for its session's metadata. Returns `None` if
+/// the server is not running anymore, in which case its stale socket is removed. A server that
+/// does not answer in time is reported as unresponsive.
+fn query_session_info(name: &str) -> Option<SessionInfo> {
     let path = &*ZELLIJ_SOCK_DIR.join(name);
+    let unresponsive = SessionInfo {
+        name: name.into(),
+        unresponsive: true,
+        ..Default::default()
+    };
     match LocalSocketStream::connect(path) {
         Ok(stream) => {
-            IpcSenderWithContext::new(stream).send(ClientToServerMsg::ClientExited);
-            true
+            if set_timeouts(&stream, SESSION_QUERY_TIMEOUT).is_err() {
+                return Some(unresponsive);
+            }
+            let mut sender = IpcSenderWithContext::new(stream);
+            let mut receiver = sender.get_receiver::<ServerToClientMsg>();
+            let answer = sender
+                .send(ClientToServerMsg::QuerySessionInfo)
+                .and_then(|_| receiver.recv());
+            match answer {
+                Ok((ServerToClientMsg::SessionInfo(session_info), _)) => Some(session_info),
+                _ => Some(unresponsive),
+            }
         }
         Err(e) => {
             if e.kind() == io::ErrorKind::ConnectionRefused {
                 drop(fs::remove_file(path));
-                false
+                None
             } else {
-                true
+                Some(unresponsive)
             }
         }
     }
 }
 
-fn print_sessions(sessions: Vec<String>) {
+/// Keeps reads and writes on the socket from blocking for longer than `timeout`
+fn set_timeouts(stream: &LocalSocketStream, timeout: Duration) -> nix::Result<()> {
+    let timeout = TimeVal::milliseconds(timeout.as_millis() as i64);
+    setsockopt(stream.as_raw_fd(), ReceiveTimeout, &timeout)?;
+    setsockopt(stream.as_raw_fd(), SendTimeout, &timeout)
+}
+
+fn format_elapsed_time(since: u64, now: u64) -> String {
+    let elapsed = now.saturating_sub(since);
+    let (days, hours, minutes, seconds) = (
+        elapsed / 86400,
+        elapsed / 3600 % 24,
+        elapsed / 60 % 60,
+        elapsed % 60,
+    );
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+fn print_sessions(sessions: Vec<SessionInfo>) {
     let curr_session = std::env::var("ZELLIJ_SESSION_NAME").unwrap_or_else(|_| "".into());
-    sessions.iter().for_each(|session| {
-        let suffix = if curr_session == *session {
-            " (current)"
-        } else {
-            ""
-        };
-        println!("{}{}", session, suffix);
-    })
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default();
+    for line in format_sessions(&sessions, &curr_session, now) {
+        println!("{}", line);
+    }
+}
+
+/// One line per session, with its metadata
+fn format_sessions(sessions: &[SessionInfo], curr_session: &str, now: u64) -> Vec<String> {
+    sessions
+        .iter()
+        .map(|session| format_session(session, curr_session, now))
+        .collect()
+}
+
+fn format_session(session: &SessionInfo, curr_session: &str, now: u64) -> String {
+    let suffix = if curr_session == session.name {
+        " (current)"
+    } else {
+        ""
+    };
+    if session.unresponsive {
+        return format!("{}{} [unresponsive]", session.name, suffix);
+    }
+    let tabs: Vec<String> = session
+        .tabs
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| {
+            if tab.is_empty() {
+                format!("Tab #{}", i + 1)
+            } else {
+                tab.clone()
+            }
+        })
+        .collect();
+    format!(
+        "{}{} [created {} ago] [clients: {}] [panes: {}] [tabs: {}] [layout: {}]",
+        session.name,
+        suffix,
+        format_elapsed_time(session.created, now),
+        session.clients,
+        session.panes,
+        tabs.join(", "),
+        session.layout.as_deref().unwrap_or("none"),
+    )
+}
+
+fn print_sessions_json(sessions: Vec<SessionInfo>) {
+    match sessions_to_json(&sessions) {
+        Ok(sessions) => println!("{}", sessions),
+        Err(e) => eprintln!("Error occured: {:?}", e),
+    }
+}
+
+fn sessions_to_json(sessions: &[SessionInfo]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(sessions)
 }
 
 pub(crate) fn get_active_session() -> String {
     match get_sessions() {
         Ok(mut sessions) => {
             if sessions.len() == 1 {
-                return sessions.pop().unwrap();
+                return sessions.pop().unwrap().name;
             }
             if sessions.is_empty() {
                 println!("No active zellij sessions found.");
@@ -77,10 +197,12 @@ pub(crate) fn get_active_session() -> String {
     process::exit(1);
 }
 
-pub(crate) fn list_sessions() {
+pub(crate) fn list_sessions(json: bool) {
     let exit_code = match get_sessions() {
         Ok(sessions) => {
-            if sessions.is_empty() {
+            if json {
+                print_sessions_json(sessions);
+            } else if sessions.is_empty() {
                 println!("No active zellij sessions found.");
             } else {
                 print_sessions(sessions);
@@ -95,8 +217,62 @@ pub(crate) fn list_sessions() {
     process::exit(exit_code);
 }
 
+fn kill_session(name: &str) {
+    let path = &*ZELLIJ_SOCK_DIR.join(name);
+    match LocalSocketStream::connect(path) {
+        Ok(stream) => {
+            if let Err(e) = IpcSenderWithContext::new(stream).send(ClientToServerMsg::KillSession) {
+                eprintln!("Error occured: {:?}", e);
+                process::exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error occured: {:?}", e);
+            process::exit(1);
+        }
+    };
+}
+
+pub(crate) fn kill_session_by_name(name: &str) {
+    assert_session(name);
+    kill_session(name);
+    process::exit(0);
+}
+
+pub(crate) fn kill_all_sessions(yes: bool) {
+    match get_session_names() {
+        Ok(sessions) => {
+            if sessions.is_empty() {
+                println!("No active zellij sessions found.");
+                process::exit(1);
+            }
+            if !yes {
+                print!(
+                    "WARNING: this action will kill all {} active sessions. Continue? [y/N] ",
+                    sessions.len()
+                );
+                let _ = io::stdout().flush();
+                let mut answer = String::new();
+                let _ = io::stdin().read_line(&mut answer);
+                if !answer.trim().eq_ignore_ascii_case("y") {
+                    println!("Abort.");
+                    process::exit(1);
+                }
+            }
+            for session in &sessions {
+                kill_session(session);
+            }
+            process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Error occured: {:?}", e);
+            process::exit(1);
+        }
+    }
+}
+
 pub(crate) fn assert_session(name: &str) {
-    match get_sessions() {
+    match get_session_names() {
         Ok(sessions) => {
             if sessions.iter().any(|s| s == name) {
                 return;
@@ -109,7 +285,7 @@ pub(crate) fn assert_session(name: &str) {
 }
 
 pub(crate) fn assert_session_ne(name: &str) {
-    match get_sessions() {
+    match get_session_names() {
         Ok(sessions) => {
             if sessions.iter().all(|s| s != name) {
                 return;
@@ -120,3 +296,55 @@ pub(crate) fn assert_session_ne(name: &str) {
     };
     process::exit(1);
 }
+
+/// Reads the state the session `name` was last serialized to, so it can be resurrected.
+pub(crate) fn get_session_snapshot(name: &str, data_dir: &Path) -> SessionSnapshot {
+    match SessionSnapshot::from_path(&SessionSnapshot::path(data_dir, name)) {
+        Ok(snapshot) if !snapshot.tabs.is_empty() => snapshot,
+        Ok(_) => {
+            println!("Nothing to resurrect, session {:?} has no tabs.", name);
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Cannot resurrect session {:?}: {}", name, e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Sends the given action to a running session. When no session name is given, the session
+/// this is run from (or the only active one) is used.
+pub(crate) fn send_action_to_session(session_name: Option<String>, cli_action: CliAction) {
+    let session_name = session_name
+        .or_else(|| std::env::var("ZELLIJ_SESSION_NAME").ok())
+        .unwrap_or_else(get_active_session);
+    assert_session(&session_name);
+
+    let path = &*ZELLIJ_SOCK_DIR.join(&session_name);
+    let exit_code = match LocalSocketStream::connect(path) {
+        Ok(stream) => {
+            let mut sender = IpcSenderWithContext::new(stream);
+            let sent = Action::actions_from_cli(cli_action)
+                .into_iter()
+                .map(ClientToServerMsg::Action)
+                .chain(std::iter::once(ClientToServerMsg::ClientExited))
+                .try_for_each(|msg| sender.send(msg));
+            match sent {
+                Ok(_) => 0,
+                Err(e) => {
+                    eprintln!("Error occured: {:?}", e);
+                    1
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Error occured: {:?}", e);
+            1
+        }
+    };
+    process::exit(exit_code);
+}
+
+#[cfg(test)]
+#[path = "./unit/sessions_tests.rs"]
+mod sessions_tests;
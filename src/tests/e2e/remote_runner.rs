@@ -1,7 +1,7 @@
 use zellij_tile::data::Palette;
 use zellij_utils::pane_size::PositionAndSize;
 
-use zellij_server::panes::TerminalPane;
+use zellij_server::panes::{TerminalPane, DEFAULT_SCROLL_BUFFER_SIZE};
 use zellij_utils::{vte, zellij_tile};
 
 use ssh2::Session;
@@ -209,7 +209,13 @@ impl RemoteRunner {
         let sess = ssh_connect();
         let mut channel = sess.channel_session().unwrap();
         let vte_parser = vte::Parser::new();
-        let terminal_output = TerminalPane::new(0, win_size, Palette::default());
+        let terminal_output = TerminalPane::new(
+            0,
+            win_size,
+            Palette::default(),
+            None,
+            DEFAULT_SCROLL_BUFFER_SIZE,
+        );
         setup_remote_environment(&mut channel, win_size);
         start_zellij(&mut channel, session_name.as_ref());
         RemoteRunner {
@@ -237,7 +243,13 @@ impl RemoteRunner {
         let sess = ssh_connect();
         let mut channel = sess.channel_session().unwrap();
         let vte_parser = vte::Parser::new();
-        let terminal_output = TerminalPane::new(0, win_size, Palette::default());
+        let terminal_output = TerminalPane::new(
+            0,
+            win_size,
+            Palette::default(),
+            None,
+            DEFAULT_SCROLL_BUFFER_SIZE,
+        );
         setup_remote_environment(&mut channel, win_size);
         start_zellij_with_layout(
             &mut channel,
@@ -346,10 +358,11 @@ impl RemoteRunner {
             match self.channel.read(&mut buf) {
                 Ok(0) => break,
                 Ok(_count) => {
+                    let mut grid = self.terminal_output.grid();
                     for byte in buf.iter() {
-                        self.vte_parser
-                            .advance(&mut self.terminal_output.grid, *byte);
+                        self.vte_parser.advance(&mut *grid, *byte);
                     }
+                    drop(grid);
                     self.run_next_step();
                     if !self.steps_left() {
                         break;
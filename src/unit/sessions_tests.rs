@@ -0,0 +1,69 @@
+use super::*;
+
+fn session(name: &str) -> SessionInfo {
+    SessionInfo {
+        name: name.into(),
+        created: 1000,
+        tabs: vec!["editor".into(), "".into()],
+        panes: 3,
+        clients: 2,
+        layout: Some("default".into()),
+        unresponsive: false,
+    }
+}
+
+#[test]
+fn sessions_are_listed_with_their_metadata() {
+    let sessions = vec![session("first"), session("second")];
+    assert_eq!(
+        format_sessions(&sessions, "second", 1000 + 3725),
+        vec![
+            "first [created 1h 2m ago] [clients: 2] [panes: 3] [tabs: editor, Tab #2] [layout: default]",
+            "second (current) [created 1h 2m ago] [clients: 2] [panes: 3] [tabs: editor, Tab #2] [layout: default]",
+        ]
+    );
+}
+
+#[test]
+fn unresponsive_sessions_are_listed_by_name() {
+    let sessions = vec![SessionInfo {
+        name: "stuck".into(),
+        unresponsive: true,
+        ..Default::default()
+    }];
+    assert_eq!(
+        format_sessions(&sessions, "", 1000),
+        vec!["stuck [unresponsive]"]
+    );
+}
+
+#[test]
+fn elapsed_time_is_formatted_with_its_two_largest_units() {
+    assert_eq!(format_elapsed_time(0, 59), "59s");
+    assert_eq!(format_elapsed_time(0, 61), "1m 1s");
+    assert_eq!(format_elapsed_time(0, 3 * 3600 + 120), "3h 2m");
+    assert_eq!(format_elapsed_time(0, 2 * 86400 + 3600), "2d 1h");
+    // a clock that went backwards
+    assert_eq!(format_elapsed_time(100, 0), "0s");
+}
+
+#[test]
+fn sessions_are_listed_as_json() {
+    let sessions = vec![session("first")];
+    let json = sessions_to_json(&sessions).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        parsed,
+        serde_json::json!([{
+            "name": "first",
+            "created": 1000,
+            "tabs": ["editor", ""],
+            "panes": 3,
+            "clients": 2,
+            "layout": "default",
+            "unresponsive": false,
+        }])
+    );
+    let deserialized: Vec<SessionInfo> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, sessions);
+}
@@ -31,6 +31,7 @@ struct InputHandler {
     send_client_instructions: SenderWithContext<ClientInstruction>,
     should_exit: bool,
     pasting: bool,
+    paste_buffer: Vec<u8>,
 }
 
 impl InputHandler {
@@ -52,6 +53,7 @@ impl InputHandler {
             send_client_instructions,
             should_exit: false,
             pasting: false,
+            paste_buffer: vec![],
         }
     }
 
@@ -86,13 +88,20 @@ impl InputHandler {
                         termion::event::Event::Unsupported(unsupported_key) => {
                             // we have to do this because of a bug in termion
                             // this should be a key event and not an unsupported event
-                            if unsupported_key == alt_left_bracket {
+                            if let Some(mouse_event) =
+                                MouseEvent::from_unsupported(&unsupported_key)
+                            {
+                                self.handle_mouse_event(&mouse_event);
+                            } else if unsupported_key == alt_left_bracket {
                                 let key = Key::Alt('[');
                                 self.handle_key(&key, raw_bytes);
                             } else if unsupported_key == bracketed_paste_start {
                                 self.pasting = true;
                             } else if unsupported_key == bracketed_paste_end {
                                 self.pasting = false;
+                                self.end_paste();
+                            } else if self.pasting {
+                                self.paste_buffer.extend(raw_bytes);
                             } else {
                                 // this is a hack because termion doesn't recognize certain keys
                                 // in this case we just forward it to the terminal
@@ -114,13 +123,9 @@ impl InputHandler {
     fn handle_key(&mut self, key: &Key, raw_bytes: Vec<u8>) {
         let keybinds = &self.config.keybinds;
         if self.pasting {
-            // we're inside a paste block, if we're in a mode that allows sending text to the
-            // terminal, send all text directly without interpreting it
-            // otherwise, just discard the input
-            if self.mode == InputMode::Normal || self.mode == InputMode::Locked {
-                let action = Action::Write(raw_bytes);
-                self.dispatch_action(action);
-            }
+            // we're inside a paste block, collect all text without interpreting it so that
+            // it can be sent as a whole once the paste ends
+            self.paste_buffer.extend(raw_bytes);
         } else {
             for action in Keybinds::key_to_actions(key, raw_bytes, &self.mode, keybinds) {
                 let should_exit = self.dispatch_action(action);
@@ -130,6 +135,16 @@ impl InputHandler {
             }
         }
     }
+    fn end_paste(&mut self) {
+        let pasted_bytes = std::mem::take(&mut self.paste_buffer);
+        // if we're in a mode that allows sending text to the terminal, send the pasted text,
+        // otherwise just discard it
+        if !pasted_bytes.is_empty()
+            && (self.mode == InputMode::Normal || self.mode == InputMode::Locked)
+        {
+            self.dispatch_action(Action::Paste(pasted_bytes));
+        }
+    }
     fn handle_mouse_event(&mut self, mouse_event: &MouseEvent) {
         match *mouse_event {
             MouseEvent::Press(button, point) => match button {
@@ -142,7 +157,12 @@ impl InputHandler {
                 MouseButton::Left => {
                     self.dispatch_action(Action::LeftClick(point));
                 }
-                _ => {}
+                MouseButton::Right => {
+                    self.dispatch_action(Action::RightClick(point));
+                }
+                MouseButton::Middle => {
+                    self.dispatch_action(Action::MiddleClick(point));
+                }
             },
             MouseEvent::Release(point) => {
                 self.dispatch_action(Action::MouseRelease(point));
@@ -152,6 +172,9 @@ impl InputHandler {
                 self.os_input
                     .start_action_repeater(Action::MouseHold(point));
             }
+            MouseEvent::Motion(point) => {
+                self.dispatch_action(Action::MouseMotion(point));
+            }
         }
     }
 
@@ -19,8 +19,13 @@ use zellij_utils::{
     channels::{self, ChannelWithContext, SenderWithContext},
     consts::{SESSION_NAME, ZELLIJ_IPC_PIPE},
     errors::{ClientContext, ContextType, ErrorInstruction},
-    input::{actions::Action, config::Config, layout::Layout, options::Options},
-    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
+    input::{
+        actions::Action,
+        config::Config,
+        layout::{Layout, SessionSnapshot},
+        options::Options,
+    },
+    ipc::{ClientAttributes, ClientToServerMsg, ClientType, ExitReason, ServerToClientMsg},
 };
 
 /// Instructions related to the client-side application
@@ -38,6 +43,7 @@ impl From<ServerToClientMsg> for ClientInstruction {
             ServerToClientMsg::Exit(e) => ClientInstruction::Exit(e),
             ServerToClientMsg::Render(buffer) => ClientInstruction::Render(buffer),
             ServerToClientMsg::UnblockInputThread => ClientInstruction::UnblockInputThread,
+            ServerToClientMsg::SessionInfo(_) => unreachable!(),
         }
     }
 }
@@ -78,8 +84,9 @@ fn spawn_server(socket_path: &Path) -> io::Result<()> {
 
 #[derive(Debug, Clone)]
 pub enum ClientInfo {
-    Attach(String, bool, Options),
+    Attach(String, ClientType, bool, Options),
     New(String),
+    Resurrect(String, SessionSnapshot),
 }
 
 pub fn start_client(
@@ -118,14 +125,15 @@ pub fn start_client(
     let client_attributes = ClientAttributes {
         position_and_size: full_screen_ws,
         palette,
+        character_cell_size: os_input.get_character_cell_size_using_fd(0),
     };
 
     let first_msg = match info {
-        ClientInfo::Attach(name, force, config_options) => {
+        ClientInfo::Attach(name, client_type, force, config_options) => {
             SESSION_NAME.set(name).unwrap();
             std::env::set_var(&"ZELLIJ_SESSION_NAME", SESSION_NAME.get().unwrap());
 
-            ClientToServerMsg::AttachClient(client_attributes, force, config_options)
+            ClientToServerMsg::AttachClient(client_attributes, client_type, force, config_options)
         }
         ClientInfo::New(name) => {
             SESSION_NAME.set(name).unwrap();
@@ -138,6 +146,21 @@ pub fn start_client(
                 Box::new(opts),
                 Box::new(config_options.clone()),
                 layout,
+                None,
+            )
+        }
+        ClientInfo::Resurrect(name, snapshot) => {
+            SESSION_NAME.set(name).unwrap();
+            std::env::set_var(&"ZELLIJ_SESSION_NAME", SESSION_NAME.get().unwrap());
+
+            spawn_server(&*ZELLIJ_IPC_PIPE).unwrap();
+
+            ClientToServerMsg::NewClient(
+                client_attributes,
+                Box::new(opts),
+                Box::new(config_options.clone()),
+                layout,
+                Some(snapshot),
             )
         }
     };
@@ -198,6 +221,7 @@ pub fn start_client(
                         move || {
                             os_api.send_to_server(ClientToServerMsg::TerminalResize(
                                 os_api.get_terminal_size_using_fd(0),
+                                os_api.get_character_cell_size_using_fd(0),
                             ));
                         }
                     }),
@@ -15,7 +15,7 @@ use zellij_tile::data::Palette;
 use zellij_utils::{
     errors::ErrorContext,
     ipc::{ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, ServerToClientMsg},
-    pane_size::PositionAndSize,
+    pane_size::{PositionAndSize, SizeInPixels},
     shared::default_palette,
 };
 
@@ -35,7 +35,7 @@ fn unset_raw_mode(pid: RawFd, orig_termios: termios::Termios) {
     };
 }
 
-pub(crate) fn get_terminal_size_using_fd(fd: RawFd) -> PositionAndSize {
+fn get_winsize_using_fd(fd: RawFd) -> Winsize {
     // TODO: do this with the nix ioctl
     use libc::ioctl;
     use libc::TIOCGWINSZ;
@@ -54,7 +54,11 @@ pub(crate) fn get_terminal_size_using_fd(fd: RawFd) -> PositionAndSize {
     unsafe {
         ioctl(fd, TIOCGWINSZ.into(), &mut winsize)
     };
-    PositionAndSize::from(winsize)
+    winsize
+}
+
+pub(crate) fn get_terminal_size_using_fd(fd: RawFd) -> PositionAndSize {
+    PositionAndSize::from(get_winsize_using_fd(fd))
 }
 
 #[derive(Clone)]
@@ -70,6 +74,9 @@ pub struct ClientOsInputOutput {
 pub trait ClientOsApi: Send + Sync {
     /// Returns the size of the terminal associated to file descriptor `fd`.
     fn get_terminal_size_using_fd(&self, fd: RawFd) -> PositionAndSize;
+    /// Returns the size in pixels of a character cell of the terminal associated to file
+    /// descriptor `fd`, if the terminal reports it.
+    fn get_character_cell_size_using_fd(&self, fd: RawFd) -> Option<SizeInPixels>;
     /// Set the terminal associated to file descriptor `fd` to
     /// [raw mode](https://en.wikipedia.org/wiki/Terminal_mode).
     fn set_raw_mode(&mut self, fd: RawFd);
@@ -101,6 +108,9 @@ impl ClientOsApi for ClientOsInputOutput {
     fn get_terminal_size_using_fd(&self, fd: RawFd) -> PositionAndSize {
         get_terminal_size_using_fd(fd)
     }
+    fn get_character_cell_size_using_fd(&self, fd: RawFd) -> Option<SizeInPixels> {
+        SizeInPixels::character_cell_size(&get_winsize_using_fd(fd))
+    }
     fn set_raw_mode(&mut self, fd: RawFd) {
         into_raw_mode(fd);
     }
@@ -125,7 +135,9 @@ impl ClientOsApi for ClientOsInputOutput {
         Box::new(stdout)
     }
     fn send_to_server(&self, msg: ClientToServerMsg) {
-        self.send_instructions_to_server
+        // a server that went away is noticed when receiving from it
+        let _ = self
+            .send_instructions_to_server
             .lock()
             .unwrap()
             .as_mut()
@@ -139,6 +151,7 @@ impl ClientOsApi for ClientOsInputOutput {
             .as_mut()
             .unwrap()
             .recv()
+            .expect("failed to receive message from server")
     }
     fn handle_signals(&self, sigwinch_cb: Box<dyn Fn()>, quit_cb: Box<dyn Fn()>) {
         let mut signals = Signals::new(&[SIGWINCH, SIGTERM, SIGINT, SIGQUIT, SIGHUP]).unwrap();
@@ -191,6 +204,12 @@ impl ClientOsApi for ClientOsInputOutput {
         let mut mouse_term = self.mouse_term.lock().unwrap();
         if mouse_term.is_none() {
             *mouse_term = Some(termion::input::MouseTerminal::from(std::io::stdout()));
+            // termion tracks the mouse only while a button is held, the panes that track all of
+            // its motion need it tracked always. This has to come after the modes termion sets,
+            // which would replace it.
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(b"\x1b[?1003h");
+            let _ = stdout.flush();
         }
     }
 
@@ -198,6 +217,9 @@ impl ClientOsApi for ClientOsInputOutput {
         let mut mouse_term = self.mouse_term.lock().unwrap();
         if mouse_term.is_some() {
             *mouse_term = None;
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(b"\x1b[?1003l");
+            let _ = stdout.flush();
         }
     }
 
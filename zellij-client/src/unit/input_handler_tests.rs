@@ -2,7 +2,7 @@ use super::input_loop;
 use zellij_utils::input::actions::{Action, Direction};
 use zellij_utils::input::config::Config;
 use zellij_utils::input::options::Options;
-use zellij_utils::pane_size::PositionAndSize;
+use zellij_utils::pane_size::{PositionAndSize, SizeInPixels};
 use zellij_utils::zellij_tile::data::Palette;
 
 use crate::{os_input_output::ClientOsApi, ClientInstruction, CommandIsExecuting};
@@ -96,6 +96,9 @@ impl ClientOsApi for FakeClientOsApi {
     fn get_terminal_size_using_fd(&self, _fd: RawFd) -> PositionAndSize {
         unimplemented!()
     }
+    fn get_character_cell_size_using_fd(&self, _fd: RawFd) -> Option<SizeInPixels> {
+        unimplemented!()
+    }
     fn set_raw_mode(&mut self, _fd: RawFd) {
         unimplemented!()
     }
@@ -259,7 +262,7 @@ pub fn bracketed_paste() {
         default_mode,
     ));
     let expected_actions_sent_to_server = vec![
-        Action::Write(commands::MOVE_FOCUS_LEFT_IN_NORMAL_MODE.to_vec()), // keys were directly written to server and not interpreted
+        Action::Paste(commands::MOVE_FOCUS_LEFT_IN_NORMAL_MODE.to_vec()), // keys were directly pasted to server and not interpreted
         Action::Quit,
     ];
     let received_actions = extract_actions_sent_to_server(events_sent_to_server);
@@ -0,0 +1,185 @@
+//! Recordings of panes in the [asciicast v2](https://github.com/asciinema/asciinema/blob/develop/doc/asciicast-v2.md)
+//! format of asciinema, and their playback.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{sync_channel, SyncSender, TrySendError},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+use zellij_utils::cli::MIN_REPLAY_SPEED;
+
+/// How many reads of the output of a pane can wait to be recorded, the recording stops once
+/// writing it falls further behind
+const MAX_PENDING_EVENTS: usize = 256;
+/// How far into a replay its events can be played, later ones are played at this point
+const MAX_REPLAY_TIME: f64 = 24.0 * 60.0 * 60.0;
+
+pub(crate) struct AsciicastRecorder {
+    file: File,
+    /// The start of a character split between two reads of the output
+    incomplete_character: Vec<u8>,
+}
+
+impl AsciicastRecorder {
+    pub fn new(path: &Path, columns: u16, rows: u16) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_secs())
+            .unwrap_or(0);
+        let header = json!({
+            "version": 2,
+            "width": columns,
+            "height": rows,
+            "timestamp": timestamp,
+        });
+        writeln!(file, "{}", header)?;
+        Ok(AsciicastRecorder {
+            file,
+            incomplete_character: vec![],
+        })
+    }
+    /// Writes the output printed `time` seconds into the recording
+    pub fn output(&mut self, time: f64, bytes: &[u8]) -> io::Result<()> {
+        let mut output = std::mem::take(&mut self.incomplete_character);
+        output.extend_from_slice(bytes);
+        // events hold text, so a character that isn't complete yet waits for the next read
+        let complete_length = match std::str::from_utf8(&output) {
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            _ => output.len(),
+        };
+        self.incomplete_character = output.split_off(complete_length);
+        if output.is_empty() {
+            return Ok(());
+        }
+        self.write_event(time, "o", &String::from_utf8_lossy(&output))
+    }
+    pub fn resize(&mut self, time: f64, columns: u16, rows: u16) -> io::Result<()> {
+        self.write_event(time, "r", &format!("{}x{}", columns, rows))
+    }
+    fn write_event(&mut self, time: f64, code: &str, data: &str) -> io::Result<()> {
+        writeln!(self.file, "{}", json!([time, code, data]))
+    }
+}
+
+/// An event of a pane, with the seconds since its recording started
+pub(crate) enum RecordedEvent {
+    Output(f64, Vec<u8>),
+    Resize(f64, u16, u16),
+}
+
+/// A recording of a pane, written by a thread of its own so that the task reading the output of
+/// the pane is never held up by it
+pub(crate) struct PaneRecording {
+    pub path: PathBuf,
+    sender: SyncSender<RecordedEvent>,
+    started_at: Instant,
+    /// The size of the pane as last recorded
+    size: (u16, u16),
+}
+
+impl PaneRecording {
+    /// Starts recording a pane of `columns` by `rows` to `path`. The writing thread calls
+    /// `on_failure` if writing the recording fails, after which it takes no more events.
+    pub fn new(
+        path: &Path,
+        columns: u16,
+        rows: u16,
+        on_failure: impl FnOnce(io::Error) + Send + 'static,
+    ) -> io::Result<Self> {
+        let mut recorder = AsciicastRecorder::new(path, columns, rows)?;
+        let (sender, receiver) = sync_channel(MAX_PENDING_EVENTS);
+        thread::spawn(move || {
+            // ends once the recording is dropped, or once writing fails
+            let failure = receiver.iter().find_map(|event| {
+                let written = match event {
+                    RecordedEvent::Output(time, bytes) => recorder.output(time, &bytes),
+                    RecordedEvent::Resize(time, columns, rows) => {
+                        recorder.resize(time, columns, rows)
+                    }
+                };
+                written.err()
+            });
+            // the recording takes no more events from here on
+            drop(receiver);
+            if let Some(e) = failure {
+                on_failure(e);
+            }
+        });
+        Ok(PaneRecording {
+            path: path.to_path_buf(),
+            sender,
+            started_at: Instant::now(),
+            size: (columns, rows),
+        })
+    }
+    /// Hands the output of the pane over to the writing thread without waiting for it, after a
+    /// resize if the pane is no longer of the `(columns, rows)` last recorded. Resizes are
+    /// recorded along with the output that follows them, which the programs print as they redraw
+    /// themselves. Fails with `TrySendError::Full` if writing falls too far behind and with
+    /// `TrySendError::Disconnected` if it failed.
+    pub fn record(
+        &mut self,
+        bytes: &[u8],
+        size: Option<(u16, u16)>,
+    ) -> Result<(), TrySendError<RecordedEvent>> {
+        let time = self.started_at.elapsed().as_secs_f64();
+        if let Some((columns, rows)) = size.filter(|size| *size != self.size) {
+            self.sender
+                .try_send(RecordedEvent::Resize(time, columns, rows))?;
+            self.size = (columns, rows);
+        }
+        self.sender
+            .try_send(RecordedEvent::Output(time, bytes.to_vec()))
+    }
+}
+
+/// Text a recorded pane printed, with the seconds since its recording started
+#[derive(Debug, PartialEq)]
+pub struct RecordedOutput {
+    pub time: f64,
+    pub text: String,
+}
+
+/// Reads the output of a recording, failing if it does not start with the header of an asciicast
+/// v2 file. The other events, and the lines that aren't events, are skipped.
+pub fn recorded_output<R: BufRead>(
+    recording: R,
+) -> io::Result<impl Iterator<Item = RecordedOutput>> {
+    let mut lines = recording.lines();
+    let header: Value = match lines.next() {
+        Some(line) => serde_json::from_str(&line?)?,
+        None => Value::Null,
+    };
+    if header["version"] != 2 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an asciicast v2 recording",
+        ));
+    }
+    Ok(lines.take_while(Result::is_ok).filter_map(|line| {
+        let (time, code, text): (f64, String, String) = serde_json::from_str(&line.ok()?).ok()?;
+        if code == "o" {
+            Some(RecordedOutput { time, text })
+        } else {
+            None
+        }
+    }))
+}
+
+/// How long into a replay at `speed` an event `time` seconds into the recording is played.
+/// Speeds below `MIN_REPLAY_SPEED` are played at it, and times that make no sense at the start.
+pub fn replay_delay(time: f64, speed: f64) -> Duration {
+    let seconds = time / speed.max(MIN_REPLAY_SPEED);
+    // `max` and `min` also get rid of NaN
+    Duration::from_secs_f64(seconds.max(0.0).min(MAX_REPLAY_TIME))
+}
+
+#[cfg(test)]
+#[path = "./unit/asciicast_tests.rs"]
+mod asciicast_tests;
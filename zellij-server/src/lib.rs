@@ -1,8 +1,10 @@
+pub mod asciicast;
 pub mod os_input_output;
 pub mod panes;
 pub mod tab;
 
 mod logging_pipe;
+mod pane_pipe;
 mod pty;
 mod route;
 mod screen;
@@ -13,9 +15,11 @@ mod wasm_vm;
 use log::info;
 use zellij_utils::zellij_tile;
 
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use wasmer::Store;
 use zellij_tile::data::{Event, Palette, PluginCapabilities};
 
@@ -34,37 +38,40 @@ use zellij_utils::{
     input::{
         command::{RunCommand, TerminalAction},
         get_mode_info,
-        layout::Layout,
+        layout::{Layout, SessionSnapshot},
         options::Options,
     },
-    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
+    ipc::{ClientAttributes, ClientId, ClientType, ExitReason, ServerToClientMsg, SessionInfo},
     setup::get_default_data_dir,
 };
 
+/// How often a session is written to disk, so it can be resurrected
+const SESSION_SERIALIZATION_INTERVAL: Duration = Duration::from_secs(60);
+/// How many instructions from the pty thread and the tasks reading the ptys can wait for the
+/// screen thread. Sending more waits until it catches up, which pauses the reading of a pane
+/// flooded with output, so that its program is slowed down instead of the whole session.
+const MAX_PENDING_SCREEN_INSTRUCTIONS: usize = 10;
+
 /// Instructions related to server-side application
 #[derive(Debug, Clone)]
 pub(crate) enum ServerInstruction {
-    NewClient(ClientAttributes, Box<CliArgs>, Box<Options>, Option<Layout>),
+    NewClient(
+        ClientAttributes,
+        Box<CliArgs>,
+        Box<Options>,
+        Option<Layout>,
+        Option<SessionSnapshot>,
+        ClientId,
+    ),
     Render(Option<String>),
     UnblockInputThread,
-    ClientExit,
+    ClientExit(ClientId),
+    RemoveClient(ClientId),
     Error(String),
-    DetachSession,
-    AttachClient(ClientAttributes, bool, Options),
-}
-
-impl From<ClientToServerMsg> for ServerInstruction {
-    fn from(instruction: ClientToServerMsg) -> Self {
-        match instruction {
-            ClientToServerMsg::NewClient(attrs, opts, options, layout) => {
-                ServerInstruction::NewClient(attrs, opts, options, layout)
-            }
-            ClientToServerMsg::AttachClient(attrs, force, options) => {
-                ServerInstruction::AttachClient(attrs, force, options)
-            }
-            _ => unreachable!(),
-        }
-    }
+    DetachSession(ClientId),
+    AttachClient(ClientAttributes, ClientType, bool, Options, ClientId),
+    SessionInfo(ClientId, SessionInfo),
+    KillSession,
 }
 
 impl From<&ServerInstruction> for ServerContext {
@@ -73,10 +80,13 @@ impl From<&ServerInstruction> for ServerContext {
             ServerInstruction::NewClient(..) => ServerContext::NewClient,
             ServerInstruction::Render(_) => ServerContext::Render,
             ServerInstruction::UnblockInputThread => ServerContext::UnblockInputThread,
-            ServerInstruction::ClientExit => ServerContext::ClientExit,
+            ServerInstruction::ClientExit(..) => ServerContext::ClientExit,
+            ServerInstruction::RemoveClient(..) => ServerContext::RemoveClient,
             ServerInstruction::Error(_) => ServerContext::Error,
-            ServerInstruction::DetachSession => ServerContext::DetachSession,
+            ServerInstruction::DetachSession(..) => ServerContext::DetachSession,
             ServerInstruction::AttachClient(..) => ServerContext::AttachClient,
+            ServerInstruction::SessionInfo(..) => ServerContext::SessionInfo,
+            ServerInstruction::KillSession => ServerContext::KillSession,
         }
     }
 }
@@ -92,6 +102,8 @@ pub(crate) struct SessionMetaData {
     pub capabilities: PluginCapabilities,
     pub palette: Palette,
     pub default_shell: Option<TerminalAction>,
+    pub created: SystemTime,
+    pub layout_name: Option<String>,
     screen_thread: Option<thread::JoinHandle<()>>,
     pty_thread: Option<thread::JoinHandle<()>>,
     wasm_thread: Option<thread::JoinHandle<()>>,
@@ -108,11 +120,28 @@ impl Drop for SessionMetaData {
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub(crate) enum SessionState {
-    Attached,
-    Detached,
-    Uninitialized,
+/// Keeps track of the clients currently attached to the session.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SessionState {
+    clients: HashSet<ClientId>,
+}
+
+impl SessionState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn new_client(&mut self, client_id: ClientId) {
+        self.clients.insert(client_id);
+    }
+    pub fn remove_client(&mut self, client_id: ClientId) {
+        self.clients.remove(&client_id);
+    }
+    pub fn client_ids(&self) -> Vec<ClientId> {
+        self.clients.iter().copied().collect()
+    }
+    pub fn has_clients(&self) -> bool {
+        !self.clients.is_empty()
+    }
 }
 
 pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
@@ -130,7 +159,12 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
     let (to_server, server_receiver): ChannelWithContext<ServerInstruction> = channels::bounded(50);
     let to_server = SenderWithContext::new(to_server);
     let session_data: Arc<RwLock<Option<SessionMetaData>>> = Arc::new(RwLock::new(None));
-    let session_state = Arc::new(RwLock::new(SessionState::Uninitialized));
+    let session_state = Arc::new(RwLock::new(SessionState::new()));
+    let session_name = socket_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut snapshot_path = None;
 
     std::panic::set_hook({
         use zellij_utils::errors::handle_panic;
@@ -140,7 +174,8 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
         })
     });
 
-    let thread_handles = Arc::new(Mutex::new(Vec::new()));
+    // the routers of the connected clients, each removes its own handle once it ends
+    let thread_handles = Arc::new(Mutex::new(HashMap::new()));
 
     let _ = thread::Builder::new()
         .name("server_listener".to_string())
@@ -151,7 +186,6 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
 
             let os_input = os_input.clone();
             let session_data = session_data.clone();
-            let session_state = session_state.clone();
             let to_server = to_server.clone();
             let socket_path = socket_path.clone();
             let thread_handles = thread_handles.clone();
@@ -159,33 +193,41 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                 drop(std::fs::remove_file(&socket_path));
                 let listener = LocalSocketListener::bind(&*socket_path).unwrap();
                 set_permissions(&socket_path).unwrap();
+                // never wraps, so an id is never given to two clients
+                let mut next_client_id: ClientId = 0;
                 for stream in listener.incoming() {
                     match stream {
                         Ok(stream) => {
-                            let mut os_input = os_input.clone();
-                            os_input.update_receiver(stream);
+                            let os_input = os_input.clone();
+                            let client_id = next_client_id;
+                            next_client_id += 1;
+                            let receiver = os_input.new_client(client_id, stream);
                             let session_data = session_data.clone();
-                            let session_state = session_state.clone();
                             let to_server = to_server.clone();
-                            thread_handles.lock().unwrap().push(
-                                thread::Builder::new()
-                                    .name("server_router".to_string())
-                                    .spawn({
-                                        let session_data = session_data.clone();
-                                        let os_input = os_input.clone();
-                                        let to_server = to_server.clone();
-
-                                        move || {
-                                            route_thread_main(
-                                                session_data,
-                                                session_state,
-                                                os_input,
-                                                to_server,
-                                            )
-                                        }
-                                    })
-                                    .unwrap(),
-                            );
+                            // held until the handle is in, so that the router can't remove it
+                            // before that
+                            let mut handles = thread_handles.lock().unwrap();
+                            let handle = thread::Builder::new()
+                                .name("server_router".to_string())
+                                .spawn({
+                                    let session_data = session_data.clone();
+                                    let os_input = os_input.clone();
+                                    let to_server = to_server.clone();
+                                    let thread_handles = thread_handles.clone();
+
+                                    move || {
+                                        route_thread_main(
+                                            session_data,
+                                            os_input,
+                                            to_server,
+                                            receiver,
+                                            client_id,
+                                        );
+                                        thread_handles.lock().unwrap().remove(&client_id);
+                                    }
+                                })
+                                .unwrap();
+                            handles.insert(client_id, handle);
                         }
                         Err(err) => {
                             panic!("err {:?}", err);
@@ -199,7 +241,16 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
         let (instruction, mut err_ctx) = server_receiver.recv().unwrap();
         err_ctx.add_call(ContextType::IPCServer((&instruction).into()));
         match instruction {
-            ServerInstruction::NewClient(client_attributes, opts, config_options, layout) => {
+            ServerInstruction::NewClient(
+                client_attributes,
+                opts,
+                config_options,
+                layout,
+                snapshot,
+                client_id,
+            ) => {
+                let data_dir = opts.data_dir.clone().unwrap_or_else(get_default_data_dir);
+                snapshot_path = Some(SessionSnapshot::path(&data_dir, &session_name));
                 let session = init_session(
                     os_input.clone(),
                     opts,
@@ -208,9 +259,10 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     client_attributes,
                     session_state.clone(),
                     layout,
+                    &session_name,
                 );
                 *session_data.write().unwrap() = Some(session);
-                *session_state.write().unwrap() = SessionState::Attached;
+                session_state.write().unwrap().new_client(client_id);
 
                 let default_shell = config_options.default_shell.map(|shell| {
                     TerminalAction::RunCommand(RunCommand {
@@ -219,22 +271,41 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     })
                 });
 
+                let pty_instruction = match snapshot {
+                    Some(snapshot) => PtyInstruction::ResurrectSession(snapshot, default_shell),
+                    None => PtyInstruction::StartSession(default_shell),
+                };
                 session_data
                     .read()
                     .unwrap()
                     .as_ref()
                     .unwrap()
                     .senders
-                    .send_to_pty(PtyInstruction::NewTab(default_shell.clone()))
+                    .send_to_pty(pty_instruction)
                     .unwrap();
             }
-            ServerInstruction::AttachClient(attrs, _, options) => {
-                *session_state.write().unwrap() = SessionState::Attached;
+            ServerInstruction::AttachClient(attrs, client_type, force, options, client_id) => {
+                if force {
+                    detach_other_clients(&*os_input, &session_state, client_id);
+                }
+                session_state.write().unwrap().new_client(client_id);
                 let rlock = session_data.read().unwrap();
                 let session_data = rlock.as_ref().unwrap();
+                if client_type == ClientType::Reader {
+                    // a spectator should not resize the session or change its mode,
+                    // it only needs a full frame to start from
+                    session_data
+                        .senders
+                        .send_to_screen(ScreenInstruction::ForceRender)
+                        .unwrap();
+                    continue;
+                }
                 session_data
                     .senders
-                    .send_to_screen(ScreenInstruction::TerminalResize(attrs.position_and_size))
+                    .send_to_screen(ScreenInstruction::TerminalResize(
+                        attrs.position_and_size,
+                        attrs.character_cell_size,
+                    ))
                     .unwrap();
                 let default_mode = options.default_mode.unwrap_or_default();
                 let mode_info =
@@ -252,49 +323,133 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     .unwrap();
             }
             ServerInstruction::UnblockInputThread => {
-                if *session_state.read().unwrap() == SessionState::Attached {
-                    os_input.send_to_client(ServerToClientMsg::UnblockInputThread);
+                send_to_all_clients(
+                    &*os_input,
+                    &session_state,
+                    ServerToClientMsg::UnblockInputThread,
+                );
+            }
+            ServerInstruction::ClientExit(client_id) => {
+                let _ =
+                    os_input.send_to_client(client_id, ServerToClientMsg::Exit(ExitReason::Normal));
+                remove_client(&*os_input, &session_state, client_id);
+                // the session only goes away once its last client has quit
+                if !session_state.read().unwrap().has_clients() {
+                    *session_data.write().unwrap() = None;
+                    break;
                 }
             }
-            ServerInstruction::ClientExit => {
-                *session_data.write().unwrap() = None;
-                os_input.send_to_client(ServerToClientMsg::Exit(ExitReason::Normal));
-                break;
+            ServerInstruction::RemoveClient(client_id) => {
+                remove_client(&*os_input, &session_state, client_id);
             }
-            ServerInstruction::DetachSession => {
-                *session_state.write().unwrap() = SessionState::Detached;
-                os_input.send_to_client(ServerToClientMsg::Exit(ExitReason::Normal));
-                os_input.remove_client_sender();
+            ServerInstruction::DetachSession(client_id) => {
+                let _ =
+                    os_input.send_to_client(client_id, ServerToClientMsg::Exit(ExitReason::Normal));
+                remove_client(&*os_input, &session_state, client_id);
             }
             ServerInstruction::Render(output) => {
-                if *session_state.read().unwrap() == SessionState::Attached {
-                    // Here output is of the type Option<String> sent by screen thread.
-                    // If `Some(_)`- unwrap it and forward it to the client to render.
-                    // If `None`- Send an exit instruction. This is the case when the user closes last Tab/Pane.
-                    if let Some(op) = output {
-                        os_input.send_to_client(ServerToClientMsg::Render(op));
-                    } else {
-                        os_input.send_to_client(ServerToClientMsg::Exit(ExitReason::Normal));
-                        break;
-                    }
+                // Here output is of the type Option<String> sent by screen thread.
+                // If `Some(_)`- unwrap it and forward it to the clients to render.
+                // If `None`- Send an exit instruction. This is the case when the user closes last Tab/Pane.
+                if let Some(op) = output {
+                    send_to_all_clients(&*os_input, &session_state, ServerToClientMsg::Render(op));
+                } else {
+                    send_to_all_clients(
+                        &*os_input,
+                        &session_state,
+                        ServerToClientMsg::Exit(ExitReason::Normal),
+                    );
+                    break;
                 }
             }
-            ServerInstruction::Error(backtrace) => {
-                if *session_state.read().unwrap() == SessionState::Attached {
-                    os_input.send_to_client(ServerToClientMsg::Exit(ExitReason::Error(backtrace)));
+            ServerInstruction::SessionInfo(client_id, mut session_info) => {
+                session_info.name = session_name.clone();
+                session_info.clients = session_state.read().unwrap().client_ids().len();
+                if let Some(session_data) = session_data.read().unwrap().as_ref() {
+                    session_info.created = session_data
+                        .created
+                        .duration_since(UNIX_EPOCH)
+                        .map(|created| created.as_secs())
+                        .unwrap_or_default();
+                    session_info.layout = session_data.layout_name.clone();
                 }
+                let _ = os_input
+                    .send_to_client(client_id, ServerToClientMsg::SessionInfo(session_info));
+                os_input.remove_client(client_id);
+            }
+            ServerInstruction::KillSession => {
+                send_to_all_clients(
+                    &*os_input,
+                    &session_state,
+                    ServerToClientMsg::Exit(ExitReason::Normal),
+                );
+                *session_data.write().unwrap() = None;
+                break;
+            }
+            ServerInstruction::Error(backtrace) => {
+                send_to_all_clients(
+                    &*os_input,
+                    &session_state,
+                    ServerToClientMsg::Exit(ExitReason::Error(backtrace)),
+                );
+                // keep the last snapshot around, so the session can be resurrected
+                snapshot_path = None;
                 break;
             }
         }
     }
-    thread_handles
-        .lock()
-        .unwrap()
-        .drain(..)
-        .for_each(|h| drop(h.join()));
+    // the routers remove their handles as they end, so the lock can't be held while joining them
+    let handles: Vec<_> = thread_handles.lock().unwrap().drain().collect();
+    handles.into_iter().for_each(|(_, h)| drop(h.join()));
+    if let Some(snapshot_path) = snapshot_path {
+        // the session was ended on purpose, there is nothing to resurrect
+        *session_data.write().unwrap() = None;
+        drop(std::fs::remove_file(snapshot_path));
+    }
     drop(std::fs::remove_file(&socket_path));
 }
 
+fn remove_client(
+    os_input: &dyn ServerOsApi,
+    session_state: &Arc<RwLock<SessionState>>,
+    client_id: ClientId,
+) {
+    session_state.write().unwrap().remove_client(client_id);
+    os_input.remove_client(client_id);
+}
+
+/// Sends `msg` to every attached client, removing the ones that went away without telling
+fn send_to_all_clients(
+    os_input: &dyn ServerOsApi,
+    session_state: &Arc<RwLock<SessionState>>,
+    msg: ServerToClientMsg,
+) {
+    let client_ids = session_state.read().unwrap().client_ids();
+    for client_id in client_ids {
+        if let Err(e) = os_input.send_to_client(client_id, msg.clone()) {
+            log::error!("Failed to send to client {}, removing it: {}", client_id, e);
+            remove_client(os_input, session_state, client_id);
+        }
+    }
+}
+
+/// Makes room for the client forcing its way into the session
+fn detach_other_clients(
+    os_input: &dyn ServerOsApi,
+    session_state: &Arc<RwLock<SessionState>>,
+    client_id: ClientId,
+) {
+    let client_ids = session_state.read().unwrap().client_ids();
+    for other_client_id in client_ids {
+        if other_client_id != client_id {
+            let msg = ServerToClientMsg::Exit(ExitReason::ForceDetached);
+            let _ = os_input.send_to_client(other_client_id, msg);
+            remove_client(os_input, session_state, other_client_id);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn init_session(
     os_input: Box<dyn ServerOsApi>,
     opts: Box<CliArgs>,
@@ -303,12 +458,13 @@ fn init_session(
     client_attributes: ClientAttributes,
     session_state: Arc<RwLock<SessionState>>,
     layout: Option<Layout>,
+    session_name: &str,
 ) -> SessionMetaData {
     let (to_screen, screen_receiver): ChannelWithContext<ScreenInstruction> = channels::unbounded();
     let to_screen = SenderWithContext::new(to_screen);
 
     let (to_screen_bounded, bounded_screen_receiver): ChannelWithContext<ScreenInstruction> =
-        channels::bounded(50);
+        channels::bounded(MAX_PENDING_SCREEN_INSTRUCTIONS);
     let to_screen_bounded = SenderWithContext::new(to_screen_bounded);
 
     let (to_plugin, plugin_receiver): ChannelWithContext<PluginInstruction> = channels::unbounded();
@@ -316,6 +472,13 @@ fn init_session(
     let (to_pty, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
     let to_pty = SenderWithContext::new(to_pty);
 
+    let layout_name = opts
+        .layout_path
+        .as_ref()
+        .or_else(|| opts.layout.as_ref())
+        .map(|layout| layout.display().to_string())
+        .or_else(|| layout.as_ref().map(|_| "default".into()));
+
     // Determine and initialize the data directory
     let data_dir = opts.data_dir.unwrap_or_else(get_default_data_dir);
 
@@ -330,6 +493,24 @@ fn init_session(
         })
     });
 
+    if !config_options.disable_session_serialization {
+        let to_screen = to_screen.clone();
+        let snapshot_path = SessionSnapshot::path(&data_dir, session_name);
+        let with_scrollback = config_options.serialize_pane_scrollback;
+        // this thread ends on its own once the screen thread is gone
+        thread::Builder::new()
+            .name("session_serializer".to_string())
+            .spawn(move || loop {
+                thread::sleep(SESSION_SERIALIZATION_INTERVAL);
+                let instruction =
+                    ScreenInstruction::SerializeSession(snapshot_path.clone(), with_scrollback);
+                if to_screen.send(instruction).is_err() {
+                    break;
+                }
+            })
+            .unwrap();
+    }
+
     let pty_thread = thread::Builder::new()
         .name("pty".to_string())
         .spawn({
@@ -343,6 +524,8 @@ fn init_session(
                     Some(os_input.clone()),
                 ),
                 opts.debug,
+                !config_options.disable_cwd_inheritance,
+                config_options.link_opener.clone(),
             );
 
             move || pty_thread_main(pty, layout)
@@ -401,8 +584,14 @@ fn init_session(
         capabilities,
         default_shell,
         palette: client_attributes.palette,
+        created: SystemTime::now(),
+        layout_name,
         screen_thread: Some(screen_thread),
         pty_thread: Some(pty_thread),
         wasm_thread: Some(wasm_thread),
     }
 }
+
+#[cfg(test)]
+#[path = "./unit/server_tests.rs"]
+mod server_tests;
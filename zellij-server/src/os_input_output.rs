@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::env;
+use std::io;
 use std::os::unix::io::RawFd;
 use std::path::PathBuf;
 use std::process::{Child, Command};
@@ -17,10 +19,9 @@ use nix::unistd::{self, ForkResult};
 use signal_hook::consts::*;
 use zellij_tile::data::Palette;
 use zellij_utils::{
-    errors::ErrorContext,
     input::command::{RunCommand, TerminalAction},
     ipc::{
-        ClientToServerMsg, ExitReason, IpcReceiverWithContext, IpcSenderWithContext,
+        ClientId, ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext,
         ServerToClientMsg,
     },
     shared::default_palette,
@@ -91,6 +92,18 @@ fn handle_command_exit(mut child: Child) {
     }
 }
 
+/// The process to run for `cmd`, in its working directory and with its environment variables
+fn command_for(cmd: RunCommand) -> Command {
+    let mut command = Command::new(cmd.command);
+    command.args(&cmd.args).envs(&cmd.env);
+    // the directory might be gone, eg. when resurrecting a session
+    // after a reboot, in which case we start in our own cwd
+    if let Some(cwd) = cmd.cwd.filter(|cwd| cwd.is_dir()) {
+        command.current_dir(cwd);
+    }
+    command
+}
+
 /// Spawns a new terminal from the parent terminal with [`termios`](termios::Termios)
 /// `orig_termios`.
 ///
@@ -102,10 +115,7 @@ fn handle_terminal(cmd: RunCommand, orig_termios: termios::Termios) -> (RawFd, P
                 let pid_secondary = match fork_pty_res.fork_result {
                     ForkResult::Parent { child } => child,
                     ForkResult::Child => {
-                        let child = Command::new(cmd.command)
-                            .args(&cmd.args)
-                            .spawn()
-                            .expect("failed to spawn");
+                        let child = command_for(cmd).spawn().expect("failed to spawn");
                         handle_command_exit(child);
                         ::std::process::exit(0);
                     }
@@ -148,14 +158,22 @@ pub fn spawn_terminal(
                 .into_os_string()
                 .into_string()
                 .expect("Not valid Utf8 Encoding")];
-            RunCommand { command, args }
+            RunCommand {
+                command,
+                args,
+                ..Default::default()
+            }
         }
         Some(TerminalAction::RunCommand(command)) => command,
         None => {
             let command =
                 PathBuf::from(env::var("SHELL").expect("Could not find the SHELL variable"));
             let args = vec![];
-            RunCommand { command, args }
+            RunCommand {
+                command,
+                args,
+                ..Default::default()
+            }
         }
     };
 
@@ -165,8 +183,7 @@ pub fn spawn_terminal(
 #[derive(Clone)]
 pub struct ServerOsInputOutput {
     orig_termios: Arc<Mutex<termios::Termios>>,
-    receive_instructions_from_client: Option<Arc<Mutex<IpcReceiverWithContext<ClientToServerMsg>>>>,
-    send_instructions_to_client: Arc<Mutex<Option<IpcSenderWithContext<ServerToClientMsg>>>>,
+    client_senders: Arc<Mutex<HashMap<ClientId, IpcSenderWithContext<ServerToClientMsg>>>>,
 }
 
 // async fn in traits is not supported by rust, so dtolnay's excellent async_trait macro is being
@@ -218,23 +235,20 @@ pub trait ServerOsApi: Send + Sync {
     fn force_kill(&self, pid: Pid) -> Result<(), nix::Error>;
     /// Returns a [`Box`] pointer to this [`ServerOsApi`] struct.
     fn box_clone(&self) -> Box<dyn ServerOsApi>;
-    /// Receives a message on server-side IPC channel
-    fn recv_from_client(&self) -> (ClientToServerMsg, ErrorContext);
-    /// Sends a message to client
-    fn send_to_client(&self, msg: ServerToClientMsg);
-    /// Adds a sender to client
-    fn add_client_sender(&self);
-    /// Send to the temporary client
-    // A temporary client is the one that hasn't been registered as a client yet.
-    // Only the corresponding router thread has access to send messages to it.
-    // This can be the case when the client cannot attach to the session,
-    // so it tries to connect and then exits, hence temporary.
-    fn send_to_temp_client(&self, msg: ServerToClientMsg);
-    /// Removes the sender to client
-    fn remove_client_sender(&self);
-    /// Update the receiver socket for the client
-    fn update_receiver(&mut self, stream: LocalSocketStream);
+    /// Sends a message to the client with the given id, removing its sender if the client went
+    /// away
+    fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) -> io::Result<()>;
+    /// Registers a newly connected client, returning a receiver for the messages it sends
+    fn new_client(
+        &self,
+        client_id: ClientId,
+        stream: LocalSocketStream,
+    ) -> IpcReceiverWithContext<ClientToServerMsg>;
+    /// Removes the sender to the client with the given id
+    fn remove_client(&self, client_id: ClientId);
     fn load_palette(&self) -> Palette;
+    /// Returns the working directory of the foreground process of the terminal referred to by `fd`
+    fn get_cwd(&self, fd: RawFd) -> Option<PathBuf>;
 }
 
 impl ServerOsApi for ServerOsInputOutput {
@@ -269,59 +283,42 @@ impl ServerOsApi for ServerOsInputOutput {
         let _ = kill(pid, Some(Signal::SIGKILL));
         Ok(())
     }
-    fn recv_from_client(&self) -> (ClientToServerMsg, ErrorContext) {
-        self.receive_instructions_from_client
-            .as_ref()
-            .unwrap()
-            .lock()
-            .unwrap()
-            .recv()
-    }
-    fn send_to_client(&self, msg: ServerToClientMsg) {
-        self.send_instructions_to_client
-            .lock()
-            .unwrap()
-            .as_mut()
-            .unwrap()
-            .send(msg);
-    }
-    fn add_client_sender(&self) {
-        let sender = self
-            .receive_instructions_from_client
-            .as_ref()
-            .unwrap()
-            .lock()
-            .unwrap()
-            .get_sender();
-        let old_sender = self
-            .send_instructions_to_client
-            .lock()
-            .unwrap()
-            .replace(sender);
-        if let Some(mut sender) = old_sender {
-            sender.send(ServerToClientMsg::Exit(ExitReason::ForceDetached));
+    fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) -> io::Result<()> {
+        let mut client_senders = self.client_senders.lock().unwrap();
+        let result = match client_senders.get_mut(&client_id) {
+            Some(sender) => sender.send(msg),
+            None => Ok(()),
+        };
+        if result.is_err() {
+            client_senders.remove(&client_id);
         }
+        result
     }
-    fn send_to_temp_client(&self, msg: ServerToClientMsg) {
-        self.receive_instructions_from_client
-            .as_ref()
-            .unwrap()
+    fn new_client(
+        &self,
+        client_id: ClientId,
+        stream: LocalSocketStream,
+    ) -> IpcReceiverWithContext<ClientToServerMsg> {
+        let receiver = IpcReceiverWithContext::new(stream);
+        let sender = receiver.get_sender();
+        self.client_senders
             .lock()
             .unwrap()
-            .get_sender()
-            .send(msg);
-    }
-    fn remove_client_sender(&self) {
-        assert!(self.send_instructions_to_client.lock().unwrap().is_some());
-        *self.send_instructions_to_client.lock().unwrap() = None;
+            .insert(client_id, sender);
+        receiver
     }
-    fn update_receiver(&mut self, stream: LocalSocketStream) {
-        self.receive_instructions_from_client =
-            Some(Arc::new(Mutex::new(IpcReceiverWithContext::new(stream))));
+    fn remove_client(&self, client_id: ClientId) {
+        self.client_senders.lock().unwrap().remove(&client_id);
     }
     fn load_palette(&self) -> Palette {
         default_palette()
     }
+    fn get_cwd(&self, fd: RawFd) -> Option<PathBuf> {
+        // this is the process group of whatever runs in the foreground of the terminal,
+        // usually the shell itself
+        let pgrp = unistd::tcgetpgrp(fd).ok()?;
+        std::fs::read_link(format!("/proc/{}/cwd", pgrp)).ok()
+    }
 }
 
 impl Clone for Box<dyn ServerOsApi> {
@@ -335,7 +332,10 @@ pub fn get_server_os_input() -> Result<ServerOsInputOutput, nix::Error> {
     let orig_termios = Arc::new(Mutex::new(current_termios));
     Ok(ServerOsInputOutput {
         orig_termios,
-        receive_instructions_from_client: None,
-        send_instructions_to_client: Arc::new(Mutex::new(None)),
+        client_senders: Arc::new(Mutex::new(HashMap::new())),
     })
 }
+
+#[cfg(test)]
+#[path = "./unit/os_input_output_tests.rs"]
+mod os_input_output_tests;
@@ -0,0 +1,141 @@
+//! Copies of everything panes print, to a file or to the stdin of a command.
+
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    mem,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::mpsc::{sync_channel, SyncSender, TrySendError},
+    thread,
+};
+
+use zellij_utils::{
+    input::actions::PipeTarget,
+    vte::{self, Params, Perform},
+};
+
+/// How many reads of the output of a pane can wait to be written, the pipe stops once its
+/// target falls further behind
+const MAX_PENDING_WRITES: usize = 256;
+
+pub(crate) struct PanePipe {
+    pub target: PipeTarget,
+    /// The output goes to a thread of its own, which strips it if asked to and writes it to the
+    /// target
+    sender: SyncSender<Vec<u8>>,
+}
+
+impl PanePipe {
+    /// Opens `target`, a command being run with the shell in `cwd`. The writing thread calls
+    /// `on_failure` if writing to the target fails, after which the pipe takes no more output.
+    pub fn new(
+        target: &PipeTarget,
+        strip_ansi: bool,
+        cwd: Option<PathBuf>,
+        on_failure: impl FnOnce(io::Error) + Send + 'static,
+    ) -> io::Result<Self> {
+        let (mut writer, child): (Box<dyn Write + Send>, _) = match target {
+            PipeTarget::File(path) => {
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                (Box::new(file), None)
+            }
+            PipeTarget::Command(command_line) => {
+                let mut command = Command::new("sh");
+                command
+                    .arg("-c")
+                    .arg(command_line)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null());
+                if let Some(cwd) = cwd {
+                    command.current_dir(cwd);
+                }
+                let mut child = command.spawn()?;
+                let stdin = child.stdin.take().unwrap();
+                (Box::new(stdin), Some(child))
+            }
+        };
+        // removes the ANSI escape sequences from the output when set, on the writing thread so
+        // that parsing doesn't hold up the callers
+        let mut ansi_stripper = if strip_ansi {
+            Some((vte::Parser::new(), AnsiStripper::default()))
+        } else {
+            None
+        };
+        let (sender, receiver) = sync_channel(MAX_PENDING_WRITES);
+        thread::spawn(move || {
+            // ends once the pipe is dropped, or once writing fails
+            let failure = receiver.iter().find_map(|bytes| {
+                let bytes = match ansi_stripper.as_mut() {
+                    Some((parser, ansi_stripper)) => {
+                        for byte in &bytes {
+                            parser.advance(ansi_stripper, *byte);
+                        }
+                        mem::take(&mut ansi_stripper.text)
+                    }
+                    None => bytes,
+                };
+                if bytes.is_empty() {
+                    return None;
+                }
+                write_all_and_flush(writer.as_mut(), &bytes).err()
+            });
+            // the pipe takes no more output from here on
+            drop(receiver);
+            // the command gets to the end of its input once the writer is dropped, then it's
+            // reaped so that it doesn't linger as a zombie
+            drop(writer);
+            if let Some(e) = failure {
+                on_failure(e);
+            }
+            if let Some(mut child) = child {
+                let _ = child.wait();
+            }
+        });
+        Ok(PanePipe {
+            target: target.clone(),
+            sender,
+        })
+    }
+    /// Hands the output over to the writing thread without waiting for it, failing with
+    /// `TrySendError::Full` if the target falls too far behind and with
+    /// `TrySendError::Disconnected` if writing to it failed
+    pub fn write(&self, bytes: &[u8]) -> Result<(), TrySendError<Vec<u8>>> {
+        self.sender.try_send(bytes.to_vec())
+    }
+}
+
+fn write_all_and_flush(writer: &mut dyn Write, bytes: &[u8]) -> io::Result<()> {
+    writer.write_all(bytes)?;
+    writer.flush()
+}
+
+/// Keeps the text of the output, along with its line breaks and tabs
+#[derive(Default)]
+struct AnsiStripper {
+    text: Vec<u8>,
+}
+
+impl Perform for AnsiStripper {
+    fn print(&mut self, c: char) {
+        let mut buf = [0; 4];
+        self.text
+            .extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+    }
+    fn execute(&mut self, byte: u8) {
+        if byte == b'\n' || byte == b'\t' {
+            self.text.push(byte);
+        }
+    }
+    fn hook(&mut self, _params: &Params, _intermediates: &[u8], _ignore: bool, _c: char) {}
+    fn put(&mut self, _byte: u8) {}
+    fn unhook(&mut self) {}
+    fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {}
+    fn csi_dispatch(&mut self, _params: &Params, _intermediates: &[u8], _ignore: bool, _c: char) {}
+    fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {}
+}
+
+#[cfg(test)]
+#[path = "./unit/pane_pipe_tests.rs"]
+mod pane_pipe_tests;
@@ -0,0 +1,88 @@
+use zellij_utils::input::actions::SelectionKind;
+use zellij_utils::position::{Column, Line, Position};
+
+use super::selection::Selection;
+
+/// The cursor of the copy mode of a grid, and where its selection started if any.
+/// They are positioned relative to the viewport and moved along with it, like the [`Selection`].
+#[derive(Debug, Clone)]
+pub struct CopyMode {
+    pub cursor: Position,
+    anchor: Option<(Position, SelectionKind)>,
+}
+
+impl CopyMode {
+    pub fn new(cursor: Position) -> Self {
+        CopyMode {
+            cursor,
+            anchor: None,
+        }
+    }
+    /// Starts a selection of this kind at the cursor, or stops it if it is already of this kind.
+    /// Switching to another kind keeps the start of the selection, like in vi.
+    pub fn toggle_selection(&mut self, kind: SelectionKind) {
+        self.anchor = match self.anchor {
+            Some((_, current_kind)) if current_kind == kind => None,
+            Some((anchor, _)) => Some((anchor, kind)),
+            None => Some((self.cursor, kind)),
+        };
+    }
+    /// The selection from its start to the cursor, including the character under the cursor
+    pub fn selection(&self, width: usize) -> Selection {
+        let mut selection = Selection::default();
+        let (anchor, kind) = match self.anchor {
+            Some(anchor) => anchor,
+            None => return selection,
+        };
+        let (first, last) = if anchor <= self.cursor {
+            (anchor, self.cursor)
+        } else {
+            (self.cursor, anchor)
+        };
+        match kind {
+            SelectionKind::Character => {
+                selection.start(first);
+                selection.end(Some(&Position {
+                    line: last.line,
+                    column: Column(last.column.0 + 1),
+                }));
+            }
+            SelectionKind::Line => {
+                selection.start(Position {
+                    line: first.line,
+                    column: Column(0),
+                });
+                selection.end(Some(&Position {
+                    line: last.line,
+                    column: Column(width),
+                }));
+            }
+            SelectionKind::Block => {
+                let left = anchor.column.0.min(self.cursor.column.0);
+                let right = anchor.column.0.max(self.cursor.column.0) + 1;
+                selection.start(Position {
+                    line: first.line,
+                    column: Column(left),
+                });
+                selection.end(Some(&Position {
+                    line: last.line,
+                    column: Column(right),
+                }));
+                selection.set_block(true);
+            }
+        }
+        selection
+    }
+    pub fn move_up(&mut self, lines: usize) {
+        self.cursor.line = Line(self.cursor.line.0 - lines as isize);
+        if let Some((anchor, _)) = self.anchor.as_mut() {
+            anchor.line = Line(anchor.line.0 - lines as isize);
+        }
+    }
+    pub fn move_down(&mut self, lines: usize) {
+        self.cursor.line = Line(self.cursor.line.0 + lines as isize);
+        if let Some((anchor, _)) = self.anchor.as_mut() {
+            anchor.line = Line(anchor.line.0 + lines as isize);
+        }
+    }
+}
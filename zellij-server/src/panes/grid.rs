@@ -1,19 +1,27 @@
 use unicode_width::UnicodeWidthChar;
 
 use std::{
+    borrow::Cow,
     cmp::Ordering,
-    collections::{BTreeSet, VecDeque},
+    collections::{BTreeSet, HashSet},
     fmt::{self, Debug, Formatter},
     str,
 };
 
-use zellij_utils::{position::Position, vte, zellij_tile};
+use zellij_utils::{
+    input::actions::{CopyModeMotion, SearchOption, SelectionKind},
+    pane_size::SizeInPixels,
+    position::{Column, Line, Position},
+    vte, zellij_tile,
+};
 
 const TABSTOP_WIDTH: usize = 8; // TODO: is this always right?
-const SCROLL_BACK: usize = 10_000;
+
+/// The characters that end a word selected by double clicking, besides whitespace
+pub const DEFAULT_WORD_SEPARATORS: &str = ",│`|:\"' ()[]{}<>";
 
 use vte::{Params, Perform};
-use zellij_tile::data::{Palette, PaletteColor};
+use zellij_tile::data::{Palette, PaletteColor, SearchInfo};
 use zellij_utils::{consts::VERSION, logging::debug_log_to_file, shared::version_number};
 
 use crate::panes::terminal_character::{
@@ -21,7 +29,12 @@ use crate::panes::terminal_character::{
     EMPTY_TERMINAL_CHARACTER,
 };
 
+use super::copy_mode::CopyMode;
+use super::link_handler::{LinkAnchor, LinkHandler};
+use super::scrollback::Scrollback;
+use super::search::{SearchResult, SearchableLine};
 use super::selection::Selection;
+use super::sixel::{ImageLayer, ImagePlacement, SixelDecoder};
 
 // this was copied verbatim from alacritty
 fn parse_number(input: &[u8]) -> Option<u8> {
@@ -60,24 +73,8 @@ fn get_top_non_canonical_rows(rows: &mut Vec<Row>) -> Vec<Row> {
     }
 }
 
-fn get_bottom_canonical_row_and_wraps(rows: &mut VecDeque<Row>) -> Vec<Row> {
-    let mut index_of_last_non_canonical_row = None;
-    for (i, row) in rows.iter().enumerate().rev() {
-        index_of_last_non_canonical_row = Some(i);
-        if row.is_canonical {
-            break;
-        }
-    }
-    match index_of_last_non_canonical_row {
-        Some(index_of_last_non_canonical_row) => {
-            rows.drain(index_of_last_non_canonical_row..).collect()
-        }
-        None => vec![],
-    }
-}
-
 fn transfer_rows_down(
-    source: &mut VecDeque<Row>,
+    source: &mut Scrollback,
     destination: &mut Vec<Row>,
     count: usize,
     max_src_width: Option<usize>,
@@ -121,7 +118,7 @@ fn transfer_rows_down(
             }
             None => {
                 let excess_row = Row::from_rows(next_lines, 0);
-                bounded_push(source, excess_row);
+                source.push_back(excess_row);
             }
         }
     }
@@ -129,7 +126,7 @@ fn transfer_rows_down(
 
 fn transfer_rows_up(
     source: &mut Vec<Row>,
-    destination: &mut VecDeque<Row>,
+    destination: &mut Scrollback,
     count: usize,
     max_src_width: Option<usize>,
     max_dst_width: Option<usize>,
@@ -141,7 +138,7 @@ fn transfer_rows_up(
                 let next_line = source.remove(0);
                 if !next_line.is_canonical {
                     let mut bottom_canonical_row_and_wraps_in_dst =
-                        get_bottom_canonical_row_and_wraps(destination);
+                        destination.pop_bottom_canonical_row_and_wraps();
                     next_lines.append(&mut bottom_canonical_row_and_wraps_in_dst);
                 }
                 next_lines.push(next_line);
@@ -154,7 +151,7 @@ fn transfer_rows_up(
                 break; // no more rows
             }
         }
-        bounded_push(destination, next_lines.remove(0));
+        destination.push_back(next_lines.remove(0));
     }
     if !next_lines.is_empty() {
         match max_src_width {
@@ -173,11 +170,50 @@ fn transfer_rows_up(
     }
 }
 
-fn bounded_push(vec: &mut VecDeque<Row>, value: Row) {
-    if vec.len() >= SCROLL_BACK {
-        vec.pop_front();
+fn character_at_column(row: &Row, column: usize) -> Option<char> {
+    let mut row_column = 0;
+    for character in row.columns.iter() {
+        row_column += character.width.max(1);
+        if column < row_column {
+            return Some(character.character);
+        }
     }
-    vec.push_back(value)
+    None
+}
+
+/// Words are made of characters of the same class, see the copy mode motions
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CharacterClass {
+    Whitespace,
+    Word,
+    Punctuation,
+}
+
+impl CharacterClass {
+    fn of(character: char) -> Self {
+        if character.is_whitespace() {
+            CharacterClass::Whitespace
+        } else if character.is_alphanumeric() || character == '_' {
+            CharacterClass::Word
+        } else {
+            CharacterClass::Punctuation
+        }
+    }
+}
+
+fn reset_character_styles() -> CharacterStyles {
+    let mut styles = CharacterStyles::new();
+    styles.reset_all();
+    styles
+}
+
+fn finish_dumped_line(line: String, character_styles: &mut CharacterStyles) -> String {
+    let mut line = String::from(line.trim_end());
+    if *character_styles != reset_character_styles() {
+        line.push_str("\u{1b}[m");
+        *character_styles = reset_character_styles();
+    }
+    line
 }
 
 pub fn create_horizontal_tabstops(columns: usize) -> BTreeSet<usize> {
@@ -294,13 +330,48 @@ impl OutputBuffer {
     }
 }
 
+/// Which mouse events the application running in the pane asked to receive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseTracking {
+    Off,
+    X10,         // ?9 - button presses only
+    Normal,      // ?1000 - presses and releases
+    ButtonEvent, // ?1002 - also motion while a button is held
+    AnyEvent,    // ?1003 - also all motion
+}
+
+/// How mouse events are encoded when they are reported to the application
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseEncoding {
+    X10,   // default - CSI M Cb Cx Cy, coordinates limited to 223
+    Sgr,   // ?1006 - CSI < Cb ; Cx ; Cy M/m
+    Urxvt, // ?1015 - CSI Cb ; Cx ; Cy M
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseEvent {
+    LeftPress,
+    LeftRelease,
+    LeftDrag,
+    RightPress,
+    RightRelease,
+    RightDrag,
+    MiddlePress,
+    MiddleRelease,
+    MiddleDrag,
+    /// The mouse moved with no button held
+    Motion,
+    WheelUp,
+    WheelDown,
+}
+
 #[derive(Clone)]
 pub struct Grid {
-    lines_above: VecDeque<Row>,
+    lines_above: Scrollback,
     viewport: Vec<Row>,
     lines_below: Vec<Row>,
     horizontal_tabstops: BTreeSet<usize>,
-    alternative_lines_above_viewport_and_cursor: Option<(VecDeque<Row>, Vec<Row>, Cursor)>,
+    alternative_lines_above_viewport_and_cursor: Option<(Scrollback, Vec<Row>, Cursor)>,
     cursor: Cursor,
     saved_cursor_position: Option<Cursor>,
     scroll_region: Option<(usize, usize)>,
@@ -318,6 +389,21 @@ pub struct Grid {
     pub height: usize,
     pub pending_messages_to_pty: Vec<Vec<u8>>,
     pub selection: Selection,
+    pub search_results: SearchResult,
+    // the output changed since the matches of the search were found
+    search_outdated: bool,
+    copy_mode: Option<CopyMode>,
+    pub mouse_tracking: MouseTracking,
+    pub mouse_encoding: MouseEncoding,
+    pub bracketed_paste: bool,
+    pub link_handler: LinkHandler,
+    /// The size of a character cell of the clients' terminal, None if it does not tell us
+    pub character_cell_size: Option<SizeInPixels>,
+    pub image_layer: ImageLayer,
+    alternative_image_layer: Option<ImageLayer>,
+    // the images of both the primary and the alternative screen are numbered by it
+    next_image_id: usize,
+    sixel_decoder: Option<SixelDecoder>,
 }
 
 impl Debug for Grid {
@@ -334,9 +420,9 @@ impl Debug for Grid {
 }
 
 impl Grid {
-    pub fn new(rows: usize, columns: usize, colors: Palette) -> Self {
+    pub fn new(rows: usize, columns: usize, colors: Palette, scroll_buffer_size: usize) -> Self {
         Grid {
-            lines_above: VecDeque::with_capacity(SCROLL_BACK),
+            lines_above: Scrollback::new(scroll_buffer_size),
             viewport: vec![Row::new(columns).canonical()],
             lines_below: vec![],
             horizontal_tabstops: create_horizontal_tabstops(columns),
@@ -358,6 +444,18 @@ impl Grid {
             colors,
             output_buffer: Default::default(),
             selection: Default::default(),
+            search_results: Default::default(),
+            search_outdated: false,
+            copy_mode: None,
+            mouse_tracking: MouseTracking::Off,
+            mouse_encoding: MouseEncoding::X10,
+            bracketed_paste: false,
+            link_handler: LinkHandler::new(),
+            character_cell_size: None,
+            image_layer: Default::default(),
+            alternative_image_layer: None,
+            next_image_id: 0,
+            sixel_decoder: None,
         }
     }
     pub fn render_full_viewport(&mut self) {
@@ -471,6 +569,19 @@ impl Grid {
         }
         y_coordinates
     }
+    /// Keep at most `scroll_buffer_size` lines above the viewport, dropping the oldest ones
+    pub fn set_scroll_buffer_size(&mut self, scroll_buffer_size: usize) {
+        self.lines_above.set_limit(scroll_buffer_size);
+        self.image_layer.trim_to_scrollback(self.lines_above.len());
+        if let Some((alternative_lines_above, _, _)) =
+            self.alternative_lines_above_viewport_and_cursor.as_mut()
+        {
+            alternative_lines_above.set_limit(scroll_buffer_size);
+            if let Some(alternative_image_layer) = self.alternative_image_layer.as_mut() {
+                alternative_image_layer.trim_to_scrollback(alternative_lines_above.len());
+            }
+        }
+    }
     pub fn scroll_up_one_line(&mut self) {
         if !self.lines_above.is_empty() && self.viewport.len() == self.height {
             let line_to_push_down = self.viewport.pop().unwrap();
@@ -478,6 +589,11 @@ impl Grid {
             let line_to_insert_at_viewport_top = self.lines_above.pop_back().unwrap();
             self.viewport.insert(0, line_to_insert_at_viewport_top);
             self.selection.move_down(1);
+            self.search_results.move_down(1);
+            if let Some(copy_mode) = self.copy_mode.as_mut() {
+                copy_mode.move_down(1);
+            }
+            self.image_layer.move_down(1);
         }
         self.output_buffer.update_all_lines();
     }
@@ -485,15 +601,20 @@ impl Grid {
         if !self.lines_below.is_empty() && self.viewport.len() == self.height {
             let mut line_to_push_up = self.viewport.remove(0);
             if line_to_push_up.is_canonical {
-                bounded_push(&mut self.lines_above, line_to_push_up);
+                self.lines_above.push_back(line_to_push_up);
             } else {
                 let mut last_line_above = self.lines_above.pop_back().unwrap();
                 last_line_above.append(&mut line_to_push_up.columns);
-                bounded_push(&mut self.lines_above, last_line_above);
+                self.lines_above.push_back(last_line_above);
             }
             let line_to_insert_at_viewport_bottom = self.lines_below.remove(0);
             self.viewport.push(line_to_insert_at_viewport_bottom);
             self.selection.move_up(1);
+            self.search_results.move_up(1);
+            if let Some(copy_mode) = self.copy_mode.as_mut() {
+                copy_mode.move_up(1);
+            }
+            self.image_layer.move_up(1, self.lines_above.len());
             self.output_buffer.update_all_lines();
         }
     }
@@ -631,6 +752,14 @@ impl Grid {
         if self.scroll_region.is_some() {
             self.set_scroll_region_to_viewport_size();
         }
+        if !self.search_results.matches.is_empty() {
+            // the lines were rewrapped
+            self.find_search_matches();
+        }
+        if self.copy_mode.is_some() {
+            // the positions of the copy mode do not match the rewrapped lines anymore
+            self.start_copy_mode();
+        }
         self.output_buffer.update_all_lines();
     }
     pub fn as_character_lines(&self) -> Vec<Vec<TerminalCharacter>> {
@@ -665,7 +794,14 @@ impl Grid {
         changes
     }
     pub fn cursor_coordinates(&self) -> Option<(usize, usize)> {
-        if self.cursor.is_hidden {
+        if let Some(copy_mode) = self.copy_mode.as_ref() {
+            let Position { line, column } = copy_mode.cursor;
+            if line.0 >= 0 && (line.0 as usize) < self.height {
+                Some((column.0, line.0 as usize))
+            } else {
+                None
+            }
+        } else if self.cursor.is_hidden {
             None
         } else {
             Some((self.cursor.x, self.cursor.y))
@@ -772,6 +908,11 @@ impl Grid {
                 None,
             );
             self.selection.move_up(1);
+            self.search_results.move_up(1);
+            if let Some(copy_mode) = self.copy_mode.as_mut() {
+                copy_mode.move_up(1);
+            }
+            self.image_layer.move_up(1, self.lines_above.len());
             self.output_buffer.update_all_lines();
         } else {
             self.cursor.y += 1;
@@ -849,6 +990,11 @@ impl Grid {
                 let wrapped_row = Row::new(self.width);
                 self.viewport.push(wrapped_row);
                 self.selection.move_up(1);
+                self.search_results.move_up(1);
+                if let Some(copy_mode) = self.copy_mode.as_mut() {
+                    copy_mode.move_up(1);
+                }
+                self.image_layer.move_up(1, self.lines_above.len());
                 self.output_buffer.update_all_lines();
             } else {
                 self.cursor.y += 1;
@@ -1125,8 +1271,15 @@ impl Grid {
     pub fn mark_for_rerender(&mut self) {
         self.should_render = true;
     }
+    fn mark_search_outdated(&mut self) {
+        if !self.search_results.query.text.is_empty() {
+            self.search_outdated = true;
+        }
+    }
     fn reset_terminal_state(&mut self) {
-        self.lines_above = VecDeque::with_capacity(SCROLL_BACK);
+        self.search_results.matches.clear();
+        self.search_results.active = None;
+        self.lines_above.clear();
         self.lines_below = vec![];
         self.viewport = vec![Row::new(self.width).canonical()];
         self.alternative_lines_above_viewport_and_cursor = None;
@@ -1139,6 +1292,12 @@ impl Grid {
         self.erasure_mode = false;
         self.disable_linewrap = false;
         self.cursor.change_shape(CursorShape::Block);
+        self.mouse_tracking = MouseTracking::Off;
+        self.mouse_encoding = MouseEncoding::X10;
+        self.bracketed_paste = false;
+        self.link_handler.clear();
+        self.image_layer.clear();
+        self.alternative_image_layer = None;
         self.output_buffer.update_all_lines();
     }
     fn set_preceding_character(&mut self, terminal_character: TerminalCharacter) {
@@ -1150,6 +1309,124 @@ impl Grid {
         self.update_selected_lines(&old_selection, &self.selection.clone());
         self.mark_for_rerender();
     }
+    /// Scrolls towards `to`, which is above or below the viewport, and extends the selection to
+    /// the edge of the viewport. The further `to` is from the viewport, the more lines are
+    /// scrolled at once.
+    pub fn scroll_selection_towards(&mut self, to: &Position) {
+        let edge_line = if to.line.0 < 0 {
+            self.move_viewport_up((to.line.0.abs() as usize).min(self.height));
+            0
+        } else {
+            let distance = (to.line.0 as usize + 1).saturating_sub(self.height);
+            self.move_viewport_down(distance.min(self.height));
+            self.height.saturating_sub(1)
+        };
+        self.update_selection(&Position {
+            line: Line(edge_line as isize),
+            column: Column(to.column.0.min(self.width)),
+        });
+    }
+    /// Selects the word at `position`, words can wrap and end at whitespace or at one of the
+    /// `word_separators`. A separator or whitespace at `position` is selected on its own.
+    pub fn select_word(&mut self, position: &Position, word_separators: &str) {
+        let (first_line, last_line) = self.canonical_line_bounds(position.line.0);
+        // (line, column, character, width) of every character of the canonical line
+        let mut characters = vec![];
+        for line in first_line..=last_line {
+            if let Some(row) = self.row_at_line(line) {
+                let mut column = 0;
+                for character in row.columns {
+                    characters.push((line, column, character.character, character.width));
+                    column += character.width;
+                }
+            }
+        }
+        let clicked = characters.iter().position(|&(line, column, _, width)| {
+            line == position.line.0
+                && column <= position.column.0
+                && position.column.0 < column + width.max(1)
+        });
+        let clicked = match clicked {
+            Some(clicked) => clicked,
+            None => return,
+        };
+        let is_word_character =
+            |character: char| !character.is_whitespace() && !word_separators.contains(character);
+        let (mut first, mut last) = (clicked, clicked);
+        if is_word_character(characters[clicked].2) {
+            while first > 0 && is_word_character(characters[first - 1].2) {
+                first -= 1;
+            }
+            while last + 1 < characters.len() && is_word_character(characters[last + 1].2) {
+                last += 1;
+            }
+        }
+        let (start_line, start_column, _, _) = characters[first];
+        let (end_line, end_column, _, end_width) = characters[last];
+        self.set_selection(
+            Position {
+                line: Line(start_line),
+                column: Column(start_column),
+            },
+            Position {
+                line: Line(end_line),
+                column: Column(end_column + end_width),
+            },
+        );
+    }
+    /// Selects the canonical line at `position`, along with the lines it wraps into
+    pub fn select_line(&mut self, position: &Position) {
+        let (first_line, last_line) = self.canonical_line_bounds(position.line.0);
+        self.set_selection(
+            Position {
+                line: Line(first_line),
+                column: Column(0),
+            },
+            Position {
+                line: Line(last_line),
+                column: Column(self.width),
+            },
+        );
+    }
+    fn set_selection(&mut self, start: Position, end: Position) {
+        let old_selection = self.selection.clone();
+        self.selection.start(start);
+        self.selection.end(Some(&end));
+        self.update_selected_lines(&old_selection, &self.selection.clone());
+        self.mark_for_rerender();
+    }
+    /// The first and last lines of the canonical line that `line` is part of
+    fn canonical_line_bounds(&self, line: isize) -> (isize, isize) {
+        let is_wrapped = |line: isize| {
+            self.row_at_line(line)
+                .map(|row| !row.is_canonical)
+                .unwrap_or(false)
+        };
+        let mut first_line = line;
+        while first_line > self.first_line_index() && is_wrapped(first_line) {
+            first_line -= 1;
+        }
+        let mut last_line = line;
+        while last_line < self.last_line_index() && is_wrapped(last_line + 1) {
+            last_line += 1;
+        }
+        (first_line, last_line)
+    }
+    /// The row at this line, relative to the viewport
+    fn row_at_line(&self, line: isize) -> Option<Row> {
+        if line < 0 {
+            self.lines_above
+                .len()
+                .checked_sub(line.abs() as usize)
+                .and_then(|index| self.lines_above.get(index))
+        } else if (line as usize) < self.viewport.len() {
+            self.viewport.get(line as usize).cloned()
+        } else {
+            self.lines_below
+                .get(line as usize - self.viewport.len())
+                .cloned()
+        }
+    }
     pub fn update_selection(&mut self, to: &Position) {
         let old_selection = self.selection.clone();
         self.selection.to(*to);
@@ -1170,6 +1447,319 @@ impl Grid {
         self.update_selected_lines(&old_selection, &self.selection.clone());
         self.mark_for_rerender();
     }
+    pub fn update_search_term(&mut self, bytes: &[u8]) {
+        match bytes {
+            // keys that are not bound to anything in search mode, eg. arrows or alt+key
+            [27, ..] => return,
+            // delete and backspace keys
+            [127] | [8] => {
+                self.search_results.query.text.pop();
+            }
+            _ => {
+                if let Ok(text) = str::from_utf8(bytes) {
+                    self.search_results
+                        .query
+                        .text
+                        .extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+        self.search_and_jump_to_nearest_match();
+    }
+    /// Starts a new search, keeping the options of the previous one
+    pub fn reset_search_term(&mut self) {
+        self.search_results.query.text.clear();
+        self.search_and_jump_to_nearest_match();
+    }
+    /// Finds the matches of the search again if the output changed since they were found,
+    /// keeping the active match and the scroll position
+    pub fn refresh_search_matches(&mut self) {
+        if self.search_outdated {
+            self.search_outdated = false;
+            self.find_search_matches();
+        }
+    }
+    pub fn toggle_search_option(&mut self, option: SearchOption) {
+        let query = &mut self.search_results.query;
+        match option {
+            SearchOption::CaseSensitivity => query.case_sensitive = !query.case_sensitive,
+            SearchOption::Regex => query.regex = !query.regex,
+        }
+        self.search_and_jump_to_nearest_match();
+    }
+    /// Scroll to the match above the active one, towards the older output
+    pub fn search_up(&mut self) {
+        let match_count = self.search_results.matches.len();
+        let previous_match = match self.search_results.active {
+            _ if match_count == 0 => None,
+            Some(0) => Some(match_count - 1),
+            Some(active) => Some(active - 1),
+            None => self.nearest_search_match(),
+        };
+        self.jump_to_search_match(previous_match);
+    }
+    /// Scroll to the match below the active one, towards the more recent output
+    pub fn search_down(&mut self) {
+        let match_count = self.search_results.matches.len();
+        let next_match = match self.search_results.active {
+            _ if match_count == 0 => None,
+            Some(active) => Some((active + 1) % match_count),
+            None => self.nearest_search_match(),
+        };
+        self.jump_to_search_match(next_match);
+    }
+    pub fn clear_search(&mut self) {
+        if !self.search_results.matches.is_empty() {
+            self.output_buffer.update_all_lines();
+        }
+        self.search_results.clear();
+        self.search_outdated = false;
+    }
+    pub fn search_info(&self) -> SearchInfo {
+        self.search_results.info()
+    }
+    /// Puts a cursor on the grid that can move through the scrollback to select text, at the
+    /// position of the terminal cursor, or at the bottom left of the viewport if it is scrolled
+    pub fn start_copy_mode(&mut self) {
+        let cursor = if self.lines_below.is_empty() {
+            Position::new(self.cursor.y as i32, self.cursor.x as u16)
+        } else {
+            Position::new(self.viewport.len() as i32 - 1, 0)
+        };
+        self.copy_mode = Some(CopyMode::new(cursor));
+        self.selection.reset();
+        self.output_buffer.update_all_lines();
+    }
+    pub fn exit_copy_mode(&mut self) {
+        if self.copy_mode.take().is_some() {
+            self.selection.reset();
+            self.output_buffer.update_all_lines();
+        }
+    }
+    pub fn copy_mode_move(&mut self, motion: CopyModeMotion) {
+        let cursor = match self.copy_mode.as_ref() {
+            Some(copy_mode) => copy_mode.cursor,
+            None => return,
+        };
+        let (first_line, last_line) = (self.first_line_index(), self.last_line_index());
+        let move_lines = |lines: isize| Position {
+            line: Line((cursor.line.0 + lines).max(first_line).min(last_line)),
+            column: cursor.column,
+        };
+        let half_page = (self.height / 2).max(1) as isize;
+        let new_cursor = match motion {
+            CopyModeMotion::Left => Position {
+                line: cursor.line,
+                column: Column(cursor.column.0.saturating_sub(1)),
+            },
+            CopyModeMotion::Right => Position {
+                line: cursor.line,
+                column: Column((cursor.column.0 + 1).min(self.width.saturating_sub(1))),
+            },
+            CopyModeMotion::Up => move_lines(-1),
+            CopyModeMotion::Down => move_lines(1),
+            CopyModeMotion::WordForward => self.next_word_start(cursor),
+            CopyModeMotion::WordBackward => self.previous_word_start(cursor),
+            CopyModeMotion::WordEnd => self.next_word_end(cursor),
+            CopyModeMotion::LineStart => Position {
+                line: cursor.line,
+                column: Column(0),
+            },
+            CopyModeMotion::LineEnd => Position {
+                line: cursor.line,
+                column: Column(
+                    (0..self.width)
+                        .rev()
+                        .find(|column| !self.character_at(cursor.line.0, *column).is_whitespace())
+                        .unwrap_or(0),
+                ),
+            },
+            CopyModeMotion::HalfPageUp => move_lines(-half_page),
+            CopyModeMotion::HalfPageDown => move_lines(half_page),
+            CopyModeMotion::PageUp => move_lines(-(self.height as isize)),
+            CopyModeMotion::PageDown => move_lines(self.height as isize),
+            CopyModeMotion::Top => Position::new(first_line as i32, 0),
+            CopyModeMotion::Bottom => Position::new(last_line as i32, 0),
+        };
+        if let Some(copy_mode) = self.copy_mode.as_mut() {
+            copy_mode.cursor = new_cursor;
+        }
+        // scroll the viewport so that the cursor stays in it
+        let line = new_cursor.line.0;
+        if line < 0 {
+            self.move_viewport_up(line.abs() as usize);
+        } else if line >= self.height as isize {
+            self.move_viewport_down(line as usize + 1 - self.height);
+        }
+        self.update_copy_mode_selection();
+    }
+    pub fn copy_mode_select(&mut self, kind: SelectionKind) {
+        if let Some(copy_mode) = self.copy_mode.as_mut() {
+            copy_mode.toggle_selection(kind);
+        }
+        self.update_copy_mode_selection();
+    }
+    fn update_copy_mode_selection(&mut self) {
+        if let Some(copy_mode) = self.copy_mode.as_ref() {
+            self.selection = copy_mode.selection(self.width);
+        }
+        self.output_buffer.update_all_lines();
+    }
+    fn first_line_index(&self) -> isize {
+        -(self.lines_above.len() as isize)
+    }
+    fn last_line_index(&self) -> isize {
+        (self.viewport.len() + self.lines_below.len()) as isize - 1
+    }
+    /// The character displayed at this line (relative to the viewport) and column, a space if
+    /// there is none. The columns taken by a wide character all have it.
+    fn character_at(&self, line: isize, column: usize) -> char {
+        let character = if line < 0 {
+            self.lines_above
+                .len()
+                .checked_sub(line.abs() as usize)
+                .and_then(|index| self.lines_above.get(index))
+                .and_then(|row| character_at_column(&row, column))
+        } else if (line as usize) < self.viewport.len() {
+            character_at_column(&self.viewport[line as usize], column)
+        } else {
+            self.lines_below
+                .get(line as usize - self.viewport.len())
+                .and_then(|row| character_at_column(row, column))
+        };
+        character.unwrap_or(' ')
+    }
+    fn character_class_at(&self, position: Position) -> CharacterClass {
+        CharacterClass::of(self.character_at(position.line.0, position.column.0))
+    }
+    fn next_position(&self, position: Position) -> Option<Position> {
+        if position.column.0 + 1 < self.width {
+            Some(Position {
+                line: position.line,
+                column: Column(position.column.0 + 1),
+            })
+        } else if position.line.0 < self.last_line_index() {
+            Some(Position::new(position.line.0 as i32 + 1, 0))
+        } else {
+            None
+        }
+    }
+    fn previous_position(&self, position: Position) -> Option<Position> {
+        if position.column.0 > 0 {
+            Some(Position {
+                line: position.line,
+                column: Column(position.column.0 - 1),
+            })
+        } else if position.line.0 > self.first_line_index() {
+            Some(Position::new(
+                position.line.0 as i32 - 1,
+                self.width.saturating_sub(1) as u16,
+            ))
+        } else {
+            None
+        }
+    }
+    fn next_word_start(&self, mut position: Position) -> Position {
+        let mut previous_class = self.character_class_at(position);
+        while let Some(next_position) = self.next_position(position) {
+            position = next_position;
+            let class = self.character_class_at(position);
+            if class != CharacterClass::Whitespace && class != previous_class {
+                break;
+            }
+            previous_class = class;
+        }
+        position
+    }
+    fn previous_word_start(&self, mut position: Position) -> Position {
+        while let Some(previous_position) = self.previous_position(position) {
+            position = previous_position;
+            let class = self.character_class_at(position);
+            if class == CharacterClass::Whitespace {
+                continue;
+            }
+            match self.previous_position(position) {
+                Some(before) if self.character_class_at(before) == class => {}
+                _ => break,
+            }
+        }
+        position
+    }
+    fn next_word_end(&self, mut position: Position) -> Position {
+        while let Some(next_position) = self.next_position(position) {
+            position = next_position;
+            let class = self.character_class_at(position);
+            if class == CharacterClass::Whitespace {
+                continue;
+            }
+            match self.next_position(position) {
+                Some(after) if self.character_class_at(after) == class => {}
+                _ => break,
+            }
+        }
+        position
+    }
+    fn search_and_jump_to_nearest_match(&mut self) {
+        self.find_search_matches();
+        self.jump_to_search_match(self.nearest_search_match());
+    }
+    /// The last match that starts above the bottom of the viewport, or the first one if they are
+    /// all below it
+    fn nearest_search_match(&self) -> Option<usize> {
+        let matches = &self.search_results.matches;
+        matches
+            .iter()
+            .rposition(|found| found.start.line.0 < self.height as isize)
+            .or_else(|| if matches.is_empty() { None } else { Some(0) })
+    }
+    fn find_search_matches(&mut self) {
+        self.search_outdated = false;
+        let mut matches = vec![];
+        if let Some(regex) = self.search_results.query.to_regex() {
+            // search whole lines rather than rows, so that wrapping does not split matches
+            let mut searchable_line = SearchableLine::default();
+            let first_line_index = -(self.lines_above.len() as isize);
+            for (i, (text, is_canonical)) in self.text_rows().enumerate() {
+                if is_canonical {
+                    searchable_line.find_matches(&regex, &mut matches);
+                }
+                let mut column = 0;
+                for character in text.chars() {
+                    let width = character.width().unwrap_or(0);
+                    searchable_line.push(character, first_line_index + i as isize, column, width);
+                    column += width;
+                }
+            }
+            searchable_line.find_matches(&regex, &mut matches);
+        }
+        let match_count = matches.len();
+        if matches != self.search_results.matches {
+            self.output_buffer.update_all_lines();
+        }
+        self.search_results.matches = matches;
+        self.search_results.active = self
+            .search_results
+            .active
+            .filter(|active| *active < match_count);
+    }
+    /// Marks the match at `index` as the active one and scrolls it to the middle of the viewport,
+    /// unless it is already visible
+    fn jump_to_search_match(&mut self, index: Option<usize>) {
+        self.search_results.active = index;
+        if let Some(line) = self
+            .search_results
+            .active_match()
+            .map(|found| found.start.line.0)
+        {
+            let middle_line = (self.height / 2) as isize;
+            if line < 0 {
+                self.move_viewport_up((middle_line - line) as usize);
+            } else if line >= self.height as isize {
+                self.move_viewport_down((line - middle_line) as usize);
+            }
+        }
+        self.output_buffer.update_all_lines();
+    }
     pub fn get_selected_text(&self) -> Option<String> {
         if self.selection.is_empty() {
             return None;
@@ -1184,10 +1774,14 @@ impl Grid {
 
             // on the first line of the selection, use the selection start column
             // otherwise, start at the beginning of the line
-            let start_column = if l == start.line.0 { start.column.0 } else { 0 };
+            let start_column = if l == start.line.0 || sorted_selection.is_block() {
+                start.column.0
+            } else {
+                0
+            };
 
             // same thing on the last line, but with the selection end column
-            let end_column = if l == end.line.0 {
+            let end_column = if l == end.line.0 || sorted_selection.is_block() {
                 end.column.0
             } else {
                 self.width
@@ -1200,12 +1794,18 @@ impl Grid {
             let empty_row = Row::from_columns(vec![EMPTY_TERMINAL_CHARACTER; self.width]);
 
             // get the row from lines_above, viewport, or lines below depending on index
+            let scrollback_row;
             let row = if l < 0 {
                 let offset_from_end = l.abs();
-                &self.lines_above[self
+                scrollback_row = self
                     .lines_above
-                    .len()
-                    .saturating_sub(offset_from_end as usize)]
+                    .get(
+                        self.lines_above
+                            .len()
+                            .saturating_sub(offset_from_end as usize),
+                    )
+                    .unwrap_or_else(|| empty_row.clone());
+                &scrollback_row
             } else if l >= 0 && (l as usize) < self.viewport.len() {
                 &self.viewport[l as usize]
             } else if (l as usize) < self.height {
@@ -1237,6 +1837,215 @@ impl Grid {
         Some(selection.join("\n"))
     }
 
+    fn device_attributes(&self) -> &'static str {
+        if self.character_cell_size.is_some() {
+            // VT220 with sixel graphics
+            "\u{1b}[?62;4c"
+        } else {
+            "\u{1b}[?6c"
+        }
+    }
+    /// Where the images of this grid are drawn over its text, if its top left corner is at
+    /// `x`/`y` on the screen
+    pub fn image_placements(&self, x: usize, y: usize) -> Vec<ImagePlacement> {
+        if self.character_cell_size.is_none() {
+            return vec![];
+        }
+        self.image_layer.placements(x, y, self.height, self.width)
+    }
+    /// Draws the part of an image shown by `placement`, see [`Grid::image_placements`]
+    pub fn render_image(&self, placement: &ImagePlacement) -> Option<String> {
+        let character_cell_size = self.character_cell_size?;
+        self.image_layer.render(placement, character_cell_size)
+    }
+    /// The uri of the hyperlink at `position` (relative to the viewport)
+    pub fn link_at(&self, position: &Position) -> Option<String> {
+        if position.line.0 < 0 {
+            return None;
+        }
+        let row = self.viewport.get(position.line.0 as usize)?;
+        let x = position.column.0;
+        let character = row
+            .columns
+            .get(x.saturating_sub(row.excess_width_until(x)))?;
+        character
+            .link_anchor
+            .and_then(|anchor| self.link_handler.link(anchor))
+            .map(|link| link.uri.clone())
+    }
+    pub fn link_under_cursor(&self) -> Option<String> {
+        self.link_at(&Position::new(self.cursor.y as i32, self.cursor.x as u16))
+    }
+    /// The anchors of the links that are still printed somewhere in this grid
+    fn link_anchors_in_use(&self) -> HashSet<LinkAnchor> {
+        let row_anchors = |rows: &[Row]| -> Vec<LinkAnchor> {
+            rows.iter()
+                .flat_map(|row| row.columns.iter())
+                .filter_map(|character| character.link_anchor)
+                .collect()
+        };
+        let mut anchors: HashSet<LinkAnchor> = self.lines_above.link_anchors().collect();
+        anchors.extend(row_anchors(&self.viewport));
+        anchors.extend(row_anchors(&self.lines_below));
+        if let Some((lines_above, viewport, _)) = &self.alternative_lines_above_viewport_and_cursor
+        {
+            anchors.extend(lines_above.link_anchors());
+            anchors.extend(row_anchors(viewport));
+        }
+        anchors.extend(
+            self.preceding_char
+                .and_then(|character| character.link_anchor),
+        );
+        anchors
+    }
+    pub fn mouse_reporting(&self) -> bool {
+        self.mouse_tracking != MouseTracking::Off
+    }
+    /// Encode `event` at `position` (relative to the viewport) the way the application asked
+    /// for it, returns None if this event should not be reported
+    pub fn mouse_event_report(&self, event: MouseEvent, position: &Position) -> Option<Vec<u8>> {
+        let should_report = match self.mouse_tracking {
+            MouseTracking::Off => false,
+            MouseTracking::X10 => matches!(
+                event,
+                MouseEvent::LeftPress
+                    | MouseEvent::RightPress
+                    | MouseEvent::MiddlePress
+                    | MouseEvent::WheelUp
+                    | MouseEvent::WheelDown
+            ),
+            MouseTracking::Normal => !matches!(
+                event,
+                MouseEvent::LeftDrag
+                    | MouseEvent::RightDrag
+                    | MouseEvent::MiddleDrag
+                    | MouseEvent::Motion
+            ),
+            MouseTracking::ButtonEvent => event != MouseEvent::Motion,
+            MouseTracking::AnyEvent => true,
+        };
+        let is_release = matches!(
+            event,
+            MouseEvent::LeftRelease | MouseEvent::RightRelease | MouseEvent::MiddleRelease
+        );
+        if !should_report {
+            return None;
+        }
+        let button = match event {
+            MouseEvent::LeftPress => 0,
+            MouseEvent::MiddlePress => 1,
+            MouseEvent::RightPress => 2,
+            // only the sgr encoding tells which button was released
+            MouseEvent::LeftRelease if self.mouse_encoding == MouseEncoding::Sgr => 0,
+            MouseEvent::MiddleRelease if self.mouse_encoding == MouseEncoding::Sgr => 1,
+            MouseEvent::RightRelease if self.mouse_encoding == MouseEncoding::Sgr => 2,
+            MouseEvent::LeftRelease | MouseEvent::MiddleRelease | MouseEvent::RightRelease => 3,
+            MouseEvent::LeftDrag => 32,
+            MouseEvent::MiddleDrag => 33,
+            MouseEvent::RightDrag => 34,
+            MouseEvent::Motion => 35,
+            MouseEvent::WheelUp => 64,
+            MouseEvent::WheelDown => 65,
+        };
+        // the position might be outside of the pane while dragging, mouse coordinates are 1 based
+        let x = std::cmp::min(position.column.0, self.width.saturating_sub(1)) + 1;
+        let y = std::cmp::min(
+            std::cmp::max(position.line.0, 0) as usize,
+            self.height.saturating_sub(1),
+        ) + 1;
+        match self.mouse_encoding {
+            MouseEncoding::Sgr => {
+                let suffix = if is_release { 'm' } else { 'M' };
+                Some(format!("\u{1b}[<{};{};{}{}", button, x, y, suffix).into_bytes())
+            }
+            MouseEncoding::Urxvt => {
+                Some(format!("\u{1b}[{};{};{}M", button + 32, x, y).into_bytes())
+            }
+            MouseEncoding::X10 => {
+                if x > 223 || y > 223 {
+                    // does not fit in a single byte
+                    return None;
+                }
+                Some(vec![
+                    0x1b,
+                    b'[',
+                    b'M',
+                    button + 32,
+                    x as u8 + 32,
+                    y as u8 + 32,
+                ])
+            }
+        }
+    }
+    /// The text of every row, from the oldest one of the scrollback to the last one below the
+    /// viewport, and whether each row is canonical
+    fn text_rows(&self) -> impl Iterator<Item = (Cow<'_, str>, bool)> + '_ {
+        let scrollback = self
+            .lines_above
+            .text_rows()
+            .map(|(text, is_canonical)| (Cow::Borrowed(text), is_canonical));
+        let screen = self
+            .viewport
+            .iter()
+            .chain(self.lines_below.iter())
+            .map(|row| (Cow::Owned(row.text()), row.is_canonical));
+        scrollback.chain(screen)
+    }
+    /// Returns the text of the whole scrollback, including the viewport, one line per row.
+    pub fn dump_screen(&self) -> String {
+        self.text_rows()
+            .map(|(text, _)| String::from(text.trim_end()))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+    /// Like `dump_screen`, but with one line per canonical line rather than per row, and
+    /// optionally with the escape sequences of the character styles
+    pub fn dump_scrollback(&self, with_ansi_styles: bool) -> String {
+        if !with_ansi_styles {
+            let mut lines: Vec<String> = vec![];
+            let mut line = String::new();
+            for (i, (text, is_canonical)) in self.text_rows().enumerate() {
+                if is_canonical && i > 0 {
+                    lines.push(String::from(line.trim_end()));
+                    line.clear();
+                }
+                line.push_str(&text);
+            }
+            lines.push(String::from(line.trim_end()));
+            return lines.join("\n");
+        }
+        let mut lines: Vec<String> = vec![];
+        let mut line = String::new();
+        // each line starts from the default styles, so that it can be read on its own
+        let mut character_styles = reset_character_styles();
+        let rows = self
+            .lines_above
+            .iter()
+            .chain(self.viewport.iter().cloned())
+            .chain(self.lines_below.iter().cloned());
+        for (i, row) in rows.enumerate() {
+            if row.is_canonical && i > 0 {
+                lines.push(finish_dumped_line(line, &mut character_styles));
+                line = String::new();
+            }
+            for character in row.columns {
+                let styles = if character.styles == CharacterStyles::new() {
+                    reset_character_styles()
+                } else {
+                    character.styles
+                };
+                if styles != character_styles {
+                    if let Some(new_styles) = character_styles.update_and_return_diff(&styles) {
+                        line.push_str(&new_styles.to_string());
+                    }
+                }
+                line.push(character.character);
+            }
+        }
+        lines.push(finish_dumped_line(line, &mut character_styles));
+        lines.join("\n")
+    }
+
     fn update_selected_lines(&mut self, old_selection: &Selection, new_selection: &Selection) {
         for l in old_selection.diff(new_selection, self.height) {
             self.output_buffer.update_line(l as usize);
@@ -1253,9 +2062,11 @@ impl Perform for Grid {
             character: c,
             width: c.width().unwrap_or(0),
             styles: self.cursor.pending_styles,
+            link_anchor: self.link_handler.pending_link_anchor(),
         };
         self.set_preceding_character(terminal_character);
         self.add_character(terminal_character);
+        self.mark_search_outdated();
     }
 
     fn execute(&mut self, byte: u8) {
@@ -1288,16 +2099,42 @@ impl Perform for Grid {
         }
     }
 
-    fn hook(&mut self, _params: &Params, _intermediates: &[u8], _ignore: bool, _c: char) {
-        // TBD
+    fn hook(&mut self, params: &Params, _intermediates: &[u8], _ignore: bool, c: char) {
+        if c == 'q' {
+            // sixel image
+            let background_select = params.iter().nth(1).map(|param| param[0]).unwrap_or(0);
+            self.sixel_decoder = Some(SixelDecoder::new(background_select));
+        }
     }
 
-    fn put(&mut self, _byte: u8) {
-        // TBD
+    fn put(&mut self, byte: u8) {
+        if let Some(sixel_decoder) = self.sixel_decoder.as_mut() {
+            sixel_decoder.put(byte);
+        }
     }
 
     fn unhook(&mut self) {
-        // TBD
+        if let Some(sixel_decoder) = self.sixel_decoder.take() {
+            let character_cell_size = self.character_cell_size;
+            // without knowing the size of a character cell, we can't place the image on the grid
+            if let (Some(image), Some(character_cell_size)) =
+                (sixel_decoder.finish(), character_cell_size)
+            {
+                self.image_layer.add(
+                    image,
+                    self.next_image_id,
+                    self.cursor.y,
+                    self.cursor.x,
+                    character_cell_size,
+                );
+                self.next_image_id += 1;
+                // like xterm, leave the cursor on the last line of the image
+                for _ in 1..self.image_layer.rows_of_last_image() {
+                    self.add_canonical_line();
+                }
+                self.mark_for_rerender();
+            }
+        }
     }
 
     fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
@@ -1322,6 +2159,15 @@ impl Perform for Grid {
                 }
             }
 
+            // Hyperlink.
+            b"8" => {
+                if self.link_handler.is_full() {
+                    let anchors_in_use = self.link_anchors_in_use();
+                    self.link_handler.retain_links(&anchors_in_use);
+                }
+                self.link_handler.dispatch_osc8(params);
+            }
+
             // Set color index.
             b"4" => {
                 // TBD: set color index - currently unsupported
@@ -1436,6 +2282,8 @@ impl Perform for Grid {
     }
 
     fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, c: char) {
+        // most sequences move the cursor or change styles, but erasing lines changes the text
+        self.mark_search_outdated();
         let mut params_iter = params.iter();
         let mut next_param_or = |default: u16| {
             params_iter
@@ -1479,6 +2327,7 @@ impl Perform for Grid {
                     self.clear_all_before_cursor(char_to_replace);
                 } else if clear_type == 2 {
                     self.fill_viewport(char_to_replace);
+                    self.image_layer.clear_viewport(self.height);
                 }
             };
         } else if c == 'H' || c == 'f' {
@@ -1507,45 +2356,60 @@ impl Perform for Grid {
                 _ => false,
             };
             if first_intermediate_is_questionmark {
-                match params_iter.next().map(|param| param[0]) {
-                    Some(1049) => {
-                        if let Some((
-                            alternative_lines_above,
-                            alternative_viewport,
-                            alternative_cursor,
-                        )) = self.alternative_lines_above_viewport_and_cursor.as_mut()
-                        {
-                            std::mem::swap(&mut self.lines_above, alternative_lines_above);
-                            std::mem::swap(&mut self.viewport, alternative_viewport);
-                            std::mem::swap(&mut self.cursor, alternative_cursor);
+                // several modes can be set at once, like `?1000;1006h`
+                for param in params_iter.by_ref() {
+                    match param[0] {
+                        1049 => {
+                            if let Some((
+                                alternative_lines_above,
+                                alternative_viewport,
+                                alternative_cursor,
+                            )) = self.alternative_lines_above_viewport_and_cursor.as_mut()
+                            {
+                                std::mem::swap(&mut self.lines_above, alternative_lines_above);
+                                std::mem::swap(&mut self.viewport, alternative_viewport);
+                                std::mem::swap(&mut self.cursor, alternative_cursor);
+                            }
+                            self.alternative_lines_above_viewport_and_cursor = None;
+                            if let Some(image_layer) = self.alternative_image_layer.take() {
+                                self.image_layer = image_layer;
+                            }
+                            self.clear_viewport_before_rendering = true;
+                            self.change_size(self.height, self.width); // the alternative_viewport might have been of a different size...
+                            self.mark_for_rerender();
                         }
-                        self.alternative_lines_above_viewport_and_cursor = None;
-                        self.clear_viewport_before_rendering = true;
-                        self.change_size(self.height, self.width); // the alternative_viewport might have been of a different size...
-                        self.mark_for_rerender();
-                    }
-                    Some(25) => {
-                        self.hide_cursor();
-                        self.mark_for_rerender();
-                    }
-                    Some(1) => {
-                        self.cursor_key_mode = false;
-                    }
-                    Some(3) => {
-                        // DECCOLM - only side effects
-                        self.scroll_region = None;
-                        self.clear_all(EMPTY_TERMINAL_CHARACTER);
-                        self.cursor.x = 0;
-                        self.cursor.y = 0;
-                    }
-                    Some(6) => {
-                        self.erasure_mode = false;
-                    }
-                    Some(7) => {
-                        self.disable_linewrap = true;
+                        25 => {
+                            self.hide_cursor();
+                            self.mark_for_rerender();
+                        }
+                        1 => {
+                            self.cursor_key_mode = false;
+                        }
+                        3 => {
+                            // DECCOLM - only side effects
+                            self.scroll_region = None;
+                            self.clear_all(EMPTY_TERMINAL_CHARACTER);
+                            self.cursor.x = 0;
+                            self.cursor.y = 0;
+                        }
+                        6 => {
+                            self.erasure_mode = false;
+                        }
+                        7 => {
+                            self.disable_linewrap = true;
+                        }
+                        9 | 1000 | 1002 | 1003 => {
+                            self.mouse_tracking = MouseTracking::Off;
+                        }
+                        1006 | 1015 => {
+                            self.mouse_encoding = MouseEncoding::X10;
+                        }
+                        2004 => {
+                            self.bracketed_paste = false;
+                        }
+                        _ => {}
                     }
-                    _ => {}
-                };
+                }
             } else if let Some(4) = params_iter.next().map(|param| param[0]) {
                 self.insert_mode = false;
             }
@@ -1556,43 +2420,71 @@ impl Perform for Grid {
                 _ => false,
             };
             if first_intermediate_is_questionmark {
-                match params_iter.next().map(|param| param[0]) {
-                    Some(25) => {
-                        self.show_cursor();
-                        self.mark_for_rerender();
-                    }
-                    Some(1049) => {
-                        let current_lines_above = std::mem::replace(
-                            &mut self.lines_above,
-                            VecDeque::with_capacity(SCROLL_BACK),
-                        );
-                        let current_viewport = std::mem::replace(
-                            &mut self.viewport,
-                            vec![Row::new(self.width).canonical()],
-                        );
-                        let current_cursor = std::mem::replace(&mut self.cursor, Cursor::new(0, 0));
-                        self.alternative_lines_above_viewport_and_cursor =
-                            Some((current_lines_above, current_viewport, current_cursor));
-                        self.clear_viewport_before_rendering = true;
-                    }
-                    Some(1) => {
-                        self.cursor_key_mode = true;
-                    }
-                    Some(3) => {
-                        // DECCOLM - only side effects
-                        self.scroll_region = None;
-                        self.clear_all(EMPTY_TERMINAL_CHARACTER);
-                        self.cursor.x = 0;
-                        self.cursor.y = 0;
-                    }
-                    Some(6) => {
-                        self.erasure_mode = true;
-                    }
-                    Some(7) => {
-                        self.disable_linewrap = false;
+                // several modes can be set at once, like `?1000;1006h`
+                for param in params_iter.by_ref() {
+                    match param[0] {
+                        25 => {
+                            self.show_cursor();
+                            self.mark_for_rerender();
+                        }
+                        1049 => {
+                            let scroll_buffer_size = self.lines_above.limit();
+                            let current_lines_above = std::mem::replace(
+                                &mut self.lines_above,
+                                Scrollback::new(scroll_buffer_size),
+                            );
+                            let current_viewport = std::mem::replace(
+                                &mut self.viewport,
+                                vec![Row::new(self.width).canonical()],
+                            );
+                            let current_cursor =
+                                std::mem::replace(&mut self.cursor, Cursor::new(0, 0));
+                            self.alternative_lines_above_viewport_and_cursor =
+                                Some((current_lines_above, current_viewport, current_cursor));
+                            self.alternative_image_layer =
+                                Some(std::mem::take(&mut self.image_layer));
+                            self.clear_viewport_before_rendering = true;
+                        }
+                        1 => {
+                            self.cursor_key_mode = true;
+                        }
+                        3 => {
+                            // DECCOLM - only side effects
+                            self.scroll_region = None;
+                            self.clear_all(EMPTY_TERMINAL_CHARACTER);
+                            self.cursor.x = 0;
+                            self.cursor.y = 0;
+                        }
+                        6 => {
+                            self.erasure_mode = true;
+                        }
+                        7 => {
+                            self.disable_linewrap = false;
+                        }
+                        9 => {
+                            self.mouse_tracking = MouseTracking::X10;
+                        }
+                        1000 => {
+                            self.mouse_tracking = MouseTracking::Normal;
+                        }
+                        1002 => {
+                            self.mouse_tracking = MouseTracking::ButtonEvent;
+                        }
+                        1003 => {
+                            self.mouse_tracking = MouseTracking::AnyEvent;
+                        }
+                        1006 => {
+                            self.mouse_encoding = MouseEncoding::Sgr;
+                        }
+                        1015 => {
+                            self.mouse_encoding = MouseEncoding::Urxvt;
+                        }
+                        2004 => {
+                            self.bracketed_paste = true;
+                        }
+                        _ => {}
                     }
-                    _ => {}
-                };
+                }
             } else if let Some(4) = params_iter.next().map(|param| param[0]) {
                 self.insert_mode = true;
             }
@@ -1713,7 +2605,7 @@ impl Perform for Grid {
             match intermediates.get(0) {
                 None | Some(0) => {
                     // primary device attributes
-                    let terminal_capabilities = "\u{1b}[?6c";
+                    let terminal_capabilities = self.device_attributes();
                     self.pending_messages_to_pty
                         .push(terminal_capabilities.as_bytes().to_vec());
                 }
@@ -1747,9 +2639,27 @@ impl Perform for Grid {
         } else if c == 't' {
             match next_param_or(1) as usize {
                 14 => {
-                    // TODO: report text area size in pixels, currently unimplemented
-                    // to solve this we probably need to query the user's terminal for the cursor
-                    // size and then use it as a multiplier
+                    // report text area size in pixels
+                    if let Some(character_cell_size) = self.character_cell_size {
+                        let text_area_pixel_size_report = format!(
+                            "\x1b[4;{};{}t",
+                            character_cell_size.height * self.height,
+                            character_cell_size.width * self.width
+                        );
+                        self.pending_messages_to_pty
+                            .push(text_area_pixel_size_report.as_bytes().to_vec());
+                    }
+                }
+                16 => {
+                    // report character cell size in pixels
+                    if let Some(character_cell_size) = self.character_cell_size {
+                        let character_cell_size_report = format!(
+                            "\x1b[6;{};{}t",
+                            character_cell_size.height, character_cell_size.width
+                        );
+                        self.pending_messages_to_pty
+                            .push(character_cell_size_report.as_bytes().to_vec());
+                    }
                 }
                 18 => {
                     // report text area
@@ -1825,7 +2735,7 @@ impl Perform for Grid {
                 self.save_cursor_position();
             }
             (b'Z', None) => {
-                let terminal_capabilities = "\u{1b}[?6c";
+                let terminal_capabilities = self.device_attributes();
                 self.pending_messages_to_pty
                     .push(terminal_capabilities.as_bytes().to_vec());
             }
@@ -1885,6 +2795,10 @@ impl Row {
         self.columns.push(terminal_character);
         self
     }
+    /// The characters of the row, without their styles
+    pub fn text(&self) -> String {
+        self.columns.iter().map(|c| c.character).collect()
+    }
     pub fn canonical(mut self) -> Self {
         self.is_canonical = true;
         self
@@ -0,0 +1,120 @@
+use std::collections::{HashMap, HashSet};
+use std::str;
+
+/// Refers to a [`Link`] stored in a [`LinkHandler`], this is what terminal characters carry
+/// so that they don't have to hold the whole uri
+pub type LinkAnchor = u16;
+
+/// How many links a [`LinkHandler`] can refer to at the same time
+const MAX_LINKS: usize = LinkAnchor::MAX as usize + 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Link {
+    pub id: Option<String>,
+    pub uri: String,
+}
+
+/// Keeps track of the hyperlinks (OSC 8) printed to a grid
+#[derive(Debug, Clone, Default)]
+pub struct LinkHandler {
+    links: HashMap<LinkAnchor, Link>,
+    anchors: HashMap<Link, LinkAnchor>,
+    next_anchor: LinkAnchor,
+    pending_link_anchor: Option<LinkAnchor>,
+}
+
+impl LinkHandler {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Handle `OSC 8 ; params ; uri ST`, the characters printed after it are part of the link
+    /// until it is ended by an OSC 8 with an empty uri
+    pub fn dispatch_osc8(&mut self, params: &[&[u8]]) {
+        let id = params.get(1).and_then(|link_params| {
+            link_params
+                .split(|&b| b == b':')
+                .find(|param| param.starts_with(b"id="))
+                .and_then(|param| str::from_utf8(&param[3..]).ok())
+                .map(|id| id.to_owned())
+        });
+        // the uri might contain semicolons itself
+        let uri = params
+            .get(2..)
+            .unwrap_or(&[])
+            .iter()
+            .flat_map(|part| str::from_utf8(part))
+            .collect::<Vec<&str>>()
+            .join(";");
+        self.pending_link_anchor = if uri.is_empty() {
+            None
+        } else {
+            Some(self.intern(Link { id, uri }))
+        };
+    }
+    fn intern(&mut self, link: Link) -> LinkAnchor {
+        if let Some(anchor) = self.anchors.get(&link) {
+            return *anchor;
+        }
+        let anchor = self.free_anchor();
+        self.next_anchor = anchor.wrapping_add(1);
+        if let Some(replaced_link) = self.links.insert(anchor, link.clone()) {
+            self.anchors.remove(&replaced_link);
+        }
+        self.anchors.insert(link, anchor);
+        anchor
+    }
+    /// The first anchor from `next_anchor` on that does not refer to a link - or, if every
+    /// anchor is still in use, `next_anchor` itself, whose link is then replaced
+    fn free_anchor(&self) -> LinkAnchor {
+        let mut anchor = self.next_anchor;
+        if !self.is_full() {
+            while self.links.contains_key(&anchor) {
+                anchor = anchor.wrapping_add(1);
+            }
+        }
+        anchor
+    }
+    /// Whether a new link would have to replace one of the links already stored, see
+    /// [`LinkHandler::retain_links`]
+    pub fn is_full(&self) -> bool {
+        self.links.len() >= MAX_LINKS
+    }
+    /// Forgets the links whose anchors are not in `anchors_in_use` (the ones the characters
+    /// of the grid still carry), so that their anchors can be given to new links
+    pub fn retain_links(&mut self, anchors_in_use: &HashSet<LinkAnchor>) {
+        let pending_link_anchor = self.pending_link_anchor;
+        let anchors = &mut self.anchors;
+        self.links.retain(|anchor, link| {
+            let in_use = anchors_in_use.contains(anchor) || pending_link_anchor == Some(*anchor);
+            if !in_use {
+                anchors.remove(link);
+            }
+            in_use
+        });
+    }
+    pub fn pending_link_anchor(&self) -> Option<LinkAnchor> {
+        self.pending_link_anchor
+    }
+    pub fn link(&self, anchor: LinkAnchor) -> Option<&Link> {
+        self.links.get(&anchor)
+    }
+    /// The OSC 8 sequence that starts the link of `anchor`, or ends the current link if None
+    pub fn output_osc8(&self, anchor: Option<LinkAnchor>) -> String {
+        match anchor.and_then(|anchor| self.link(anchor)) {
+            Some(link) => {
+                let link_params = link
+                    .id
+                    .as_ref()
+                    .map(|id| format!("id={}", id))
+                    .unwrap_or_default();
+                format!("\u{1b}]8;{};{}\u{1b}\\", link_params, link.uri)
+            }
+            None => "\u{1b}]8;;\u{1b}\\".to_owned(),
+        }
+    }
+    pub fn clear(&mut self) {
+        self.links.clear();
+        self.anchors.clear();
+        self.pending_link_anchor = None;
+    }
+}
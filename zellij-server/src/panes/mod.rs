@@ -1,10 +1,18 @@
+mod copy_mode;
 mod grid;
+mod link_handler;
 mod plugin_pane;
+mod scrollback;
+mod search;
 mod selection;
+mod sixel;
 mod terminal_character;
 mod terminal_pane;
 
 pub use grid::*;
+pub use link_handler::*;
 pub(crate) use plugin_pane::*;
+pub use scrollback::*;
+pub use sixel::ImagePlacement;
 pub use terminal_character::*;
 pub use terminal_pane::*;
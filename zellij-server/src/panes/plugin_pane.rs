@@ -3,7 +3,6 @@ use std::time::Instant;
 use std::unimplemented;
 
 use crate::panes::PaneId;
-use crate::pty::VteBytes;
 use crate::tab::Pane;
 use crate::wasm_vm::PluginInstruction;
 use zellij_utils::{channels::SenderWithContext, pane_size::PositionAndSize};
@@ -82,9 +81,6 @@ impl Pane for PluginPane {
         self.position_and_size_override = Some(position_and_size_override);
         self.should_render = true;
     }
-    fn handle_pty_bytes(&mut self, _event: VteBytes) {
-        unimplemented!()
-    }
     fn cursor_coordinates(&self) -> Option<(usize, usize)> {
         None
     }
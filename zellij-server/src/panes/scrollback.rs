@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+
+use unicode_width::UnicodeWidthChar;
+
+use super::grid::Row;
+use super::link_handler::LinkAnchor;
+use super::terminal_character::{CharacterStyles, TerminalCharacter};
+
+/// The scrollback size of panes, unless the `scroll_buffer_size` option or their layout
+/// sets another one
+pub const DEFAULT_SCROLL_BUFFER_SIZE: usize = 10_000;
+
+/// A run of consecutive characters in a [`CompactRow`] that share the same styles and link
+#[derive(Clone, Copy, Debug)]
+struct StyleRun {
+    length: u32,
+    styles: CharacterStyles,
+    link_anchor: Option<LinkAnchor>,
+}
+
+/// A row that left the viewport, stored as its text and the style runs over it rather than one
+/// [`TerminalCharacter`] per column - most rows only have a handful of differently styled parts
+#[derive(Clone, Debug)]
+struct CompactRow {
+    text: Box<str>,
+    style_runs: Box<[StyleRun]>,
+    is_canonical: bool,
+}
+
+impl CompactRow {
+    fn from_row(row: Row) -> Self {
+        let mut text = String::with_capacity(row.columns.len());
+        let mut style_runs: Vec<StyleRun> = vec![];
+        for character in row.columns {
+            text.push(character.character);
+            match style_runs.last_mut() {
+                Some(run)
+                    if run.styles == character.styles
+                        && run.link_anchor == character.link_anchor =>
+                {
+                    run.length += 1;
+                }
+                _ => style_runs.push(StyleRun {
+                    length: 1,
+                    styles: character.styles,
+                    link_anchor: character.link_anchor,
+                }),
+            }
+        }
+        CompactRow {
+            text: text.into_boxed_str(),
+            style_runs: style_runs.into_boxed_slice(),
+            is_canonical: row.is_canonical,
+        }
+    }
+    fn to_row(&self) -> Row {
+        let mut characters = self.text.chars();
+        let mut columns = Vec::with_capacity(self.text.len());
+        for run in self.style_runs.iter() {
+            for character in characters.by_ref().take(run.length as usize) {
+                columns.push(TerminalCharacter {
+                    character,
+                    styles: run.styles,
+                    width: character.width().unwrap_or(0),
+                    link_anchor: run.link_anchor,
+                });
+            }
+        }
+        let row = Row::from_columns(columns);
+        if self.is_canonical {
+            row.canonical()
+        } else {
+            row
+        }
+    }
+}
+
+/// The lines above the viewport of a grid, holding at most `limit` rows
+/// (the oldest rows are dropped to make room for new ones)
+#[derive(Clone, Debug)]
+pub struct Scrollback {
+    rows: VecDeque<CompactRow>,
+    limit: usize,
+}
+
+impl Scrollback {
+    pub fn new(limit: usize) -> Self {
+        Scrollback {
+            rows: VecDeque::new(),
+            limit,
+        }
+    }
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.drop_rows_over_limit();
+    }
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+    pub fn push_back(&mut self, row: Row) {
+        if self.limit == 0 {
+            return;
+        }
+        self.rows.push_back(CompactRow::from_row(row));
+        self.drop_rows_over_limit();
+    }
+    pub fn pop_back(&mut self) -> Option<Row> {
+        self.rows.pop_back().map(|row| row.to_row())
+    }
+    pub fn get(&self, index: usize) -> Option<Row> {
+        self.rows.get(index).map(|row| row.to_row())
+    }
+    /// The rows from the oldest to the most recent one
+    pub fn iter(&self) -> impl Iterator<Item = Row> + '_ {
+        self.rows.iter().map(|row| row.to_row())
+    }
+    /// The text of the rows from the oldest to the most recent one, and whether each row is
+    /// canonical, without rebuilding their styles
+    pub fn text_rows(&self) -> impl Iterator<Item = (&str, bool)> + '_ {
+        self.rows.iter().map(|row| (&*row.text, row.is_canonical))
+    }
+    /// The link anchors carried by the characters of all rows, without rebuilding them
+    pub fn link_anchors(&self) -> impl Iterator<Item = LinkAnchor> + '_ {
+        self.rows
+            .iter()
+            .flat_map(|row| row.style_runs.iter())
+            .filter_map(|run| run.link_anchor)
+    }
+    /// Removes the last canonical row and the rows it wraps into, in order
+    pub fn pop_bottom_canonical_row_and_wraps(&mut self) -> Vec<Row> {
+        let mut rows = vec![];
+        while let Some(row) = self.pop_back() {
+            let is_canonical = row.is_canonical;
+            rows.insert(0, row);
+            if is_canonical {
+                break;
+            }
+        }
+        rows
+    }
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+    fn drop_rows_over_limit(&mut self) {
+        while self.rows.len() > self.limit {
+            self.rows.pop_front();
+        }
+    }
+}
+
+impl Extend<Row> for Scrollback {
+    fn extend<T: IntoIterator<Item = Row>>(&mut self, rows: T) {
+        for row in rows {
+            self.push_back(row);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use regex::{Regex, RegexBuilder};
+
+use zellij_utils::position::{Column, Line, Position};
+use zellij_utils::zellij_tile::data::SearchInfo;
+
+use super::selection::Selection;
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    pub text: String,
+    pub case_sensitive: bool,
+    pub regex: bool,
+}
+
+impl SearchQuery {
+    /// None if there is nothing to search for, or the text is not a valid regular expression
+    pub fn to_regex(&self) -> Option<Regex> {
+        if self.text.is_empty() {
+            return None;
+        }
+        let pattern = if self.regex {
+            self.text.clone()
+        } else {
+            regex::escape(&self.text)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .ok()
+    }
+}
+
+/// Collects the text of a line and its wraps, along with the position of each of its characters
+#[derive(Debug, Default)]
+pub struct SearchableLine {
+    text: String,
+    // (byte offset in text, position, width) of every character
+    characters: Vec<(usize, Position, usize)>,
+}
+
+impl SearchableLine {
+    pub fn push(&mut self, character: char, line: isize, column: usize, width: usize) {
+        let position = Position {
+            line: Line(line),
+            column: Column(column),
+        };
+        self.characters.push((self.text.len(), position, width));
+        self.text.push(character);
+    }
+    /// Appends the matches of `regex` to `matches` and empties this line
+    pub fn find_matches(&mut self, regex: &Regex, matches: &mut Vec<Selection>) {
+        for found in regex.find_iter(&self.text) {
+            if found.start() == found.end() {
+                continue;
+            }
+            let first = self
+                .characters
+                .iter()
+                .position(|(offset, _, _)| *offset == found.start());
+            let last = self
+                .characters
+                .iter()
+                .rposition(|(offset, _, _)| *offset < found.end());
+            if let (Some(first), Some(last)) = (first, last) {
+                let (_, start, _) = self.characters[first];
+                let (_, last_position, last_width) = self.characters[last];
+                let end = Position {
+                    line: last_position.line,
+                    column: Column(last_position.column.0 + last_width),
+                };
+                let mut selection = Selection::default();
+                selection.start(start);
+                selection.end(Some(&end));
+                matches.push(selection);
+            }
+        }
+        self.text.clear();
+        self.characters.clear();
+    }
+}
+
+/// The matches of a search in the scrollback, from the oldest to the most recent one.
+/// They are positioned relative to the viewport and moved along with it, like the [`Selection`].
+#[derive(Debug, Clone, Default)]
+pub struct SearchResult {
+    pub query: SearchQuery,
+    pub matches: Vec<Selection>,
+    pub active: Option<usize>,
+}
+
+impl SearchResult {
+    /// Some(true) if the character at `line` and `column` of the viewport is part of the active
+    /// match, Some(false) if it is part of another match
+    pub fn match_at(&self, line: usize, column: usize) -> Option<bool> {
+        let position = Position {
+            line: Line(line as isize),
+            column: Column(column),
+        };
+        // matches never overlap, so only the last one starting before the position can contain it
+        let index = match self
+            .matches
+            .binary_search_by(|found| found.start.partial_cmp(&position).unwrap())
+        {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+        if self.matches[index].contains(line, column) {
+            Some(self.active == Some(index))
+        } else {
+            None
+        }
+    }
+    pub fn active_match(&self) -> Option<&Selection> {
+        self.active.and_then(|index| self.matches.get(index))
+    }
+    pub fn move_up(&mut self, lines: usize) {
+        for found in &mut self.matches {
+            found.move_up(lines);
+        }
+    }
+    pub fn move_down(&mut self, lines: usize) {
+        for found in &mut self.matches {
+            found.move_down(lines);
+        }
+    }
+    pub fn clear(&mut self) {
+        *self = Default::default();
+    }
+    pub fn info(&self) -> SearchInfo {
+        SearchInfo {
+            query: self.query.text.clone(),
+            case_sensitive: self.query.case_sensitive,
+            regex: self.query.regex,
+            match_count: self.matches.len(),
+            active_match: self.active,
+        }
+    }
+}
@@ -1,14 +1,16 @@
 use std::{collections::HashSet, ops::Range};
 
-use zellij_utils::position::Position;
+use zellij_utils::position::{Column, Line, Position};
 
 // The selection is empty when start == end
 // it includes the character at start, and everything before end.
-#[derive(Debug, Clone)]
+// A block selection includes the same columns on every line, those from start to before end.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Selection {
     pub start: Position,
     pub end: Position,
     active: bool, // used to handle moving the selection up and down
+    block: bool,
 }
 
 impl Default for Selection {
@@ -17,6 +19,7 @@ impl Default for Selection {
             start: Position::new(0, 0),
             end: Position::new(0, 0),
             active: false,
+            block: false,
         }
     }
 }
@@ -24,10 +27,19 @@ impl Default for Selection {
 impl Selection {
     pub fn start(&mut self, start: Position) {
         self.active = true;
+        self.block = false;
         self.start = start;
         self.end = start;
     }
 
+    pub fn set_block(&mut self, block: bool) {
+        self.block = block;
+    }
+
+    pub fn is_block(&self) -> bool {
+        self.block
+    }
+
     pub fn to(&mut self, to: Position) {
         self.end = to
     }
@@ -47,6 +59,13 @@ impl Selection {
             (self.end, self.start)
         };
 
+        if self.block {
+            let sorted = self.sorted();
+            return sorted.start.line.0 <= row
+                && row <= sorted.end.line.0
+                && sorted.start.column.0 <= col
+                && col < sorted.end.column.0;
+        }
         if (start.line.0) < row && row < end.line.0 {
             return true;
         }
@@ -66,10 +85,24 @@ impl Selection {
     pub fn reset(&mut self) {
         self.start = Position::new(0, 0);
         self.end = self.start;
+        self.block = false;
     }
 
+    /// The selection with its start before its end, for a block selection
+    /// the start is its top left corner and the end its bottom right one
     pub fn sorted(&self) -> Self {
-        let (start, end) = if self.start <= self.end {
+        let (start, end) = if self.block {
+            (
+                Position {
+                    line: Line(self.start.line.0.min(self.end.line.0)),
+                    column: Column(self.start.column.0.min(self.end.column.0)),
+                },
+                Position {
+                    line: Line(self.start.line.0.max(self.end.line.0)),
+                    column: Column(self.start.column.0.max(self.end.column.0)),
+                },
+            )
+        } else if self.start <= self.end {
             (self.start, self.end)
         } else {
             (self.end, self.start)
@@ -78,6 +111,7 @@ impl Selection {
             start,
             end,
             active: self.active,
+            block: self.block,
         }
     }
 
@@ -0,0 +1,419 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use zellij_utils::pane_size::SizeInPixels;
+
+// images larger than this are cut off, so that a misbehaving program can't exhaust our memory
+const MAX_SIXEL_DIMENSION: usize = 4096;
+// the oldest images are dropped once there are more than this in a single pane
+const MAX_IMAGES_PER_PANE: usize = 128;
+// or once the images of a pane have more pixels than this, which take about 64 MB
+const MAX_PIXELS_PER_PANE: usize = 2 * MAX_SIXEL_DIMENSION * MAX_SIXEL_DIMENSION;
+
+// the default color registers of the VT340, in RGB percentages
+const DEFAULT_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (20, 20, 80),
+    (80, 13, 13),
+    (20, 80, 20),
+    (80, 20, 80),
+    (20, 80, 80),
+    (80, 80, 20),
+    (53, 53, 53),
+    (26, 26, 26),
+    (33, 33, 60),
+    (60, 26, 26),
+    (33, 60, 33),
+    (60, 33, 60),
+    (33, 60, 60),
+    (60, 60, 33),
+    (80, 80, 80),
+];
+
+/// A decoded sixel image, pixels refer to the color registers of the palette and are
+/// transparent if empty
+#[derive(Debug, Clone)]
+pub struct SixelImage {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<Option<u8>>,
+    palette: Vec<(u8, u8, u8)>,
+}
+
+impl SixelImage {
+    fn pixel(&self, x: usize, y: usize) -> Option<u8> {
+        self.pixels[y * self.width + x]
+    }
+    /// Encode the part of the image starting at `x`/`y` of size `width`/`height` (in pixels)
+    /// as a sixel DCS sequence, to be drawn at the current cursor position
+    pub fn encode(&self, x: usize, y: usize, width: usize, height: usize) -> String {
+        let width = std::cmp::min(width, self.width.saturating_sub(x));
+        let height = std::cmp::min(height, self.height.saturating_sub(y));
+        // transparent pixels are left alone (P2 = 1)
+        let mut sixel = format!("\u{1b}P0;1;0q\"1;1;{};{}", width, height);
+        let mut used_colors = BTreeSet::new();
+        for pixel_y in y..y + height {
+            for pixel_x in x..x + width {
+                if let Some(color) = self.pixel(pixel_x, pixel_y) {
+                    used_colors.insert(color);
+                }
+            }
+        }
+        for color in &used_colors {
+            let (r, g, b) = self.palette[*color as usize];
+            let _ = write!(sixel, "#{};2;{};{};{}", color, r, g, b);
+        }
+        for band_y in (y..y + height).step_by(6) {
+            let band_height = std::cmp::min(6, y + height - band_y);
+            for color in &used_colors {
+                let band: Vec<u8> = (x..x + width)
+                    .map(|pixel_x| {
+                        (0..band_height)
+                            .filter(|row| self.pixel(pixel_x, band_y + row) == Some(*color))
+                            .fold(0, |bits, row| bits | 1 << row)
+                    })
+                    .collect();
+                if band.iter().all(|bits| *bits == 0) {
+                    continue;
+                }
+                let _ = write!(sixel, "#{}", color);
+                encode_band(&mut sixel, &band);
+                sixel.push('$');
+            }
+            sixel.push('-');
+        }
+        sixel.push_str("\u{1b}\\");
+        sixel
+    }
+}
+
+fn encode_band(sixel: &mut String, band: &[u8]) {
+    let mut i = 0;
+    while i < band.len() {
+        let bits = band[i];
+        let repeat = band[i..].iter().take_while(|b| **b == bits).count();
+        let character = (bits + 0x3f) as char;
+        if repeat > 3 {
+            let _ = write!(sixel, "!{}{}", repeat, character);
+        } else {
+            for _ in 0..repeat {
+                sixel.push(character);
+            }
+        }
+        i += repeat;
+    }
+}
+
+// sixel colors are given in HLS with blue at 0 degrees, red at 120 and green at 240
+fn hls_to_rgb(hue: u16, lightness: u16, saturation: u16) -> (u8, u8, u8) {
+    let hue = ((hue % 360 + 240) % 360) as f64;
+    let lightness = std::cmp::min(lightness, 100) as f64 / 100.0;
+    let saturation = std::cmp::min(saturation, 100) as f64 / 100.0;
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = lightness - chroma / 2.0;
+    let (r, g, b) = match hue as u16 / 60 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let percent = |c: f64| ((c + m) * 100.0).round() as u8;
+    (percent(r), percent(g), percent(b))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SixelCommand {
+    Data,
+    Repeat,
+    Color,
+    RasterAttributes,
+}
+
+/// Decodes the data of a sixel DCS sequence (`DCS P1 ; P2 ; P3 q <data> ST`) byte by byte
+#[derive(Debug, Clone)]
+pub struct SixelDecoder {
+    transparent_background: bool,
+    palette: Vec<(u8, u8, u8)>,
+    rows: Vec<Vec<Option<u8>>>,
+    color: u8,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    command: SixelCommand,
+    params: Vec<u16>,
+}
+
+impl SixelDecoder {
+    pub fn new(background_select: u16) -> Self {
+        let mut palette = DEFAULT_PALETTE.to_vec();
+        palette.resize(256, (0, 0, 0));
+        SixelDecoder {
+            transparent_background: background_select == 1,
+            palette,
+            rows: vec![],
+            color: 0,
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            command: SixelCommand::Data,
+            params: vec![],
+        }
+    }
+    pub fn put(&mut self, byte: u8) {
+        match byte {
+            b'0'..=b'9' if self.command != SixelCommand::Data => {
+                let param = self.params.last_mut().unwrap();
+                *param = param
+                    .saturating_mul(10)
+                    .saturating_add((byte - b'0') as u16);
+            }
+            b';' if self.command != SixelCommand::Data => self.params.push(0),
+            _ => {
+                self.finish_command();
+                match byte {
+                    b'?'..=b'~' => self.draw(byte - 0x3f),
+                    b'$' => self.x = 0,
+                    b'-' => {
+                        self.x = 0;
+                        self.y += 6;
+                    }
+                    b'!' => self.start_command(SixelCommand::Repeat),
+                    b'#' => self.start_command(SixelCommand::Color),
+                    b'"' => self.start_command(SixelCommand::RasterAttributes),
+                    _ => {}
+                }
+            }
+        }
+    }
+    fn start_command(&mut self, command: SixelCommand) {
+        self.command = command;
+        self.params = vec![0];
+    }
+    fn finish_command(&mut self) {
+        match self.command {
+            SixelCommand::Data | SixelCommand::Repeat => {}
+            SixelCommand::Color => match self.params[..] {
+                [color] => self.color = color as u8,
+                [color, 1, hue, lightness, saturation] => {
+                    self.color = color as u8;
+                    self.palette[self.color as usize] = hls_to_rgb(hue, lightness, saturation);
+                }
+                [color, 2, r, g, b] => {
+                    self.color = color as u8;
+                    let percent = |c: u16| std::cmp::min(c, 100) as u8;
+                    self.palette[self.color as usize] = (percent(r), percent(g), percent(b));
+                }
+                _ => {}
+            },
+            SixelCommand::RasterAttributes => {
+                if let [_, _, width, height] = self.params[..] {
+                    self.width = std::cmp::max(self.width, width as usize);
+                    self.height = std::cmp::max(self.height, height as usize);
+                }
+            }
+        }
+        if self.command != SixelCommand::Repeat {
+            self.command = SixelCommand::Data;
+        }
+    }
+    fn draw(&mut self, bits: u8) {
+        let repeat = match self.command {
+            SixelCommand::Repeat => std::cmp::max(self.params[0], 1) as usize,
+            _ => 1,
+        };
+        self.command = SixelCommand::Data;
+        let end_x = std::cmp::min(self.x + repeat, MAX_SIXEL_DIMENSION);
+        let end_y = std::cmp::min(self.y + 6, MAX_SIXEL_DIMENSION);
+        if end_x > self.x && end_y > self.y {
+            self.width = std::cmp::max(self.width, end_x);
+            self.height = std::cmp::max(self.height, end_y);
+            if self.rows.len() < end_y {
+                self.rows.resize(end_y, vec![]);
+            }
+            for (bit, row) in (self.y..end_y).enumerate() {
+                if bits & (1 << bit) != 0 {
+                    let row = &mut self.rows[row];
+                    if row.len() < end_x {
+                        row.resize(end_x, None);
+                    }
+                    for pixel in &mut row[self.x..end_x] {
+                        *pixel = Some(self.color);
+                    }
+                }
+            }
+        }
+        self.x += repeat;
+    }
+    pub fn finish(mut self) -> Option<SixelImage> {
+        self.finish_command();
+        let width = std::cmp::min(self.width, MAX_SIXEL_DIMENSION);
+        let height = std::cmp::min(self.height, MAX_SIXEL_DIMENSION);
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let background = if self.transparent_background {
+            None
+        } else {
+            Some(0)
+        };
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let row = self.rows.get(y).map(|row| &row[..]).unwrap_or(&[]);
+            pixels.extend((0..width).map(|x| row.get(x).copied().flatten().or(background)));
+        }
+        Some(SixelImage {
+            width,
+            height,
+            pixels,
+            palette: self.palette,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PlacedImage {
+    id: usize,
+    image: Arc<SixelImage>,
+    // relative to the top of the viewport, negative if the image is (partly) in the scrollback
+    line: isize,
+    column: usize,
+    // the amount of lines and columns it covers
+    rows: usize,
+    columns: usize,
+}
+
+/// Where the visible part of an image is drawn on the screen, in characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImagePlacement {
+    image_id: usize,
+    pub x: usize,
+    pub y: usize,
+    pub columns: usize,
+    pub rows: usize,
+    // the lines of the image above the viewport, which are not drawn
+    hidden_rows: usize,
+}
+
+/// The images displayed in a pane, anchored to the grid cells they were drawn at
+#[derive(Debug, Clone, Default)]
+pub struct ImageLayer {
+    images: Vec<PlacedImage>,
+}
+
+impl ImageLayer {
+    /// Adds `image`, which has to be identified by an `id` no other image of the pane had
+    pub fn add(
+        &mut self,
+        image: SixelImage,
+        id: usize,
+        line: usize,
+        column: usize,
+        character_cell_size: SizeInPixels,
+    ) {
+        let rows = (image.height + character_cell_size.height - 1) / character_cell_size.height;
+        let columns = (image.width + character_cell_size.width - 1) / character_cell_size.width;
+        let pixels = |image: &PlacedImage| image.image.width * image.image.height;
+        let mut total_pixels =
+            image.width * image.height + self.images.iter().map(pixels).sum::<usize>();
+        while !self.images.is_empty()
+            && (self.images.len() >= MAX_IMAGES_PER_PANE || total_pixels > MAX_PIXELS_PER_PANE)
+        {
+            total_pixels -= pixels(&self.images.remove(0));
+        }
+        self.images.push(PlacedImage {
+            id,
+            image: Arc::new(image),
+            line: line as isize,
+            column,
+            rows,
+            columns,
+        });
+    }
+    pub fn rows_of_last_image(&self) -> usize {
+        self.images.last().map(|image| image.rows).unwrap_or(0)
+    }
+    /// The content moved up by `count` lines, towards the scrollback
+    pub fn move_up(&mut self, count: usize, scrollback_length: usize) {
+        for image in &mut self.images {
+            image.line -= count as isize;
+        }
+        self.trim_to_scrollback(scrollback_length);
+    }
+    /// Forget about the images that left a scrollback of `scrollback_length` lines
+    pub fn trim_to_scrollback(&mut self, scrollback_length: usize) {
+        self.images
+            .retain(|image| image.line + image.rows as isize > -(scrollback_length as isize));
+    }
+    /// The content moved down by `count` lines, away from the scrollback
+    pub fn move_down(&mut self, count: usize) {
+        for image in &mut self.images {
+            image.line += count as isize;
+        }
+    }
+    /// Remove the images that are (partly) visible in a viewport of `height` lines
+    pub fn clear_viewport(&mut self, height: usize) {
+        self.images
+            .retain(|image| image.line + image.rows as isize <= 0 || image.line >= height as isize);
+    }
+    pub fn clear(&mut self) {
+        self.images.clear();
+    }
+    /// Where the visible parts of the images are drawn in a viewport of `rows` and `columns`,
+    /// whose top left corner is at `x`/`y` on the screen
+    pub fn placements(
+        &self,
+        x: usize,
+        y: usize,
+        rows: usize,
+        columns: usize,
+    ) -> Vec<ImagePlacement> {
+        let mut placements = vec![];
+        for image in &self.images {
+            let first_visible_line = std::cmp::max(image.line, 0) as usize;
+            let last_visible_line = std::cmp::min(image.line + image.rows as isize, rows as isize);
+            if last_visible_line <= first_visible_line as isize || image.column >= columns {
+                continue;
+            }
+            placements.push(ImagePlacement {
+                image_id: image.id,
+                x: x + image.column,
+                y: y + first_visible_line,
+                columns: std::cmp::min(image.columns, columns - image.column),
+                rows: last_visible_line as usize - first_visible_line,
+                hidden_rows: (first_visible_line as isize - image.line) as usize,
+            });
+        }
+        placements
+    }
+    /// Encode the part of an image shown by `placement`, None if the image is gone
+    pub fn render(
+        &self,
+        placement: &ImagePlacement,
+        character_cell_size: SizeInPixels,
+    ) -> Option<String> {
+        let image = self
+            .images
+            .iter()
+            .find(|image| image.id == placement.image_id)?;
+        let mut output = String::new();
+        let _ = write!(
+            output,
+            "\u{1b}[{};{}H{}",
+            placement.y + 1,
+            placement.x + 1,
+            image.image.encode(
+                0,
+                placement.hidden_rows * character_cell_size.height,
+                placement.columns * character_cell_size.width,
+                placement.rows * character_cell_size.height,
+            )
+        );
+        Some(output)
+    }
+}
@@ -4,6 +4,8 @@ use std::ops::{Index, IndexMut};
 use zellij_utils::logging::debug_log_to_file;
 use zellij_utils::vte::ParamsIter;
 
+use crate::panes::link_handler::LinkAnchor;
+
 pub const EMPTY_TERMINAL_CHARACTER: TerminalCharacter = TerminalCharacter {
     character: ' ',
     width: 1,
@@ -20,6 +22,7 @@ pub const EMPTY_TERMINAL_CHARACTER: TerminalCharacter = TerminalCharacter {
         dim: Some(AnsiCode::Reset),
         italic: Some(AnsiCode::Reset),
     },
+    link_anchor: None,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -94,7 +97,7 @@ impl NamedColor {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CharacterStyles {
     pub foreground: Option<AnsiCode>,
     pub background: Option<AnsiCode>,
@@ -750,6 +753,7 @@ pub struct TerminalCharacter {
     pub character: char,
     pub styles: CharacterStyles,
     pub width: usize,
+    pub link_anchor: Option<LinkAnchor>,
 }
 
 impl ::std::fmt::Debug for TerminalCharacter {
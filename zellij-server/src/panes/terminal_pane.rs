@@ -1,24 +1,33 @@
+use zellij_utils::input::actions::{CopyModeMotion, SearchOption, SelectionKind};
 use zellij_utils::position::Position;
+use zellij_utils::zellij_tile;
 use zellij_utils::zellij_tile::prelude::PaletteColor;
-use zellij_utils::{vte, zellij_tile};
 
 use std::fmt::Debug;
 use std::os::unix::io::RawFd;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{self, Instant};
-use zellij_tile::data::Palette;
-use zellij_utils::pane_size::PositionAndSize;
+use zellij_tile::data::{Palette, SearchInfo};
+use zellij_utils::pane_size::{PositionAndSize, SizeInPixels};
 
 use crate::panes::AnsiCode;
 use crate::panes::{
-    grid::Grid,
-    terminal_character::{
-        CharacterStyles, CursorShape, TerminalCharacter, EMPTY_TERMINAL_CHARACTER,
-    },
+    grid::{Grid, MouseEvent},
+    link_handler::LinkAnchor,
+    terminal_character::{CursorShape, TerminalCharacter, EMPTY_TERMINAL_CHARACTER},
 };
-use crate::pty::VteBytes;
 use crate::tab::Pane;
+use crate::ui::frame::{FrameCharacter, FrameChunk};
 
-pub const SELECTION_SCROLL_INTERVAL_MS: u64 = 10;
+pub const SELECTION_SCROLL_INTERVAL_MS: u64 = 50;
+
+fn palette_color_to_ansi_code(color: PaletteColor) -> AnsiCode {
+    match color {
+        PaletteColor::Rgb(rgb) => AnsiCode::RgbCode(rgb),
+        PaletteColor::EightBit(col) => AnsiCode::ColorIndex(col),
+    }
+}
 
 #[derive(PartialEq, Eq, Ord, PartialOrd, Hash, Clone, Copy, Debug)]
 pub enum PaneId {
@@ -26,15 +35,19 @@ pub enum PaneId {
     Plugin(u32), // FIXME: Drop the trait object, make this a wrapper for the struct?
 }
 
+/// The grid of a terminal pane, shared with the task reading the output of its pty: the task
+/// parses the output into the grid, while the screen thread renders it and handles the input
+pub type SharedGrid = Arc<Mutex<Grid>>;
+
 pub struct TerminalPane {
-    pub grid: Grid,
+    grid: SharedGrid,
     pub pid: RawFd,
     pub selectable: bool,
+    read_only: bool,
     pub position_and_size: PositionAndSize,
     pub position_and_size_override: Option<PositionAndSize>,
     pub active_at: Instant,
     pub colors: Palette,
-    vte_parser: vte::Parser,
     selection_scrolled_at: time::Instant,
 }
 
@@ -70,15 +83,9 @@ impl Pane for TerminalPane {
         self.position_and_size_override = Some(position_and_size_override);
         self.reflow_lines();
     }
-    fn handle_pty_bytes(&mut self, bytes: VteBytes) {
-        for byte in bytes.iter() {
-            self.vte_parser.advance(&mut self.grid, *byte);
-        }
-        self.set_should_render(true);
-    }
     fn cursor_coordinates(&self) -> Option<(usize, usize)> {
         // (x, y)
-        self.grid.cursor_coordinates()
+        self.grid().cursor_coordinates()
     }
     fn adjust_input_to_terminal(&self, input_bytes: Vec<u8>) -> Vec<u8> {
         // there are some cases in which the terminal state means that input sent to it
@@ -88,7 +95,7 @@ impl Pane for TerminalPane {
         match input_bytes.as_slice() {
             [27, 91, 68] => {
                 // left arrow
-                if self.grid.cursor_key_mode {
+                if self.grid().cursor_key_mode {
                     // please note that in the line below, there is an ANSI escape code (27) at the beginning of the string,
                     // some editors will not show this
                     return "OD".as_bytes().to_vec();
@@ -96,7 +103,7 @@ impl Pane for TerminalPane {
             }
             [27, 91, 67] => {
                 // right arrow
-                if self.grid.cursor_key_mode {
+                if self.grid().cursor_key_mode {
                     // please note that in the line below, there is an ANSI escape code (27) at the beginning of the string,
                     // some editors will not show this
                     return "OC".as_bytes().to_vec();
@@ -104,7 +111,7 @@ impl Pane for TerminalPane {
             }
             [27, 91, 65] => {
                 // up arrow
-                if self.grid.cursor_key_mode {
+                if self.grid().cursor_key_mode {
                     // please note that in the line below, there is an ANSI escape code (27) at the beginning of the string,
                     // some editors will not show this
                     return "OA".as_bytes().to_vec();
@@ -112,7 +119,7 @@ impl Pane for TerminalPane {
             }
             [27, 91, 66] => {
                 // down arrow
-                if self.grid.cursor_key_mode {
+                if self.grid().cursor_key_mode {
                     // please note that in the line below, there is an ANSI escape code (27) at the beginning of the string,
                     // some editors will not show this
                     return "OB".as_bytes().to_vec();
@@ -129,13 +136,13 @@ impl Pane for TerminalPane {
         self.position_and_size_override
     }
     fn should_render(&self) -> bool {
-        self.grid.should_render
+        self.grid().should_render
     }
     fn set_should_render(&mut self, should_render: bool) {
-        self.grid.should_render = should_render;
+        self.grid().should_render = should_render;
     }
     fn render_full_viewport(&mut self) {
-        self.grid.render_full_viewport();
+        self.grid().render_full_viewport();
     }
     fn selectable(&self) -> bool {
         self.selectable
@@ -143,6 +150,12 @@ impl Pane for TerminalPane {
     fn set_selectable(&mut self, selectable: bool) {
         self.selectable = selectable;
     }
+    fn read_only(&self) -> bool {
+        self.read_only
+    }
+    fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
     fn set_fixed_height(&mut self, fixed_height: usize) {
         self.position_and_size.rows = fixed_height;
         self.position_and_size.rows_fixed = true;
@@ -155,68 +168,95 @@ impl Pane for TerminalPane {
         unimplemented!();
     }
     fn render(&mut self) -> Option<String> {
-        if self.should_render() {
-            let mut vte_output = String::new();
-            let mut character_styles = CharacterStyles::new();
-            if self.grid.clear_viewport_before_rendering {
-                for line_index in 0..self.grid.height {
-                    let x = self.get_x();
-                    let y = self.get_y();
-                    vte_output.push_str(&format!(
-                        "\u{1b}[{};{}H\u{1b}[m",
-                        y + line_index + 1,
-                        x + 1
-                    )); // goto row/col and reset styles
-                    for _col_index in 0..self.grid.width {
-                        vte_output.push(EMPTY_TERMINAL_CHARACTER.character);
-                    }
-                }
-                self.grid.clear_viewport_before_rendering = false;
+        self.render_chunks()
+            .map(|chunks| chunks.iter().map(FrameChunk::vte_output).collect())
+    }
+    fn render_chunks(&mut self) -> Option<Vec<FrameChunk>> {
+        if !self.should_render() {
+            return None;
+        }
+        let mut grid = self.grid.lock().unwrap();
+        // once per render rather than for every chunk of output
+        grid.refresh_search_matches();
+        let pane_x = self.get_x();
+        let pane_y = self.get_y();
+        let mut chunks = vec![];
+        if grid.clear_viewport_before_rendering {
+            let empty_character = FrameCharacter::new(
+                EMPTY_TERMINAL_CHARACTER.character,
+                EMPTY_TERMINAL_CHARACTER.width,
+                EMPTY_TERMINAL_CHARACTER.styles,
+            );
+            for line_index in 0..grid.height {
+                chunks.push(FrameChunk {
+                    x: pane_x,
+                    y: pane_y + line_index,
+                    characters: vec![empty_character.clone(); grid.width],
+                });
             }
-            let max_width = self.columns();
-            for character_chunk in self.grid.read_changes() {
-                let pane_x = self.get_x();
-                let pane_y = self.get_y();
-                let chunk_absolute_x = pane_x + character_chunk.x;
-                let chunk_absolute_y = pane_y + character_chunk.y;
-                let terminal_characters = character_chunk.terminal_characters;
-                vte_output.push_str(&format!(
-                    "\u{1b}[{};{}H\u{1b}[m",
-                    chunk_absolute_y + 1,
-                    chunk_absolute_x + 1
-                )); // goto row/col and reset styles
-
-                let mut chunk_width = character_chunk.x;
-                for mut t_character in terminal_characters {
-                    // adjust the background of currently selected characters
-                    // doing it here is much easier than in grid
-                    if self.grid.selection.contains(character_chunk.y, chunk_width) {
-                        let color = match self.colors.bg {
-                            PaletteColor::Rgb(rgb) => AnsiCode::RgbCode(rgb),
-                            PaletteColor::EightBit(col) => AnsiCode::ColorIndex(col),
-                        };
-
-                        t_character.styles = t_character.styles.background(Some(color));
-                    }
-                    chunk_width += t_character.width;
-                    if chunk_width > max_width {
-                        break;
-                    }
-
-                    if let Some(new_styles) =
-                        character_styles.update_and_return_diff(&t_character.styles)
-                    {
-                        vte_output.push_str(&new_styles.to_string());
+            grid.clear_viewport_before_rendering = false;
+        }
+        let max_width = self.columns();
+        // the OSC 8 sequence of the last link, which usually spans several characters
+        let mut last_link: (Option<LinkAnchor>, Option<Rc<str>>) = (None, None);
+        for character_chunk in grid.read_changes() {
+            let mut characters: Vec<FrameCharacter> =
+                Vec::with_capacity(character_chunk.terminal_characters.len());
+            let mut chunk_width = character_chunk.x;
+            for mut t_character in character_chunk.terminal_characters {
+                if t_character.width == 0 {
+                    if let Some(previous_character) = characters.last_mut() {
+                        previous_character.add_combining_character(t_character.character);
                     }
-                    vte_output.push(t_character.character);
+                    continue;
+                }
+                // adjust the background of currently selected characters
+                // doing it here is much easier than in grid
+                if grid.selection.contains(character_chunk.y, chunk_width) {
+                    let color = palette_color_to_ansi_code(self.colors.bg);
+                    t_character.styles = t_character.styles.background(Some(color));
+                } else if let Some(is_active_match) =
+                    grid.search_results.match_at(character_chunk.y, chunk_width)
+                {
+                    let background = if is_active_match {
+                        self.colors.orange
+                    } else {
+                        self.colors.yellow
+                    };
+                    t_character.styles = t_character
+                        .styles
+                        .background(Some(palette_color_to_ansi_code(background)))
+                        .foreground(Some(palette_color_to_ansi_code(self.colors.black)));
+                }
+                chunk_width += t_character.width;
+                if chunk_width > max_width {
+                    break;
+                }
+                if t_character.link_anchor != last_link.0 {
+                    let link_handler = &grid.link_handler;
+                    last_link = (
+                        t_character.link_anchor,
+                        t_character
+                            .link_anchor
+                            .map(|anchor| Rc::from(link_handler.output_osc8(Some(anchor)))),
+                    );
                 }
-                character_styles.clear();
+                let mut character = FrameCharacter::new(
+                    t_character.character,
+                    t_character.width,
+                    t_character.styles,
+                );
+                character.link = last_link.1.clone();
+                characters.push(character);
             }
-            self.set_should_render(false);
-            Some(vte_output)
-        } else {
-            None
+            chunks.push(FrameChunk {
+                x: pane_x + character_chunk.x,
+                y: pane_y + character_chunk.y,
+                characters,
+            });
         }
+        grid.should_render = false;
+        Some(chunks)
     }
     fn pid(&self) -> PaneId {
         PaneId::Terminal(self.pid)
@@ -270,15 +310,15 @@ impl Pane for TerminalPane {
         self.position_and_size.y -= count;
     }
     fn scroll_up(&mut self, count: usize) {
-        self.grid.move_viewport_up(count);
+        self.grid().move_viewport_up(count);
         self.set_should_render(true);
     }
     fn scroll_down(&mut self, count: usize) {
-        self.grid.move_viewport_down(count);
+        self.grid().move_viewport_down(count);
         self.set_should_render(true);
     }
     fn clear_scroll(&mut self) {
-        self.grid.reset_viewport();
+        self.grid().reset_viewport();
         self.set_should_render(true);
     }
 
@@ -290,7 +330,7 @@ impl Pane for TerminalPane {
         self.active_at = time;
     }
     fn cursor_shape_csi(&self) -> String {
-        match self.grid.cursor_shape() {
+        match self.grid().cursor_shape() {
             CursorShape::Block => "\u{1b}[0 q".to_string(),
             CursorShape::BlinkingBlock => "\u{1b}[1 q".to_string(),
             CursorShape::Underline => "\u{1b}[4 q".to_string(),
@@ -299,61 +339,179 @@ impl Pane for TerminalPane {
             CursorShape::BlinkingBeam => "\u{1b}[5 q".to_string(),
         }
     }
-    fn drain_messages_to_pty(&mut self) -> Vec<Vec<u8>> {
-        self.grid.pending_messages_to_pty.drain(..).collect()
+    fn start_selection(&mut self, start: &Position) {
+        self.grid().start_selection(start);
+        self.set_should_render(true);
     }
 
-    fn start_selection(&mut self, start: &Position) {
-        self.grid.start_selection(start);
+    fn select_word(&mut self, position: &Position, word_separators: &str) {
+        self.grid().select_word(position, word_separators);
+        self.set_should_render(true);
+    }
+
+    fn select_line(&mut self, position: &Position) {
+        self.grid().select_line(position);
         self.set_should_render(true);
     }
 
     fn update_selection(&mut self, to: &Position) {
-        let should_scroll = self.selection_scrolled_at.elapsed()
-            >= time::Duration::from_millis(SELECTION_SCROLL_INTERVAL_MS);
-        // TODO: check how far up/down mouse is relative to pane, to increase scroll lines?
-        if to.line.0 < 0 && should_scroll {
-            self.grid.scroll_up_one_line();
-            self.selection_scrolled_at = time::Instant::now();
-        } else if to.line.0 as usize >= self.grid.height && should_scroll {
-            self.grid.scroll_down_one_line();
+        let is_outside_of_pane = to.line.0 < 0 || to.line.0 as usize >= self.grid().height;
+        if !is_outside_of_pane {
+            self.grid().update_selection(to);
+        } else if self.selection_scrolled_at.elapsed()
+            >= time::Duration::from_millis(SELECTION_SCROLL_INTERVAL_MS)
+        {
+            // the client repeats the hold events while the mouse is still, so that
+            // this keeps scrolling
+            self.grid().scroll_selection_towards(to);
             self.selection_scrolled_at = time::Instant::now();
-        } else if to.line.0 >= 0 && (to.line.0 as usize) < self.grid.height {
-            self.grid.update_selection(to);
         }
 
         self.set_should_render(true);
     }
 
     fn end_selection(&mut self, end: Option<&Position>) {
-        self.grid.end_selection(end);
+        self.grid().end_selection(end);
         self.set_should_render(true);
     }
 
     fn reset_selection(&mut self) {
-        self.grid.reset_selection();
+        self.grid().reset_selection();
     }
 
     fn get_selected_text(&self) -> Option<String> {
-        self.grid.get_selected_text()
+        self.grid().get_selected_text()
+    }
+
+    fn dump_screen(&self) -> String {
+        self.grid().dump_screen()
+    }
+    fn dump_scrollback(&self, with_ansi_styles: bool) -> String {
+        self.grid().dump_scrollback(with_ansi_styles)
+    }
+
+    fn mouse_reporting(&self) -> bool {
+        self.grid().mouse_reporting()
+    }
+
+    fn image_placements(&self) -> Vec<ImagePlacement> {
+        self.grid().image_placements(self.get_x(), self.get_y())
+    }
+
+    fn render_image(&self, placement: &ImagePlacement) -> Option<String> {
+        self.grid().render_image(placement)
+    }
+
+    fn set_character_cell_size(&mut self, character_cell_size: Option<SizeInPixels>) {
+        self.grid().character_cell_size = character_cell_size;
+    }
+
+    fn link_at(&self, position: &Position) -> Option<String> {
+        self.grid().link_at(position)
+    }
+
+    fn link_under_cursor(&self) -> Option<String> {
+        self.grid().link_under_cursor()
+    }
+
+    fn update_search_term(&mut self, bytes: &[u8]) {
+        self.grid().update_search_term(bytes);
+        self.set_should_render(true);
+    }
+
+    fn reset_search_term(&mut self) {
+        self.grid().reset_search_term();
+        self.set_should_render(true);
+    }
+
+    fn search_up(&mut self) {
+        self.grid().search_up();
+        self.set_should_render(true);
+    }
+
+    fn search_down(&mut self) {
+        self.grid().search_down();
+        self.set_should_render(true);
+    }
+
+    fn toggle_search_option(&mut self, option: SearchOption) {
+        self.grid().toggle_search_option(option);
+        self.set_should_render(true);
+    }
+
+    fn clear_search(&mut self) {
+        self.grid().clear_search();
+        self.set_should_render(true);
+    }
+
+    fn search_info(&self) -> Option<SearchInfo> {
+        Some(self.grid().search_info())
+    }
+
+    fn start_copy_mode(&mut self) {
+        self.grid().start_copy_mode();
+        self.set_should_render(true);
+    }
+
+    fn exit_copy_mode(&mut self) {
+        self.grid().exit_copy_mode();
+        self.set_should_render(true);
+    }
+
+    fn copy_mode_move(&mut self, motion: CopyModeMotion) {
+        self.grid().copy_mode_move(motion);
+        self.set_should_render(true);
+    }
+
+    fn copy_mode_select(&mut self, kind: SelectionKind) {
+        self.grid().copy_mode_select(kind);
+        self.set_should_render(true);
+    }
+
+    fn bracketed_paste(&self) -> bool {
+        self.grid().bracketed_paste
+    }
+
+    fn mouse_event_report(&self, event: MouseEvent, position: &Position) -> Option<Vec<u8>> {
+        self.grid().mouse_event_report(event, position)
     }
 }
 
 impl TerminalPane {
-    pub fn new(pid: RawFd, position_and_size: PositionAndSize, palette: Palette) -> TerminalPane {
-        let grid = Grid::new(position_and_size.rows, position_and_size.cols, palette);
+    pub fn new(
+        pid: RawFd,
+        position_and_size: PositionAndSize,
+        palette: Palette,
+        character_cell_size: Option<SizeInPixels>,
+        scroll_buffer_size: usize,
+    ) -> TerminalPane {
+        let mut grid = Grid::new(
+            position_and_size.rows,
+            position_and_size.cols,
+            palette,
+            scroll_buffer_size,
+        );
+        grid.character_cell_size = character_cell_size;
         TerminalPane {
             pid,
-            grid,
+            grid: Arc::new(Mutex::new(grid)),
             selectable: true,
+            read_only: false,
             position_and_size,
             position_and_size_override: None,
-            vte_parser: vte::Parser::new(),
             active_at: Instant::now(),
             colors: palette,
             selection_scrolled_at: time::Instant::now(),
         }
     }
+    /// Locks the grid, which is only ever done for a single operation so that the task
+    /// parsing the output of the pane is never held up for long
+    pub fn grid(&self) -> MutexGuard<'_, Grid> {
+        self.grid.lock().unwrap()
+    }
+    pub fn shared_grid(&self) -> SharedGrid {
+        self.grid.clone()
+    }
     pub fn get_x(&self) -> usize {
         match self.position_and_size_override {
             Some(position_and_size_override) => position_and_size_override.x,
@@ -381,15 +539,15 @@ impl TerminalPane {
     fn reflow_lines(&mut self) {
         let rows = self.get_rows();
         let columns = self.get_columns();
-        self.grid.change_size(rows, columns);
+        self.grid().change_size(rows, columns);
         self.set_should_render(true);
     }
     pub fn read_buffer_as_lines(&self) -> Vec<Vec<TerminalCharacter>> {
-        self.grid.as_character_lines()
+        self.grid().as_character_lines()
     }
     pub fn cursor_coordinates(&self) -> Option<(usize, usize)> {
         // (x, y)
-        self.grid.cursor_coordinates()
+        self.grid().cursor_coordinates()
     }
 }
 
@@ -1,6 +1,14 @@
-use super::super::Grid;
+use super::super::{
+    AnsiCode, CharacterStyles, Grid, MouseEncoding, MouseEvent, MouseTracking, NamedColor,
+    DEFAULT_SCROLL_BUFFER_SIZE, DEFAULT_WORD_SEPARATORS,
+};
 use ::insta::assert_snapshot;
-use zellij_utils::{position::Position, vte, zellij_tile::data::Palette};
+use zellij_utils::{
+    input::actions::{CopyModeMotion, SearchOption, SelectionKind},
+    position::Position,
+    vte,
+    zellij_tile::data::Palette,
+};
 
 fn read_fixture(fixture_name: &str) -> Vec<u8> {
     let mut path_to_file = std::path::PathBuf::new();
@@ -16,7 +24,7 @@ fn read_fixture(fixture_name: &str) -> Vec<u8> {
 #[test]
 fn vttest1_0() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest1-0";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -28,7 +36,7 @@ fn vttest1_0() {
 #[test]
 fn vttest1_1() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest1-1";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -40,7 +48,7 @@ fn vttest1_1() {
 #[test]
 fn vttest1_2() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest1-2";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -52,7 +60,7 @@ fn vttest1_2() {
 #[test]
 fn vttest1_3() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest1-3";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -64,7 +72,7 @@ fn vttest1_3() {
 #[test]
 fn vttest1_4() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest1-4";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -76,7 +84,7 @@ fn vttest1_4() {
 #[test]
 fn vttest1_5() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest1-5";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -88,7 +96,7 @@ fn vttest1_5() {
 #[test]
 fn vttest2_0() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-0";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -100,7 +108,7 @@ fn vttest2_0() {
 #[test]
 fn vttest2_1() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-1";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -112,7 +120,7 @@ fn vttest2_1() {
 #[test]
 fn vttest2_2() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-2";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -124,7 +132,7 @@ fn vttest2_2() {
 #[test]
 fn vttest2_3() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-3";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -136,7 +144,7 @@ fn vttest2_3() {
 #[test]
 fn vttest2_4() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-4";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -148,7 +156,7 @@ fn vttest2_4() {
 #[test]
 fn vttest2_5() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-5";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -160,7 +168,7 @@ fn vttest2_5() {
 #[test]
 fn vttest2_6() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-6";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -172,7 +180,7 @@ fn vttest2_6() {
 #[test]
 fn vttest2_7() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-7";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -184,7 +192,7 @@ fn vttest2_7() {
 #[test]
 fn vttest2_8() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-8";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -196,7 +204,7 @@ fn vttest2_8() {
 #[test]
 fn vttest2_9() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-9";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -208,7 +216,7 @@ fn vttest2_9() {
 #[test]
 fn vttest2_10() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-10";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -220,7 +228,7 @@ fn vttest2_10() {
 #[test]
 fn vttest2_11() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-11";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -232,7 +240,7 @@ fn vttest2_11() {
 #[test]
 fn vttest2_12() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-12";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -244,7 +252,7 @@ fn vttest2_12() {
 #[test]
 fn vttest2_13() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-13";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -256,7 +264,7 @@ fn vttest2_13() {
 #[test]
 fn vttest2_14() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest2-14";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -268,7 +276,7 @@ fn vttest2_14() {
 #[test]
 fn vttest3_0() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(41, 110, Palette::default());
+    let mut grid = Grid::new(41, 110, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest3-0";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -280,7 +288,7 @@ fn vttest3_0() {
 #[test]
 fn vttest8_0() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(51, 97, Palette::default());
+    let mut grid = Grid::new(51, 97, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest8-0";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -292,7 +300,7 @@ fn vttest8_0() {
 #[test]
 fn vttest8_1() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(51, 97, Palette::default());
+    let mut grid = Grid::new(51, 97, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest8-1";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -304,7 +312,7 @@ fn vttest8_1() {
 #[test]
 fn vttest8_2() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(51, 97, Palette::default());
+    let mut grid = Grid::new(51, 97, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest8-2";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -316,7 +324,7 @@ fn vttest8_2() {
 #[test]
 fn vttest8_3() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(51, 97, Palette::default());
+    let mut grid = Grid::new(51, 97, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest8-3";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -328,7 +336,7 @@ fn vttest8_3() {
 #[test]
 fn vttest8_4() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(51, 97, Palette::default());
+    let mut grid = Grid::new(51, 97, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest8-4";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -340,7 +348,7 @@ fn vttest8_4() {
 #[test]
 fn vttest8_5() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(51, 97, Palette::default());
+    let mut grid = Grid::new(51, 97, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vttest8-5";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -352,7 +360,7 @@ fn vttest8_5() {
 #[test]
 fn csi_b() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(51, 97, Palette::default());
+    let mut grid = Grid::new(51, 97, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "csi-b";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -364,7 +372,7 @@ fn csi_b() {
 #[test]
 fn csi_capital_i() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(51, 97, Palette::default());
+    let mut grid = Grid::new(51, 97, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "csi-capital-i";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -376,7 +384,7 @@ fn csi_capital_i() {
 #[test]
 fn csi_capital_z() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(51, 97, Palette::default());
+    let mut grid = Grid::new(51, 97, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "csi-capital-z";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -388,7 +396,7 @@ fn csi_capital_z() {
 #[test]
 fn terminal_reports() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(51, 97, Palette::default());
+    let mut grid = Grid::new(51, 97, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "terminal_reports";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -400,7 +408,7 @@ fn terminal_reports() {
 #[test]
 fn wide_characters() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 104, Palette::default());
+    let mut grid = Grid::new(21, 104, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "wide_characters";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -412,7 +420,7 @@ fn wide_characters() {
 #[test]
 fn wide_characters_line_wrap() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 104, Palette::default());
+    let mut grid = Grid::new(21, 104, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "wide_characters_line_wrap";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -424,7 +432,7 @@ fn wide_characters_line_wrap() {
 #[test]
 fn fish_wide_characters_override_clock() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 104, Palette::default());
+    let mut grid = Grid::new(21, 104, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "fish_wide_characters_override_clock";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -436,7 +444,7 @@ fn fish_wide_characters_override_clock() {
 #[test]
 fn bash_delete_wide_characters() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 104, Palette::default());
+    let mut grid = Grid::new(21, 104, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "bash_delete_wide_characters";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -448,7 +456,7 @@ fn bash_delete_wide_characters() {
 #[test]
 fn delete_wide_characters_before_cursor() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 104, Palette::default());
+    let mut grid = Grid::new(21, 104, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "delete_wide_characters_before_cursor";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -460,7 +468,7 @@ fn delete_wide_characters_before_cursor() {
 #[test]
 fn delete_wide_characters_before_cursor_when_cursor_is_on_wide_character() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 104, Palette::default());
+    let mut grid = Grid::new(21, 104, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "delete_wide_characters_before_cursor_when_cursor_is_on_wide_character";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -472,7 +480,7 @@ fn delete_wide_characters_before_cursor_when_cursor_is_on_wide_character() {
 #[test]
 fn delete_wide_character_under_cursor() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 104, Palette::default());
+    let mut grid = Grid::new(21, 104, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "delete_wide_character_under_cursor";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -484,7 +492,7 @@ fn delete_wide_character_under_cursor() {
 #[test]
 fn replace_wide_character_under_cursor() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 104, Palette::default());
+    let mut grid = Grid::new(21, 104, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "replace_wide_character_under_cursor";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -496,7 +504,7 @@ fn replace_wide_character_under_cursor() {
 #[test]
 fn wrap_wide_characters() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 90, Palette::default());
+    let mut grid = Grid::new(21, 90, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "wide_characters_full";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -508,7 +516,7 @@ fn wrap_wide_characters() {
 #[test]
 fn wrap_wide_characters_on_size_change() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 93, Palette::default());
+    let mut grid = Grid::new(21, 93, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "wide_characters_full";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -521,7 +529,7 @@ fn wrap_wide_characters_on_size_change() {
 #[test]
 fn unwrap_wide_characters_on_size_change() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 93, Palette::default());
+    let mut grid = Grid::new(21, 93, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "wide_characters_full";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -535,7 +543,7 @@ fn unwrap_wide_characters_on_size_change() {
 #[test]
 fn wrap_wide_characters_in_the_middle_of_the_line() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 91, Palette::default());
+    let mut grid = Grid::new(21, 91, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "wide_characters_line_middle";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -547,7 +555,7 @@ fn wrap_wide_characters_in_the_middle_of_the_line() {
 #[test]
 fn wrap_wide_characters_at_the_end_of_the_line() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(21, 90, Palette::default());
+    let mut grid = Grid::new(21, 90, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "wide_characters_line_end";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -559,7 +567,7 @@ fn wrap_wide_characters_at_the_end_of_the_line() {
 #[test]
 fn copy_selected_text_from_viewport() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(27, 125, Palette::default());
+    let mut grid = Grid::new(27, 125, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "grid_copy";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -579,7 +587,7 @@ fn copy_selected_text_from_viewport() {
 #[test]
 fn copy_selected_text_from_lines_above() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(27, 125, Palette::default());
+    let mut grid = Grid::new(27, 125, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "grid_copy";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -599,7 +607,7 @@ fn copy_selected_text_from_lines_above() {
 #[test]
 fn copy_selected_text_from_lines_below() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(27, 125, Palette::default());
+    let mut grid = Grid::new(27, 125, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "grid_copy";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -627,7 +635,7 @@ fn copy_selected_text_from_lines_below() {
 #[test]
 fn run_bandwhich_from_fish_shell() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "fish_and_bandwhich";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -639,7 +647,7 @@ fn run_bandwhich_from_fish_shell() {
 #[test]
 fn fish_tab_completion_options() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "fish_tab_completion_options";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -656,7 +664,7 @@ pub fn fish_select_tab_completion_options() {
     // this is not clearly seen in the snapshot because it does not include styles,
     // but we can see the command line change and the cursor staying in place
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "fish_select_tab_completion_options";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -676,7 +684,7 @@ pub fn vim_scroll_region_down() {
     // this tests also has other steps afterwards that fills the line with the next line in the
     // file
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vim_scroll_region_down";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -694,7 +702,7 @@ pub fn vim_ctrl_d() {
     // end of the scroll region
     // vim makes sure to fill these empty lines with the rest of the file
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vim_ctrl_d";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -711,7 +719,7 @@ pub fn vim_ctrl_u() {
     // this causes the effect of scrolling up X lines (vim replaces the lines with the ones in the
     // file above the current content)
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vim_ctrl_u";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -723,7 +731,7 @@ pub fn vim_ctrl_u() {
 #[test]
 pub fn htop() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "htop";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -735,7 +743,7 @@ pub fn htop() {
 #[test]
 pub fn htop_scrolling() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "htop_scrolling";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -747,7 +755,7 @@ pub fn htop_scrolling() {
 #[test]
 pub fn htop_right_scrolling() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "htop_right_scrolling";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -767,7 +775,7 @@ pub fn vim_overwrite() {
     // * confirm you would like to change the file by pressing 'y' and then ENTER
     // * if everything looks fine, this test passed :)
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "vim_overwrite";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -781,7 +789,7 @@ pub fn clear_scroll_region() {
     // this is actually a test of 1049h/l (alternative buffer)
     // @imsnif - the name is a monument to the time I didn't fully understand this mechanism :)
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "clear_scroll_region";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -793,7 +801,7 @@ pub fn clear_scroll_region() {
 #[test]
 pub fn display_tab_characters_properly() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "tab_characters";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -805,7 +813,7 @@ pub fn display_tab_characters_properly() {
 #[test]
 pub fn neovim_insert_mode() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "nvim_insert";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -817,7 +825,7 @@ pub fn neovim_insert_mode() {
 #[test]
 pub fn bash_cursor_linewrap() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 116, Palette::default());
+    let mut grid = Grid::new(28, 116, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "bash_cursor_linewrap";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -831,7 +839,7 @@ pub fn fish_paste_multiline() {
     // here we paste a multiline command in fish shell, making sure we support it
     // going up and changing the colors of our line-wrapped pasted text
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 149, Palette::default());
+    let mut grid = Grid::new(28, 149, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "fish_paste_multiline";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -843,7 +851,7 @@ pub fn fish_paste_multiline() {
 #[test]
 pub fn git_log() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 149, Palette::default());
+    let mut grid = Grid::new(28, 149, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "git_log";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -857,7 +865,7 @@ pub fn git_diff_scrollup() {
     // this tests makes sure that when we have a git diff that exceeds the screen size
     // we are able to scroll up
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(28, 149, Palette::default());
+    let mut grid = Grid::new(28, 149, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "git_diff_scrollup";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -869,7 +877,7 @@ pub fn git_diff_scrollup() {
 #[test]
 pub fn emacs_longbuf() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(60, 284, Palette::default());
+    let mut grid = Grid::new(60, 284, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "emacs_longbuf_tutorial";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -881,7 +889,7 @@ pub fn emacs_longbuf() {
 #[test]
 pub fn top_and_quit() {
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(56, 235, Palette::default());
+    let mut grid = Grid::new(56, 235, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "top_and_quit";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -899,7 +907,7 @@ pub fn exa_plus_omf_theme() {
     // over existing on-screen content without deleting it, so we must
     // convert it to spaces
     let mut vte_parser = vte::Parser::new();
-    let mut grid = Grid::new(56, 235, Palette::default());
+    let mut grid = Grid::new(56, 235, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
     let fixture_name = "exa_plus_omf_theme";
     let content = read_fixture(fixture_name);
     for byte in content {
@@ -907,3 +915,437 @@ pub fn exa_plus_omf_theme() {
     }
     assert_snapshot!(format!("{:?}", grid));
 }
+
+#[test]
+pub fn mouse_events_are_not_reported_by_default() {
+    let grid = Grid::new(10, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    assert!(!grid.mouse_reporting());
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::LeftPress, &Position::new(2, 3)),
+        None
+    );
+}
+
+#[test]
+pub fn mouse_events_in_x10_encoding() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"\x1b[?1000h" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::LeftPress, &Position::new(2, 3)),
+        Some(vec![0x1b, b'[', b'M', 32, 36, 35])
+    );
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::LeftRelease, &Position::new(2, 3)),
+        Some(vec![0x1b, b'[', b'M', 35, 36, 35])
+    );
+    // motion is not reported in normal tracking mode
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::LeftDrag, &Position::new(2, 4)),
+        None
+    );
+}
+
+#[test]
+pub fn several_mouse_modes_are_set_and_reset_at_once() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"\x1b[?1000;1006h" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.mouse_tracking, MouseTracking::Normal);
+    assert_eq!(grid.mouse_encoding, MouseEncoding::Sgr);
+    for byte in b"\x1b[?1006;1000l" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.mouse_tracking, MouseTracking::Off);
+    assert_eq!(grid.mouse_encoding, MouseEncoding::X10);
+}
+
+#[test]
+pub fn mouse_events_in_sgr_encoding() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"\x1b[?1002h\x1b[?1006h" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::LeftDrag, &Position::new(-1, 30)),
+        Some(b"\x1b[<32;20;1M".to_vec())
+    );
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::LeftRelease, &Position::new(2, 3)),
+        Some(b"\x1b[<0;4;3m".to_vec())
+    );
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::WheelUp, &Position::new(0, 0)),
+        Some(b"\x1b[<64;1;1M".to_vec())
+    );
+    for byte in b"\x1b[?1002l" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert!(!grid.mouse_reporting());
+}
+
+#[test]
+pub fn right_and_middle_buttons_are_reported() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"\x1b[?1002h" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::RightPress, &Position::new(2, 3)),
+        Some(vec![0x1b, b'[', b'M', 34, 36, 35])
+    );
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::MiddleDrag, &Position::new(2, 3)),
+        Some(vec![0x1b, b'[', b'M', 65, 36, 35])
+    );
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::RightRelease, &Position::new(2, 3)),
+        Some(vec![0x1b, b'[', b'M', 35, 36, 35])
+    );
+    for byte in b"\x1b[?1006h" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::MiddlePress, &Position::new(2, 3)),
+        Some(b"\x1b[<1;4;3M".to_vec())
+    );
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::RightDrag, &Position::new(2, 3)),
+        Some(b"\x1b[<34;4;3M".to_vec())
+    );
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::MiddleRelease, &Position::new(2, 3)),
+        Some(b"\x1b[<1;4;3m".to_vec())
+    );
+}
+
+#[test]
+pub fn motion_is_reported_only_when_tracking_any_event() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"\x1b[?1002;1006h" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::Motion, &Position::new(2, 3)),
+        None
+    );
+    for byte in b"\x1b[?1003h" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.mouse_event_report(MouseEvent::Motion, &Position::new(2, 3)),
+        Some(b"\x1b[<35;4;3M".to_vec())
+    );
+}
+
+#[test]
+pub fn hyperlinks() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    let content = b"a \x1b]8;id=1;https://zellij.dev/a;b\x1b\\link\x1b]8;;\x1b\\ b";
+    for byte in content {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.link_at(&Position::new(0, 0)), None);
+    assert_eq!(
+        grid.link_at(&Position::new(0, 2)),
+        Some("https://zellij.dev/a;b".to_owned())
+    );
+    assert_eq!(
+        grid.link_at(&Position::new(0, 5)),
+        Some("https://zellij.dev/a;b".to_owned())
+    );
+    assert_eq!(grid.link_at(&Position::new(0, 6)), None);
+    assert_eq!(grid.link_under_cursor(), None);
+}
+
+#[test]
+pub fn hyperlinks_still_printed_keep_their_uri_after_many_others() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    let mut content = b"\x1b]8;;https://zellij.dev/first\x1b\\first\x1b]8;;\x1b\\".to_vec();
+    // more links than there are link anchors, all printed over each other on the second line
+    let link_count = u16::MAX as usize + 100;
+    for i in 0..link_count {
+        content.extend(
+            format!(
+                "\x1b[2;1H\x1b]8;;https://zellij.dev/{}\x1b\\link\x1b]8;;\x1b\\",
+                i
+            )
+            .bytes(),
+        );
+    }
+    for byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    assert_eq!(
+        grid.link_at(&Position::new(0, 0)),
+        Some("https://zellij.dev/first".to_owned())
+    );
+    assert_eq!(
+        grid.link_at(&Position::new(1, 0)),
+        Some(format!("https://zellij.dev/{}", link_count - 1))
+    );
+}
+
+#[test]
+pub fn sixel_images_are_ignored_without_character_cell_size() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    let content = "\u{1b}Pq#1;2;100;0;0#1!10~-!10~\u{1b}\\";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert!(grid.image_placements(0, 0).is_empty());
+    assert_eq!(grid.cursor_coordinates(), Some((0, 0)));
+}
+
+#[test]
+pub fn scroll_buffer_size_limits_scrollback() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    grid.set_scroll_buffer_size(10);
+    for i in 0..30 {
+        for byte in format!("{}\r\n", i).as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+    }
+    let dump = grid.dump_screen();
+    let lines: Vec<&str> = dump.split('\n').collect();
+    assert_eq!(lines.len(), 15);
+    assert_eq!(lines[0], "16");
+    assert_eq!(lines[13], "29");
+}
+
+#[test]
+pub fn scroll_buffer_size_of_a_new_grid_limits_scrollback() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default(), 3);
+    for i in 0..30 {
+        for byte in format!("{}\r\n", i).as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+    }
+    let dump = grid.dump_scrollback(false);
+    let lines: Vec<&str> = dump.split('\n').collect();
+    assert_eq!(lines[..3], ["23", "24", "25"]);
+    assert_eq!(lines.len(), 8);
+}
+
+#[test]
+pub fn scrollback_keeps_styles_of_scrolled_lines() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(2, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    let content = b"a\x1b[31mbc\x1b[m d\r\n\r\n\r\n";
+    for byte in content {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    for _ in 0..10 {
+        grid.scroll_up_one_line();
+    }
+    let line = &grid.as_character_lines()[0];
+    let red = CharacterStyles::new().foreground(Some(AnsiCode::NamedColor(NamedColor::Red)));
+    let text: String = line.iter().take(4).map(|c| c.character).collect();
+    assert_eq!(text, "abc ");
+    assert_eq!(line[0].styles, CharacterStyles::new());
+    assert_eq!(line[1].styles, red);
+    assert_eq!(line[2].styles, red);
+    assert_eq!(line[3].styles, CharacterStyles::new());
+}
+
+#[test]
+pub fn search_through_scrollback() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 10, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    let content = b"Error one\r\nok\r\nok\r\nok\r\nok\r\nok\r\nerror twoooooo\r\nok";
+    for byte in content {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.update_search_term(b"error");
+    assert_eq!(grid.search_info().match_count, 2);
+    // the match closest to the bottom is already on screen
+    assert_eq!(grid.search_info().active_match, Some(1));
+    assert_eq!(grid.search_results.match_at(2, 0), Some(true));
+    grid.search_up();
+    assert_eq!(grid.search_info().active_match, Some(0));
+    assert_eq!(grid.search_results.match_at(0, 0), Some(true));
+    assert_eq!(grid.search_results.match_at(0, 5), None);
+
+    // matches can span wrapped rows
+    grid.reset_search_term();
+    grid.update_search_term(b"twoooooo");
+    assert_eq!(grid.search_info().match_count, 1);
+    assert_eq!(grid.search_results.match_at(2, 6), Some(true));
+    assert_eq!(grid.search_results.match_at(3, 3), Some(true));
+    assert_eq!(grid.search_results.match_at(3, 4), None);
+}
+
+#[test]
+pub fn search_matches_are_found_again_in_new_output() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"error one\r\n" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.update_search_term(b"error");
+    assert_eq!(grid.search_info().match_count, 1);
+    for byte in b"error two\r\nok" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.refresh_search_matches();
+    assert_eq!(grid.search_info().match_count, 2);
+    assert_eq!(
+        grid.search_info().active_match,
+        Some(0),
+        "the active match is kept"
+    );
+
+    grid.reset_search_term();
+    assert_eq!(grid.search_info().query, "");
+    assert_eq!(grid.search_info().match_count, 0);
+    for byte in b"\r\nerror three" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.refresh_search_matches();
+    assert_eq!(grid.search_info().match_count, 0);
+}
+
+#[test]
+pub fn search_options() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"Error\r\nerror\r\nerr" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.update_search_term(b"e.r");
+    assert_eq!(grid.search_info().match_count, 0);
+    grid.toggle_search_option(SearchOption::Regex);
+    assert_eq!(grid.search_info().match_count, 3);
+    grid.toggle_search_option(SearchOption::CaseSensitivity);
+    assert_eq!(grid.search_info().match_count, 2);
+    grid.update_search_term(b"(");
+    assert_eq!(grid.search_info().match_count, 0);
+    grid.clear_search();
+    assert_eq!(grid.search_info(), Default::default());
+}
+
+#[test]
+pub fn dump_scrollback_joins_wrapped_lines() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(3, 5, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"line one\r\n\x1b[31mtwo\x1b[m\r\n3\r\n4\r\n5" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.dump_scrollback(false), "line one\ntwo\n3\n4\n5");
+    assert_eq!(
+        grid.dump_scrollback(true),
+        "line one\n\u{1b}[31mtwo\u{1b}[m\n3\n4\n5"
+    );
+}
+
+#[test]
+pub fn copy_mode_selections() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"one two\r\nthree four\r\nfive" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.start_copy_mode();
+    grid.copy_mode_move(CopyModeMotion::Up);
+    grid.copy_mode_move(CopyModeMotion::LineStart);
+    grid.copy_mode_select(SelectionKind::Character);
+    grid.copy_mode_move(CopyModeMotion::WordForward);
+    assert_eq!(grid.get_selected_text(), Some(String::from("three f")));
+    grid.copy_mode_move(CopyModeMotion::WordEnd);
+    assert_eq!(grid.get_selected_text(), Some(String::from("three four")));
+    grid.copy_mode_move(CopyModeMotion::Up);
+    grid.copy_mode_select(SelectionKind::Block);
+    assert_eq!(
+        grid.get_selected_text(),
+        Some(String::from("one two\nthree four"))
+    );
+    grid.copy_mode_move(CopyModeMotion::WordBackward);
+    assert_eq!(grid.get_selected_text(), Some(String::from("one t\nthree")));
+    grid.copy_mode_select(SelectionKind::Line);
+    grid.copy_mode_move(CopyModeMotion::Down);
+    grid.copy_mode_move(CopyModeMotion::Down);
+    assert_eq!(
+        grid.get_selected_text(),
+        Some(String::from("three four\nfive"))
+    );
+    grid.exit_copy_mode();
+    assert_eq!(grid.get_selected_text(), None);
+}
+
+#[test]
+pub fn copy_mode_scrolls_through_scrollback() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(3, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"1\r\n2\r\n3\r\n4\r\n5" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.start_copy_mode();
+    assert_eq!(grid.cursor_coordinates(), Some((1, 2)));
+    grid.copy_mode_move(CopyModeMotion::Top);
+    assert_eq!(grid.cursor_coordinates(), Some((0, 0)));
+    grid.copy_mode_select(SelectionKind::Line);
+    grid.copy_mode_move(CopyModeMotion::Down);
+    assert_eq!(grid.get_selected_text(), Some(String::from("1\n2")));
+    grid.copy_mode_move(CopyModeMotion::Bottom);
+    assert_eq!(grid.cursor_coordinates(), Some((0, 2)));
+    assert_eq!(
+        grid.get_selected_text(),
+        Some(String::from("1\n2\n3\n4\n5"))
+    );
+}
+
+#[test]
+pub fn select_word_and_line_across_wraps() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 10, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for byte in b"ab hello.world\r\nline two" {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.select_word(&Position::new(1, 1), DEFAULT_WORD_SEPARATORS);
+    assert_eq!(grid.selection.start, Position::new(0, 3));
+    assert_eq!(grid.selection.end, Position::new(1, 4));
+    grid.select_word(&Position::new(0, 5), " .");
+    assert_eq!(grid.get_selected_text(), Some(String::from("hello")));
+    grid.select_word(&Position::new(0, 2), DEFAULT_WORD_SEPARATORS);
+    assert_eq!(grid.selection.start, Position::new(0, 2));
+    assert_eq!(grid.selection.end, Position::new(0, 3));
+    grid.select_line(&Position::new(1, 2));
+    assert_eq!(grid.selection.start, Position::new(0, 0));
+    assert_eq!(grid.selection.end, Position::new(1, 10));
+    grid.select_line(&Position::new(2, 2));
+    assert_eq!(grid.get_selected_text(), Some(String::from("line two")));
+}
+
+#[test]
+pub fn selection_scrolls_past_the_edges() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    for i in 1..=10 {
+        for byte in format!("line {}\r\n", i).as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+    }
+    grid.start_selection(&Position::new(2, 0));
+    grid.scroll_selection_towards(&Position::new(-2, 3));
+    assert_eq!(
+        grid.get_selected_text(),
+        Some(String::from("e 5\nline 6\nline 7\nline 8"))
+    );
+    grid.scroll_selection_towards(&Position::new(7, 0));
+    assert_eq!(
+        grid.get_selected_text(),
+        Some(String::from("line 9\nline 10"))
+    );
+}
@@ -1,9 +1,20 @@
-use super::super::TerminalPane;
+use super::super::{TerminalPane, DEFAULT_SCROLL_BUFFER_SIZE};
 use crate::tab::Pane;
 use ::insta::assert_snapshot;
-use zellij_utils::pane_size::PositionAndSize;
+use zellij_utils::pane_size::{PositionAndSize, SizeInPixels};
+use zellij_utils::vte;
 use zellij_utils::zellij_tile::data::Palette;
 
+/// Parses output into the grid of the pane, as the task reading its pty does
+fn feed(terminal_pane: &TerminalPane, bytes: &[u8]) {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = terminal_pane.grid();
+    for byte in bytes {
+        vte_parser.advance(&mut *grid, *byte);
+    }
+    grid.should_render = true;
+}
+
 #[test]
 pub fn scrolling_inside_a_pane() {
     let fake_win_size = PositionAndSize {
@@ -15,16 +26,70 @@ pub fn scrolling_inside_a_pane() {
     };
     let pid = 1;
     let palette = Palette::default();
-    let mut terminal_pane = TerminalPane::new(pid, fake_win_size, palette);
+    let mut terminal_pane = TerminalPane::new(
+        pid,
+        fake_win_size,
+        palette,
+        None,
+        DEFAULT_SCROLL_BUFFER_SIZE,
+    );
     let mut text_to_fill_pane = String::new();
     for i in 0..30 {
         text_to_fill_pane.push_str(&format!("\rline {}\n", i + 1));
     }
-    terminal_pane.handle_pty_bytes(text_to_fill_pane.as_bytes().to_vec());
+    feed(&terminal_pane, text_to_fill_pane.as_bytes());
     terminal_pane.scroll_up(10);
-    assert_snapshot!(format!("{:?}", terminal_pane.grid));
+    assert_snapshot!(format!("{:?}", *terminal_pane.grid()));
     terminal_pane.scroll_down(3);
-    assert_snapshot!(format!("{:?}", terminal_pane.grid));
+    assert_snapshot!(format!("{:?}", *terminal_pane.grid()));
     terminal_pane.clear_scroll();
-    assert_snapshot!(format!("{:?}", terminal_pane.grid));
+    assert_snapshot!(format!("{:?}", *terminal_pane.grid()));
+}
+
+#[test]
+pub fn sixel_images_scroll_with_the_text() {
+    let fake_win_size = PositionAndSize {
+        cols: 20,
+        rows: 10,
+        x: 2,
+        y: 3,
+        ..Default::default()
+    };
+    let pid = 1;
+    let palette = Palette::default();
+    let character_cell_size = Some(SizeInPixels {
+        height: 10,
+        width: 5,
+    });
+    let mut terminal_pane = TerminalPane::new(
+        pid,
+        fake_win_size,
+        palette,
+        character_cell_size,
+        DEFAULT_SCROLL_BUFFER_SIZE,
+    );
+    // a red image, 10 pixels wide and 12 high
+    let sixel_image = "ab\u{1b}Pq#1;2;100;0;0#1!10~-!10~\u{1b}\\";
+    feed(&terminal_pane, sixel_image.as_bytes());
+    assert_eq!(terminal_pane.cursor_coordinates(), Some((2, 1)));
+    let placements = terminal_pane.image_placements();
+    assert_eq!(placements.len(), 1);
+    assert_eq!(
+        (
+            placements[0].x,
+            placements[0].y,
+            placements[0].columns,
+            placements[0].rows
+        ),
+        (4, 3, 2, 2)
+    );
+    let rendered_image = terminal_pane.render_image(&placements[0]).unwrap();
+    assert!(rendered_image.starts_with("\u{1b}[4;5H\u{1b}P0;1;0q\"1;1;10;12#1;2;100;0;0"));
+
+    // scroll the first line of the image out of the pane
+    feed(&terminal_pane, "\n".repeat(9).as_bytes());
+    let scrolled_placements = terminal_pane.image_placements();
+    assert_ne!(scrolled_placements, placements);
+    let rendered_image = terminal_pane.render_image(&scrolled_placements[0]).unwrap();
+    assert!(rendered_image.starts_with("\u{1b}[4;5H\u{1b}P0;1;0q\"1;1;10;2#1;2;100;0;0"));
 }
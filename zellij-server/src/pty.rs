@@ -1,6 +1,8 @@
 use crate::{
+    asciicast::{recorded_output, replay_delay, PaneRecording, RecordedOutput},
     os_input_output::{AsyncReader, Pid, ServerOsApi},
-    panes::PaneId,
+    pane_pipe::PanePipe,
+    panes::{PaneId, SharedGrid},
     screen::ScreenInstruction,
     thread_bus::{Bus, ThreadSenders},
     wasm_vm::PluginInstruction,
@@ -10,22 +12,43 @@ use async_std::{
     future::timeout as async_timeout,
     task::{self, JoinHandle},
 };
+use log::error;
 use std::{
     collections::HashMap,
-    os::unix::io::RawFd,
+    env,
+    fs::{self, File},
+    io::{self, BufReader},
+    os::unix::io::{AsRawFd, RawFd},
+    path::{Path, PathBuf},
+    process::{self, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::TrySendError,
+        Arc, Mutex,
+    },
+    thread,
     time::{Duration, Instant},
 };
 use zellij_utils::{
     async_std,
+    consts::ZELLIJ_TMP_DIR,
     errors::{get_current_ctx, ContextType, PtyContext},
     input::{
-        command::TerminalAction,
-        layout::{Layout, Run},
+        actions::PipeTarget,
+        command::{RunCommand, TerminalAction},
+        layout::{Layout, Run, SessionSnapshot, TabSnapshot},
     },
     logging::debug_to_file,
+    vte,
 };
 
-pub type VteBytes = Vec<u8>;
+#[cfg(target_os = "macos")]
+const DEFAULT_LINK_OPENER: &str = "open";
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_LINK_OPENER: &str = "xdg-open";
+
+/// How often a replay checks whether its pane has shared its grid yet
+const GRID_POLL_INTERVAL: Duration = Duration::from_millis(5);
 
 /// Instructions related to PTYs (pseudoterminals).
 #[derive(Clone, Debug)]
@@ -34,8 +57,30 @@ pub(crate) enum PtyInstruction {
     SpawnTerminalVertically(Option<TerminalAction>),
     SpawnTerminalHorizontally(Option<TerminalAction>),
     NewTab(Option<TerminalAction>),
+    NewTabFromTemplate(String, Option<TerminalAction>),
+    StartSession(Option<TerminalAction>),
     ClosePane(PaneId),
     CloseTab(Vec<PaneId>),
+    SerializeSession(PathBuf, Vec<(TabSnapshot, Vec<RawFd>)>),
+    ResurrectSession(SessionSnapshot, Option<TerminalAction>),
+    DumpLayout(PathBuf, Layout, Vec<RawFd>),
+    DumpScrollback(PathBuf, String),
+    /// Opens the scrollback of a pane in the default editor, in a new pane
+    EditScrollback(RawFd, String),
+    UpdateActiveTerminal(Option<RawFd>),
+    OpenLink(String),
+    /// Copies the output of the pane to a target, without ANSI escape sequences if `true`
+    PipePane(RawFd, PipeTarget, bool),
+    StopPipePane(RawFd),
+    /// Records the output of the pane, which is as wide and as high as given, to an asciicast
+    /// file
+    StartRecording(RawFd, PathBuf, u16, u16),
+    StopRecording(RawFd),
+    /// Plays an asciicast file in a new read-only pane, this many times faster than it was
+    /// recorded
+    ReplayRecording(PathBuf, f64),
+    /// The grid of a new terminal pane, for the task reading its output to parse it into
+    ShareGrid(RawFd, SharedGrid),
     Exit,
 }
 
@@ -48,6 +93,21 @@ impl From<&PtyInstruction> for PtyContext {
             PtyInstruction::ClosePane(_) => PtyContext::ClosePane,
             PtyInstruction::CloseTab(_) => PtyContext::CloseTab,
             PtyInstruction::NewTab(_) => PtyContext::NewTab,
+            PtyInstruction::NewTabFromTemplate(..) => PtyContext::NewTabFromTemplate,
+            PtyInstruction::StartSession(_) => PtyContext::StartSession,
+            PtyInstruction::SerializeSession(..) => PtyContext::SerializeSession,
+            PtyInstruction::ResurrectSession(..) => PtyContext::ResurrectSession,
+            PtyInstruction::DumpLayout(..) => PtyContext::DumpLayout,
+            PtyInstruction::DumpScrollback(..) => PtyContext::DumpScrollback,
+            PtyInstruction::EditScrollback(..) => PtyContext::EditScrollback,
+            PtyInstruction::UpdateActiveTerminal(_) => PtyContext::UpdateActiveTerminal,
+            PtyInstruction::OpenLink(_) => PtyContext::OpenLink,
+            PtyInstruction::PipePane(..) => PtyContext::PipePane,
+            PtyInstruction::StopPipePane(_) => PtyContext::StopPipePane,
+            PtyInstruction::StartRecording(..) => PtyContext::StartRecording,
+            PtyInstruction::StopRecording(_) => PtyContext::StopRecording,
+            PtyInstruction::ReplayRecording(..) => PtyContext::ReplayRecording,
+            PtyInstruction::ShareGrid(..) => PtyContext::ShareGrid,
             PtyInstruction::Exit => PtyContext::Exit,
         }
     }
@@ -56,8 +116,29 @@ impl From<&PtyInstruction> for PtyContext {
 pub(crate) struct Pty {
     pub bus: Bus<PtyInstruction>,
     pub id_to_child_pid: HashMap<RawFd, Pid>,
+    id_to_terminal_action: HashMap<RawFd, Option<TerminalAction>>,
+    /// The focused terminal, new terminals are started in its working directory
+    active_terminal: Option<RawFd>,
+    inherit_cwd: bool,
+    /// The command hyperlinks are opened with
+    link_opener: Option<String>,
     debug_to_file: bool,
     task_handles: HashMap<RawFd, JoinHandle<()>>,
+    /// Where the output of panes is copied to, shared with the tasks reading it
+    pane_pipes: Arc<Mutex<HashMap<RawFd, PanePipe>>>,
+    /// The recordings of panes, shared with the tasks reading their output
+    pane_recordings: Arc<Mutex<HashMap<RawFd, PaneRecording>>>,
+    /// The recordings being played in read-only panes
+    replays: HashMap<RawFd, Replay>,
+    /// The grids of the terminal panes, shared with the tasks reading their output
+    pane_grids: Arc<Mutex<HashMap<RawFd, SharedGrid>>>,
+}
+
+/// A recording being played in a read-only pane. The pane is known by the descriptor of the
+/// recording, which stays open until the pane is closed so that no terminal gets the same id.
+struct Replay {
+    _recording: File,
+    task_handle: JoinHandle<()>,
 }
 
 pub(crate) fn pty_thread_main(mut pty: Pty, maybe_layout: Option<Layout>) {
@@ -87,16 +168,32 @@ pub(crate) fn pty_thread_main(mut pty: Pty, maybe_layout: Option<Layout>) {
                     .unwrap();
             }
             PtyInstruction::NewTab(terminal_action) => {
-                if let Some(layout) = maybe_layout.clone() {
-                    pty.spawn_terminals_for_layout(layout, terminal_action);
-                } else {
-                    let pid = pty.spawn_terminal(terminal_action);
-                    pty.bus
-                        .senders
-                        .send_to_screen(ScreenInstruction::NewTab(pid))
-                        .unwrap();
+                pty.new_tab(maybe_layout.as_ref(), terminal_action);
+            }
+            PtyInstruction::NewTabFromTemplate(name, terminal_action) => {
+                match maybe_layout.as_ref().and_then(|l| l.tab_template(&name)) {
+                    Some(tab) => pty.spawn_terminals_for_layout(tab.clone(), terminal_action),
+                    None => {
+                        error!("No tab named {:?} in the layout", name);
+                        pty.new_tab(maybe_layout.as_ref(), terminal_action);
+                    }
                 }
             }
+            PtyInstruction::StartSession(terminal_action) => match maybe_layout.as_ref() {
+                Some(layout) if !layout.tabs.is_empty() => {
+                    let focused_tab = layout.focused_tab();
+                    for tab in layout.tabs.iter() {
+                        pty.spawn_terminals_for_layout(tab.clone(), terminal_action.clone());
+                    }
+                    if let Some(position) = focused_tab {
+                        pty.bus
+                            .senders
+                            .send_to_screen(ScreenInstruction::GoToTab(position as u32 + 1))
+                            .unwrap();
+                    }
+                }
+                _ => pty.new_tab(maybe_layout.as_ref(), terminal_action),
+            },
             PtyInstruction::ClosePane(id) => {
                 pty.close_pane(id);
                 pty.bus
@@ -111,6 +208,78 @@ pub(crate) fn pty_thread_main(mut pty: Pty, maybe_layout: Option<Layout>) {
                     .send_to_server(ServerInstruction::UnblockInputThread)
                     .unwrap();
             }
+            PtyInstruction::SerializeSession(path, tabs) => {
+                let snapshot = pty.session_snapshot(tabs);
+                if let Err(e) = snapshot.write_to_path(&path) {
+                    error!("Failed to serialize session to {:?}: {}", path, e);
+                }
+            }
+            PtyInstruction::ResurrectSession(snapshot, default_shell) => {
+                let active_tab = snapshot.tabs.iter().position(|tab| tab.active);
+                for tab in snapshot.tabs {
+                    pty.resurrect_tab(tab, default_shell.clone());
+                }
+                if let Some(position) = active_tab {
+                    pty.bus
+                        .senders
+                        .send_to_screen(ScreenInstruction::GoToTab(position as u32 + 1))
+                        .unwrap();
+                }
+            }
+            PtyInstruction::DumpLayout(path, mut layout, terminal_ids) => {
+                let mut run_instructions = terminal_ids
+                    .iter()
+                    .map(|id| pty.layout_run_instruction(*id));
+                layout.fill_terminal_run_instructions(&mut run_instructions);
+                if let Err(e) = layout.write_to_path(&path) {
+                    error!("Failed to dump layout to {:?}: {}", path, e);
+                }
+            }
+            PtyInstruction::DumpScrollback(path, scrollback) => {
+                if let Err(e) = fs::write(&path, scrollback) {
+                    error!("Failed to write the scrollback to {:?}: {}", path, e);
+                }
+            }
+            PtyInstruction::EditScrollback(id, scrollback) => {
+                match write_scrollback_file(id, &scrollback) {
+                    Ok(file) => {
+                        let path = file.0.clone();
+                        let pid = pty.spawn_terminal_owning(
+                            Some(TerminalAction::OpenFile(path)),
+                            Some(file),
+                        );
+                        pty.bus
+                            .senders
+                            .send_to_screen(ScreenInstruction::NewPane(PaneId::Terminal(pid)))
+                            .unwrap();
+                    }
+                    Err(e) => error!("Failed to write the scrollback of pane {}: {}", id, e),
+                }
+            }
+            PtyInstruction::UpdateActiveTerminal(id) => {
+                pty.active_terminal = id;
+            }
+            PtyInstruction::OpenLink(uri) => {
+                pty.open_link(&uri);
+            }
+            PtyInstruction::PipePane(id, target, strip_ansi) => {
+                pty.pipe_pane(id, &target, strip_ansi);
+            }
+            PtyInstruction::StopPipePane(id) => {
+                pty.pane_pipes.lock().unwrap().remove(&id);
+            }
+            PtyInstruction::StartRecording(id, path, columns, rows) => {
+                pty.start_recording(id, &path, columns, rows);
+            }
+            PtyInstruction::StopRecording(id) => {
+                pty.pane_recordings.lock().unwrap().remove(&id);
+            }
+            PtyInstruction::ReplayRecording(path, speed) => {
+                pty.replay_recording(&path, speed);
+            }
+            PtyInstruction::ShareGrid(id, grid) => {
+                pty.pane_grids.lock().unwrap().insert(id, grid);
+            }
             PtyInstruction::Exit => break,
         }
     }
@@ -152,17 +321,163 @@ async fn deadline_read(
     }
 }
 
+/// A file that is removed once it is dropped
+struct TemporaryFile(PathBuf);
+
+impl Drop for TemporaryFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.0) {
+            error!("Failed to remove {:?}: {}", self.0, e);
+        }
+    }
+}
+
+/// Writes the scrollback of the pane `id` to a new file in the temporary directory of zellij
+fn write_scrollback_file(id: RawFd, scrollback: &str) -> io::Result<TemporaryFile> {
+    // the scrollback of a pane can be edited in several panes at once
+    static FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+    let count = FILE_COUNT.fetch_add(1, Ordering::Relaxed);
+    fs::create_dir_all(&*ZELLIJ_TMP_DIR)?;
+    let path = ZELLIJ_TMP_DIR.join(format!("scrollback-{}-{}-{}.txt", process::id(), id, count));
+    fs::write(&path, scrollback)?;
+    Ok(TemporaryFile(path))
+}
+
+/// Writes the answers of the terminal to the program running in it
+fn write_to_pty(os_input: &dyn ServerOsApi, pid: RawFd, messages: Vec<Vec<u8>>) {
+    for message in messages {
+        let written = os_input
+            .write_to_tty_stdin(pid, &message)
+            .and_then(|_| os_input.tcdrain(pid));
+        if let Err(e) = written {
+            error!("Failed to write to terminal {}: {}", pid, e);
+        }
+    }
+}
+
 async fn async_send_to_screen(senders: ThreadSenders, screen_instruction: ScreenInstruction) {
     task::spawn_blocking(move || senders.send_to_screen(screen_instruction))
         .await
         .unwrap()
 }
 
+/// Describes where the output of a pane is piped to, for the user
+fn pipe_target_description(target: &PipeTarget) -> String {
+    match target {
+        PipeTarget::File(path) => path.display().to_string(),
+        PipeTarget::Command(command_line) => format!("`{}`", command_line),
+    }
+}
+
+/// Copies the output of the pane to its pipe if it has one. The pipe is stopped once it falls
+/// too far behind, which is returned as a notice for the user, or once writing to it failed.
+fn pipe_pane_output(
+    pane_pipes: &Mutex<HashMap<RawFd, PanePipe>>,
+    pid: RawFd,
+    bytes: &[u8],
+) -> Option<String> {
+    // the pipes only hand the output over to their threads while they're locked, the reader
+    // tasks of the other panes use them as well
+    let stopped_pipe = {
+        let mut pane_pipes = pane_pipes.lock().unwrap();
+        let written = pane_pipes.get(&pid)?.write(bytes);
+        match written {
+            Ok(()) => return None,
+            Err(TrySendError::Full(_)) => pane_pipes.remove(&pid)?,
+            Err(TrySendError::Disconnected(_)) => {
+                // the pipe already reported why it failed
+                pane_pipes.remove(&pid);
+                return None;
+            }
+        }
+    };
+    error!("The pipe of pane {} fell behind its output", pid);
+    Some(format!(
+        "Stopped piping the output of a pane to {}, which did not keep up with it",
+        pipe_target_description(&stopped_pipe.target)
+    ))
+}
+
+/// Adds the output of the pane to its recording if it has one, along with the size of the pane
+/// if it is known. The recording is stopped once it falls too far behind, which is returned as
+/// a notice for the user, or once writing it failed.
+fn record_pane_output(
+    pane_recordings: &Mutex<HashMap<RawFd, PaneRecording>>,
+    pid: RawFd,
+    bytes: &[u8],
+    size: Option<(u16, u16)>,
+) -> Option<String> {
+    let stopped_recording = {
+        let mut pane_recordings = pane_recordings.lock().unwrap();
+        let recorded = pane_recordings.get_mut(&pid)?.record(bytes, size);
+        match recorded {
+            Ok(()) => return None,
+            Err(TrySendError::Full(_)) => pane_recordings.remove(&pid)?,
+            Err(TrySendError::Disconnected(_)) => {
+                // the recording already reported why it failed
+                pane_recordings.remove(&pid);
+                return None;
+            }
+        }
+    };
+    error!("The recording of pane {} fell behind its output", pid);
+    Some(format!(
+        "Stopped recording a pane to {}, which did not keep up with its output",
+        stopped_recording.path.display()
+    ))
+}
+
+/// Parses the output of a terminal into the grid of its pane, keeping it until the screen thread
+/// shared the grid of the new pane
+struct GridFeeder {
+    pid: RawFd,
+    pane_grids: Arc<Mutex<HashMap<RawFd, SharedGrid>>>,
+    grid: Option<SharedGrid>,
+    vte_parser: vte::Parser,
+    pending_output: Vec<u8>,
+    /// The columns and rows of the grid when the output was last parsed into it
+    size: Option<(u16, u16)>,
+}
+
+impl GridFeeder {
+    /// Parses `bytes` into the grid, returning what the terminal answers to the program in it
+    fn feed(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        if self.grid.is_none() {
+            self.grid = self.pane_grids.lock().unwrap().get(&self.pid).cloned();
+        }
+        let grid = match &self.grid {
+            Some(grid) => grid,
+            None => {
+                self.pending_output.extend_from_slice(bytes);
+                return vec![];
+            }
+        };
+        let mut grid = grid.lock().unwrap();
+        for byte in self.pending_output.drain(..).chain(bytes.iter().copied()) {
+            self.vte_parser.advance(&mut *grid, byte);
+        }
+        grid.should_render = true;
+        self.size = Some((grid.width as u16, grid.height as u16));
+        grid.pending_messages_to_pty.drain(..).collect()
+    }
+    fn is_waiting_for_grid(&self) -> bool {
+        !self.pending_output.is_empty()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn stream_terminal_bytes(
     pid: RawFd,
     senders: ThreadSenders,
     os_input: Box<dyn ServerOsApi>,
+    pane_pipes: Arc<Mutex<HashMap<RawFd, PanePipe>>>,
+    pane_recordings: Arc<Mutex<HashMap<RawFd, PaneRecording>>>,
+    // the output is parsed here rather than on the screen thread, so that busy panes are
+    // parsed in parallel and don't hold up the others
+    mut grid_feeder: GridFeeder,
     debug: bool,
+    // removed when the terminal exits, or when the task is cancelled as the pane is closed
+    temporary_file: Option<TemporaryFile>,
 ) -> JoinHandle<()> {
     let mut err_ctx = get_current_ctx();
     task::spawn({
@@ -170,7 +485,7 @@ fn stream_terminal_bytes(
             err_ctx.add_call(ContextType::AsyncTask);
 
             // After a successful read, we keep on reading additional data up to a duration of
-            // `render_pause`. This is in order to batch up PtyBytes before rendering them.
+            // `render_pause`. This is in order to batch up the output before rendering it.
             // Once `render_deadline` has elapsed, we send Render.
             let render_pause = Duration::from_millis(30);
             let mut render_deadline = None;
@@ -181,6 +496,14 @@ fn stream_terminal_bytes(
                 match deadline_read(async_reader.as_mut(), render_deadline, &mut buf).await {
                     ReadResult::Ok(0) | ReadResult::Err(_) => break, // EOF or error
                     ReadResult::Timeout => {
+                        // the output read before the pane was created
+                        write_to_pty(os_input.as_ref(), pid, grid_feeder.feed(&[]));
+                        if grid_feeder.is_waiting_for_grid() {
+                            render_deadline = Some(Instant::now() + render_pause);
+                            continue;
+                        }
+                        // waits while the queue of the screen thread is full, and the program
+                        // blocks meanwhile once it fills the buffer of the pty
                         async_send_to_screen(senders.clone(), ScreenInstruction::Render).await;
                         // next read does not need a deadline as we just rendered everything
                         render_deadline = None;
@@ -190,11 +513,23 @@ fn stream_terminal_bytes(
                         if debug {
                             let _ = debug_to_file(bytes, pid);
                         }
-                        async_send_to_screen(
-                            senders.clone(),
-                            ScreenInstruction::PtyBytes(pid, bytes.to_vec()),
-                        )
-                        .await;
+                        if let Some(notice) = pipe_pane_output(&pane_pipes, pid, bytes) {
+                            async_send_to_screen(
+                                senders.clone(),
+                                ScreenInstruction::ShowNotice(notice),
+                            )
+                            .await;
+                        }
+                        write_to_pty(os_input.as_ref(), pid, grid_feeder.feed(bytes));
+                        if let Some(notice) =
+                            record_pane_output(&pane_recordings, pid, bytes, grid_feeder.size)
+                        {
+                            async_send_to_screen(
+                                senders.clone(),
+                                ScreenInstruction::ShowNotice(notice),
+                            )
+                            .await;
+                        }
                         // if we already have a render_deadline we keep it, otherwise we set it
                         // to the duration of `render_pause`.
                         render_deadline.get_or_insert(Instant::now() + render_pause);
@@ -202,76 +537,141 @@ fn stream_terminal_bytes(
                 }
             }
             async_send_to_screen(senders.clone(), ScreenInstruction::Render).await;
+            pane_pipes.lock().unwrap().remove(&pid);
+            pane_recordings.lock().unwrap().remove(&pid);
 
             // this is a little hacky, and is because the tests end the file as soon as
             // we read everything, rather than hanging until there is new data
             // a better solution would be to fix the test fakes, but this will do for now
             async_send_to_screen(senders, ScreenInstruction::ClosePane(PaneId::Terminal(pid)))
                 .await;
+            drop(temporary_file);
+        }
+    })
+}
+
+/// Plays the output of a recording into the grid of its pane, at the pace it was recorded sped up
+/// by `speed`
+fn replay_output(
+    output: impl Iterator<Item = RecordedOutput> + Send + 'static,
+    speed: f64,
+    senders: ThreadSenders,
+    mut grid_feeder: GridFeeder,
+) -> JoinHandle<()> {
+    let mut err_ctx = get_current_ctx();
+    task::spawn(async move {
+        err_ctx.add_call(ContextType::AsyncTask);
+        let started_at = Instant::now();
+        for output in output {
+            let due_at = started_at + replay_delay(output.time, speed);
+            task::sleep(due_at.saturating_duration_since(Instant::now())).await;
+            // the answers of the terminal have no program to go to
+            grid_feeder.feed(output.text.as_bytes());
+            while grid_feeder.is_waiting_for_grid() {
+                task::sleep(GRID_POLL_INTERVAL).await;
+                grid_feeder.feed(&[]);
+            }
+            async_send_to_screen(senders.clone(), ScreenInstruction::Render).await;
         }
     })
 }
 
+/// Runs `terminal_action` in `cwd`, unless it already has a working directory of its own.
+fn with_cwd(
+    terminal_action: Option<TerminalAction>,
+    cwd: Option<PathBuf>,
+) -> Option<TerminalAction> {
+    match (terminal_action, cwd) {
+        (Some(TerminalAction::RunCommand(mut command)), cwd) => {
+            command.cwd = command.cwd.or(cwd);
+            Some(TerminalAction::RunCommand(command))
+        }
+        (None, Some(cwd)) => match env::var("SHELL") {
+            Ok(shell) => Some(TerminalAction::RunCommand(RunCommand {
+                command: shell.into(),
+                cwd: Some(cwd),
+                ..Default::default()
+            })),
+            Err(_) => None,
+        },
+        (terminal_action, _) => terminal_action,
+    }
+}
+
 impl Pty {
-    pub fn new(bus: Bus<PtyInstruction>, debug_to_file: bool) -> Self {
+    pub fn new(
+        bus: Bus<PtyInstruction>,
+        debug_to_file: bool,
+        inherit_cwd: bool,
+        link_opener: Option<String>,
+    ) -> Self {
         Pty {
             bus,
             id_to_child_pid: HashMap::new(),
+            id_to_terminal_action: HashMap::new(),
+            active_terminal: None,
+            inherit_cwd,
+            link_opener,
             debug_to_file,
             task_handles: HashMap::new(),
+            pane_pipes: Arc::new(Mutex::new(HashMap::new())),
+            pane_recordings: Arc::new(Mutex::new(HashMap::new())),
+            replays: HashMap::new(),
+            pane_grids: Arc::new(Mutex::new(HashMap::new())),
         }
     }
     pub fn spawn_terminal(&mut self, terminal_action: Option<TerminalAction>) -> RawFd {
+        self.spawn_terminal_owning(terminal_action, None)
+    }
+    /// Like [`Pty::spawn_terminal`], removing `temporary_file` once the terminal is gone
+    fn spawn_terminal_owning(
+        &mut self,
+        terminal_action: Option<TerminalAction>,
+        temporary_file: Option<TemporaryFile>,
+    ) -> RawFd {
+        let cwd = self.active_terminal_cwd();
         let (pid_primary, pid_secondary): (RawFd, Pid) = self
             .bus
             .os_input
             .as_mut()
             .unwrap()
-            .spawn_terminal(terminal_action);
+            .spawn_terminal(with_cwd(terminal_action.clone(), cwd));
         let task_handle = stream_terminal_bytes(
             pid_primary,
             self.bus.senders.clone(),
             self.bus.os_input.as_ref().unwrap().clone(),
+            self.pane_pipes.clone(),
+            self.pane_recordings.clone(),
+            self.grid_feeder(pid_primary, vec![]),
             self.debug_to_file,
+            temporary_file,
         );
         self.task_handles.insert(pid_primary, task_handle);
         self.id_to_child_pid.insert(pid_primary, pid_secondary);
+        self.id_to_terminal_action
+            .insert(pid_primary, terminal_action);
         pid_primary
     }
+    /// Opens a new tab with the layout of the session, or a single pane if the layout
+    /// does not describe one apart from its `tabs`.
+    pub fn new_tab(&mut self, layout: Option<&Layout>, terminal_action: Option<TerminalAction>) {
+        match layout.filter(|layout| !layout.parts.is_empty()) {
+            Some(layout) => self.spawn_terminals_for_layout(layout.clone(), terminal_action),
+            None => {
+                let pid = self.spawn_terminal(terminal_action);
+                self.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::NewTab(pid))
+                    .unwrap();
+            }
+        }
+    }
     pub fn spawn_terminals_for_layout(
         &mut self,
         layout: Layout,
         default_shell: Option<TerminalAction>,
     ) {
-        let extracted_run_instructions = layout.extract_run_instructions();
-        let mut new_pane_pids = vec![];
-        for run_instruction in extracted_run_instructions {
-            match run_instruction {
-                Some(Run::Command(command)) => {
-                    let cmd = TerminalAction::RunCommand(command);
-                    let (pid_primary, pid_secondary): (RawFd, Pid) = self
-                        .bus
-                        .os_input
-                        .as_mut()
-                        .unwrap()
-                        .spawn_terminal(Some(cmd));
-                    self.id_to_child_pid.insert(pid_primary, pid_secondary);
-                    new_pane_pids.push(pid_primary);
-                }
-                None => {
-                    let (pid_primary, pid_secondary): (RawFd, Pid) = self
-                        .bus
-                        .os_input
-                        .as_mut()
-                        .unwrap()
-                        .spawn_terminal(default_shell.clone());
-                    self.id_to_child_pid.insert(pid_primary, pid_secondary);
-                    new_pane_pids.push(pid_primary);
-                }
-                // Investigate moving plugin loading to here.
-                Some(Run::Plugin(_)) => {}
-            }
-        }
+        let new_pane_pids = self.spawn_layout_terminals(&layout, default_shell);
         self.bus
             .senders
             .send_to_screen(ScreenInstruction::ApplyLayout(
@@ -279,19 +679,295 @@ impl Pty {
                 new_pane_pids.clone(),
             ))
             .unwrap();
+        self.stream_layout_terminals(new_pane_pids, vec![]);
+    }
+    /// Rebuilds a serialized tab, replaying its scrollback (if any) before the
+    /// output of the freshly spawned terminals.
+    pub fn resurrect_tab(&mut self, tab: TabSnapshot, default_shell: Option<TerminalAction>) {
+        let new_pane_pids = self.spawn_layout_terminals(&tab.layout, default_shell);
+        self.bus
+            .senders
+            .send_to_screen(ScreenInstruction::ApplyLayout(
+                tab.layout,
+                new_pane_pids.clone(),
+            ))
+            .unwrap();
+        if !tab.name.is_empty() {
+            self.bus
+                .senders
+                .send_to_screen(ScreenInstruction::UpdateTabName(vec![0]))
+                .unwrap();
+            self.bus
+                .senders
+                .send_to_screen(ScreenInstruction::UpdateTabName(tab.name.into_bytes()))
+                .unwrap();
+        }
+        let scrollback = tab
+            .scrollback
+            .iter()
+            .map(|scrollback| {
+                let scrollback = scrollback.trim_end();
+                if scrollback.is_empty() {
+                    vec![]
+                } else {
+                    format!("{}\r\n", scrollback.replace('\n', "\r\n")).into_bytes()
+                }
+            })
+            .collect();
+        self.stream_layout_terminals(new_pane_pids, scrollback);
+    }
+    fn spawn_layout_terminals(
+        &mut self,
+        layout: &Layout,
+        default_shell: Option<TerminalAction>,
+    ) -> Vec<RawFd> {
+        let extracted_run_instructions = layout.extract_run_instructions();
+        let cwd = self.active_terminal_cwd();
+        let mut new_pane_pids = vec![];
+        for run_instruction in extracted_run_instructions {
+            let terminal_action = match run_instruction {
+                Some(Run::Command(command)) => Some(TerminalAction::RunCommand(command)),
+                None => default_shell.clone(),
+                // Investigate moving plugin loading to here.
+                Some(Run::Plugin(_)) => continue,
+            };
+            let (pid_primary, pid_secondary): (RawFd, Pid) = self
+                .bus
+                .os_input
+                .as_mut()
+                .unwrap()
+                .spawn_terminal(with_cwd(terminal_action.clone(), cwd.clone()));
+            self.id_to_child_pid.insert(pid_primary, pid_secondary);
+            self.id_to_terminal_action
+                .insert(pid_primary, terminal_action);
+            new_pane_pids.push(pid_primary);
+        }
+        new_pane_pids
+    }
+    /// Streams the output of the terminals, after the output they start with (if any)
+    fn stream_layout_terminals(&mut self, new_pane_pids: Vec<RawFd>, output: Vec<Vec<u8>>) {
+        let mut output = output.into_iter();
         for id in new_pane_pids {
             let task_handle = stream_terminal_bytes(
                 id,
                 self.bus.senders.clone(),
                 self.bus.os_input.as_ref().unwrap().clone(),
+                self.pane_pipes.clone(),
+                self.pane_recordings.clone(),
+                self.grid_feeder(id, output.next().unwrap_or_default()),
                 self.debug_to_file,
+                None,
             );
             self.task_handles.insert(id, task_handle);
         }
     }
+    fn grid_feeder(&self, id: RawFd, pending_output: Vec<u8>) -> GridFeeder {
+        GridFeeder {
+            pid: id,
+            pane_grids: self.pane_grids.clone(),
+            grid: None,
+            vte_parser: vte::Parser::new(),
+            pending_output,
+            size: None,
+        }
+    }
+    fn active_terminal_cwd(&self) -> Option<PathBuf> {
+        if !self.inherit_cwd {
+            return None;
+        }
+        self.active_terminal.and_then(|id| self.terminal_cwd(id))
+    }
+    /// The working directory of the program running in a terminal, replays run no program
+    fn terminal_cwd(&self, id: RawFd) -> Option<PathBuf> {
+        if self.replays.contains_key(&id) {
+            return None;
+        }
+        self.bus.os_input.as_ref().unwrap().get_cwd(id)
+    }
+    /// Fills in how to run each terminal pane of the given tabs, so they can be
+    /// started again in the same working directory.
+    pub fn session_snapshot(&self, tabs: Vec<(TabSnapshot, Vec<RawFd>)>) -> SessionSnapshot {
+        let tabs = tabs
+            .into_iter()
+            .map(|(mut tab, terminal_ids)| {
+                let mut run_instructions = terminal_ids.iter().map(|id| self.run_instruction(*id));
+                tab.layout
+                    .fill_terminal_run_instructions(&mut run_instructions);
+                tab
+            })
+            .collect();
+        SessionSnapshot { tabs }
+    }
+    fn run_instruction(&self, id: RawFd) -> Option<Run> {
+        let command = match self.id_to_terminal_action.get(&id) {
+            Some(Some(TerminalAction::RunCommand(command))) => command.clone(),
+            // the files opened in panes are the scrollbacks being edited, which are removed once
+            // their pane closes, so these panes come back as shells
+            _ => RunCommand {
+                command: env::var("SHELL").ok()?.into(),
+                ..Default::default()
+            },
+        };
+        let cwd = self.terminal_cwd(id);
+        Some(Run::Command(RunCommand {
+            cwd: cwd.or(command.cwd),
+            ..command
+        }))
+    }
+    pub fn pipe_pane(&mut self, id: RawFd, target: &PipeTarget, strip_ansi: bool) {
+        let cwd = self.terminal_cwd(id);
+        let description = pipe_target_description(target);
+        let senders = self.bus.senders.clone();
+        let on_failure = {
+            let description = description.clone();
+            move |e: io::Error| {
+                error!(
+                    "Failed to pipe the output of pane {} to {}: {}",
+                    id, description, e
+                );
+                let _ = senders.send_to_screen(ScreenInstruction::ShowNotice(format!(
+                    "Stopped piping the output of a pane to {}: {}",
+                    description, e
+                )));
+            }
+        };
+        match PanePipe::new(target, strip_ansi, cwd, on_failure) {
+            // replaces the previous pipe of the pane, if any
+            Ok(pipe) => {
+                self.pane_pipes.lock().unwrap().insert(id, pipe);
+            }
+            Err(e) => {
+                error!(
+                    "Failed to pipe the output of pane {} to {}: {}",
+                    id, description, e
+                );
+                self.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::ShowNotice(format!(
+                        "Failed to pipe the output of the pane to {}: {}",
+                        description, e
+                    )))
+                    .unwrap();
+            }
+        }
+    }
+    pub fn start_recording(&mut self, id: RawFd, path: &Path, columns: u16, rows: u16) {
+        let senders = self.bus.senders.clone();
+        let on_failure = {
+            let path = path.to_path_buf();
+            move |e: io::Error| {
+                error!("Failed to record pane {} to {:?}: {}", id, path, e);
+                let _ = senders.send_to_screen(ScreenInstruction::ShowNotice(format!(
+                    "Stopped recording a pane to {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+        match PaneRecording::new(path, columns, rows, on_failure) {
+            // replaces the previous recording of the pane, if any
+            Ok(recording) => {
+                self.pane_recordings.lock().unwrap().insert(id, recording);
+            }
+            Err(e) => {
+                error!("Failed to record pane {} to {:?}: {}", id, path, e);
+                self.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::ShowNotice(format!(
+                        "Failed to record the pane to {}: {}",
+                        path.display(),
+                        e
+                    )))
+                    .unwrap();
+            }
+        }
+    }
+    /// Plays the output of a recording in a new read-only pane, `speed` times faster than it
+    /// was recorded
+    pub fn replay_recording(&mut self, path: &Path, speed: f64) {
+        let opened = File::open(path).and_then(|recording| {
+            let output = recorded_output(BufReader::new(recording.try_clone()?))?;
+            Ok((recording, output))
+        });
+        let (recording, output) = match opened {
+            Ok(opened) => opened,
+            Err(e) => {
+                error!("Failed to replay {:?}: {}", path, e);
+                self.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::ShowNotice(format!(
+                        "Failed to replay {}: {}",
+                        path.display(),
+                        e
+                    )))
+                    .unwrap();
+                return;
+            }
+        };
+        let id = recording.as_raw_fd();
+        let task_handle = replay_output(
+            output,
+            speed,
+            self.bus.senders.clone(),
+            self.grid_feeder(id, vec![]),
+        );
+        self.replays.insert(
+            id,
+            Replay {
+                _recording: recording,
+                task_handle,
+            },
+        );
+        self.bus
+            .senders
+            .send_to_screen(ScreenInstruction::NewReadOnlyPane(PaneId::Terminal(id)))
+            .unwrap();
+    }
+    pub fn open_link(&self, uri: &str) {
+        let opener = self.link_opener.as_deref().unwrap_or(DEFAULT_LINK_OPENER);
+        let mut opener_args = opener.split_whitespace();
+        let opener_command = match opener_args.next() {
+            Some(opener_command) => opener_command,
+            None => return,
+        };
+        let mut command = Command::new(opener_command);
+        command
+            .args(opener_args)
+            .arg(uri)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        // links to relative paths are relative to the pane they were printed in
+        if let Some(cwd) = self.active_terminal_cwd() {
+            command.current_dir(cwd);
+        }
+        match command.spawn() {
+            Ok(mut child) => {
+                // reap the opener once it's done, so that it doesn't linger as a zombie
+                thread::spawn(move || child.wait());
+            }
+            Err(e) => error!("Failed to open {} with {}: {}", uri, opener, e),
+        }
+    }
+    fn layout_run_instruction(&self, id: RawFd) -> Option<Run> {
+        // panes running the default shell are left to whoever uses the layout
+        match self.id_to_terminal_action.get(&id) {
+            Some(Some(TerminalAction::RunCommand(command))) => Some(Run::Command(command.clone())),
+            _ => None,
+        }
+    }
     pub fn close_pane(&mut self, id: PaneId) {
         match id {
             PaneId::Terminal(id) => {
+                self.id_to_terminal_action.remove(&id);
+                self.pane_pipes.lock().unwrap().remove(&id);
+                self.pane_recordings.lock().unwrap().remove(&id);
+                self.pane_grids.lock().unwrap().remove(&id);
+                if let Some(replay) = self.replays.remove(&id) {
+                    // the recording is closed once the replay is stopped
+                    task::block_on(replay.task_handle.cancel());
+                    return;
+                }
                 let child_pid = self.id_to_child_pid.remove(&id).unwrap();
                 let handle = self.task_handles.remove(&id).unwrap();
                 task::block_on(async {
@@ -326,9 +1002,18 @@ impl Pty {
 
 impl Drop for Pty {
     fn drop(&mut self) {
-        let child_ids: Vec<RawFd> = self.id_to_child_pid.keys().copied().collect();
-        for id in child_ids {
+        let ids: Vec<RawFd> = self
+            .id_to_child_pid
+            .keys()
+            .chain(self.replays.keys())
+            .copied()
+            .collect();
+        for id in ids {
             self.close_pane(PaneId::Terminal(id));
         }
     }
 }
+
+#[cfg(test)]
+#[path = "./unit/pty_tests.rs"]
+mod pty_tests;
@@ -4,25 +4,45 @@ use zellij_utils::zellij_tile::data::Event;
 
 use crate::{
     os_input_output::ServerOsApi, pty::PtyInstruction, screen::ScreenInstruction,
-    wasm_vm::PluginInstruction, ServerInstruction, SessionMetaData, SessionState,
+    wasm_vm::PluginInstruction, ServerInstruction, SessionMetaData,
 };
 use zellij_utils::{
     channels::SenderWithContext,
     input::{
-        actions::{Action, Direction},
+        actions::{Action, Direction, SearchDirection},
         command::TerminalAction,
         get_mode_info,
     },
-    ipc::{ClientToServerMsg, ExitReason, ServerToClientMsg},
+    ipc::{
+        ClientId, ClientToServerMsg, ClientType, ExitReason, IpcReceiverWithContext,
+        ServerToClientMsg, SessionInfo,
+    },
 };
 
 fn route_action(
     action: Action,
     session: &SessionMetaData,
-    _os_input: &dyn ServerOsApi,
+    os_input: &dyn ServerOsApi,
     to_server: &SenderWithContext<ServerInstruction>,
+    client_id: ClientId,
+    client_type: ClientType,
 ) -> bool {
     let mut should_break = false;
+    if client_type == ClientType::Reader
+        && !matches!(action, Action::Quit | Action::Detach | Action::NoOp)
+    {
+        // read-only clients can only watch the session, but they might be
+        // waiting for the server to unblock their input thread
+        if os_input
+            .send_to_client(client_id, ServerToClientMsg::UnblockInputThread)
+            .is_err()
+        {
+            to_server
+                .send(ServerInstruction::RemoveClient(client_id))
+                .unwrap();
+        }
+        return should_break;
+    }
     match action {
         Action::Write(val) => {
             session
@@ -34,6 +54,16 @@ fn route_action(
                 .send_to_screen(ScreenInstruction::WriteCharacter(val))
                 .unwrap();
         }
+        Action::Paste(val) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::ClearScroll)
+                .unwrap();
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::Paste(val))
+                .unwrap();
+        }
         Action::SwitchToMode(mode) => {
             let palette = session.palette;
             // TODO: use the palette from the client and remove it from the server os api
@@ -188,6 +218,13 @@ fn route_action(
                 .send_to_pty(PtyInstruction::NewTab(shell))
                 .unwrap();
         }
+        Action::NewTabFromTemplate(name) => {
+            let shell = session.default_shell.clone();
+            session
+                .senders
+                .send_to_pty(PtyInstruction::NewTabFromTemplate(name, shell))
+                .unwrap();
+        }
         Action::GoToNextTab => {
             session
                 .senders
@@ -225,11 +262,18 @@ fn route_action(
                 .unwrap();
         }
         Action::Quit => {
-            to_server.send(ServerInstruction::ClientExit).unwrap();
+            // a read-only client should never take the session down with it
+            let instruction = match client_type {
+                ClientType::Reader => ServerInstruction::DetachSession(client_id),
+                ClientType::Writer => ServerInstruction::ClientExit(client_id),
+            };
+            to_server.send(instruction).unwrap();
             should_break = true;
         }
         Action::Detach => {
-            to_server.send(ServerInstruction::DetachSession).unwrap();
+            to_server
+                .send(ServerInstruction::DetachSession(client_id))
+                .unwrap();
             should_break = true;
         }
         Action::LeftClick(point) => {
@@ -238,6 +282,18 @@ fn route_action(
                 .send_to_screen(ScreenInstruction::LeftClick(point))
                 .unwrap();
         }
+        Action::RightClick(point) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::RightClick(point))
+                .unwrap();
+        }
+        Action::MiddleClick(point) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::MiddleClick(point))
+                .unwrap();
+        }
         Action::MouseRelease(point) => {
             session
                 .senders
@@ -250,12 +306,109 @@ fn route_action(
                 .send_to_screen(ScreenInstruction::MouseHold(point))
                 .unwrap();
         }
+        Action::MouseMotion(point) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::MouseMotion(point))
+                .unwrap();
+        }
         Action::Copy => {
             session
                 .senders
                 .send_to_screen(ScreenInstruction::Copy)
                 .unwrap();
         }
+        Action::DumpLayout(path) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::DumpLayout(path))
+                .unwrap();
+        }
+        Action::OpenLink => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::OpenLink)
+                .unwrap();
+        }
+        Action::SearchInput(val) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::SearchInput(val))
+                .unwrap();
+        }
+        Action::SearchReset => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::SearchReset)
+                .unwrap();
+        }
+        Action::Search(direction) => {
+            let screen_instr = match direction {
+                SearchDirection::Up => ScreenInstruction::SearchUp,
+                SearchDirection::Down => ScreenInstruction::SearchDown,
+            };
+            session.senders.send_to_screen(screen_instr).unwrap();
+        }
+        Action::SearchToggleOption(option) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::SearchToggleOption(option))
+                .unwrap();
+        }
+        Action::EditScrollback(with_ansi_styles) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::EditScrollback(with_ansi_styles))
+                .unwrap();
+        }
+        Action::DumpScrollback(path, with_ansi_styles) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::DumpScrollback(path, with_ansi_styles))
+                .unwrap();
+        }
+        Action::CopyModeMove(motion) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::CopyModeMove(motion))
+                .unwrap();
+        }
+        Action::CopyModeSelect(kind) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::CopyModeSelect(kind))
+                .unwrap();
+        }
+        Action::PipePane(target, strip_ansi) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::PipePane(target, strip_ansi))
+                .unwrap();
+        }
+        Action::StopPipePane => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::StopPipePane)
+                .unwrap();
+        }
+        Action::StartRecording(path) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::StartRecording(path))
+                .unwrap();
+        }
+        Action::StopRecording => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::StopRecording)
+                .unwrap();
+        }
+        Action::ReplayRecording(path, speed) => {
+            session
+                .senders
+                .send_to_pty(PtyInstruction::ReplayRecording(path, speed as f64 / 100.0))
+                .unwrap();
+        }
         Action::NoOp => {}
     }
     should_break
@@ -263,50 +416,107 @@ fn route_action(
 
 pub(crate) fn route_thread_main(
     session_data: Arc<RwLock<Option<SessionMetaData>>>,
-    session_state: Arc<RwLock<SessionState>>,
     os_input: Box<dyn ServerOsApi>,
     to_server: SenderWithContext<ServerInstruction>,
+    mut receiver: IpcReceiverWithContext<ClientToServerMsg>,
+    client_id: ClientId,
 ) {
+    let mut client_type = ClientType::Writer;
     loop {
-        let (instruction, err_ctx) = os_input.recv_from_client();
+        let (instruction, err_ctx) = match receiver.recv() {
+            Ok(received) => received,
+            Err(e) => {
+                // the client went away without telling, eg. its terminal was closed
+                log::error!("Failed to receive from client {}: {}", client_id, e);
+                let _ = to_server.send(ServerInstruction::RemoveClient(client_id));
+                break;
+            }
+        };
         err_ctx.update_thread_ctx();
         let rlocked_sessions = session_data.read().unwrap();
 
         match instruction {
             ClientToServerMsg::Action(action) => {
                 if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
-                    if route_action(action, rlocked_sessions, &*os_input, &to_server) {
+                    if route_action(
+                        action,
+                        rlocked_sessions,
+                        &*os_input,
+                        &to_server,
+                        client_id,
+                        client_type,
+                    ) {
                         break;
                     }
                 }
             }
-            ClientToServerMsg::TerminalResize(new_size) => {
+            ClientToServerMsg::TerminalResize(new_size, character_cell_size) => {
+                if client_type == ClientType::Reader {
+                    continue;
+                }
                 rlocked_sessions
                     .as_ref()
                     .unwrap()
                     .senders
-                    .send_to_screen(ScreenInstruction::TerminalResize(new_size))
+                    .send_to_screen(ScreenInstruction::TerminalResize(
+                        new_size,
+                        character_cell_size,
+                    ))
                     .unwrap();
             }
-            ClientToServerMsg::NewClient(..) => {
-                if *session_state.read().unwrap() != SessionState::Uninitialized {
-                    os_input.send_to_temp_client(ServerToClientMsg::Exit(ExitReason::Error(
-                        "Cannot add new client".into(),
-                    )));
+            ClientToServerMsg::NewClient(attrs, opts, options, layout, snapshot) => {
+                if rlocked_sessions.is_some() {
+                    let _ = os_input.send_to_client(
+                        client_id,
+                        ServerToClientMsg::Exit(ExitReason::Error("Cannot add new client".into())),
+                    );
                 } else {
-                    os_input.add_client_sender();
-                    to_server.send(instruction.into()).unwrap();
+                    to_server
+                        .send(ServerInstruction::NewClient(
+                            attrs, opts, options, layout, snapshot, client_id,
+                        ))
+                        .unwrap();
                 }
             }
-            ClientToServerMsg::AttachClient(_, force, _) => {
-                if *session_state.read().unwrap() == SessionState::Attached && !force {
-                    os_input.send_to_temp_client(ServerToClientMsg::Exit(ExitReason::CannotAttach));
-                } else {
-                    os_input.add_client_sender();
-                    to_server.send(instruction.into()).unwrap();
+            ClientToServerMsg::AttachClient(attrs, attach_type, force, options) => {
+                client_type = attach_type;
+                to_server
+                    .send(ServerInstruction::AttachClient(
+                        attrs,
+                        client_type,
+                        force,
+                        options,
+                        client_id,
+                    ))
+                    .unwrap();
+            }
+            ClientToServerMsg::QuerySessionInfo => {
+                match rlocked_sessions.as_ref() {
+                    Some(session) => session
+                        .senders
+                        .send_to_screen(ScreenInstruction::QuerySessionInfo(client_id))
+                        .unwrap(),
+                    None => to_server
+                        .send(ServerInstruction::SessionInfo(
+                            client_id,
+                            SessionInfo::default(),
+                        ))
+                        .unwrap(),
                 }
+                // the server answers and closes this connection, there's nothing more to read
+                break;
+            }
+            ClientToServerMsg::KillSession => {
+                to_server.send(ServerInstruction::KillSession).unwrap();
+                break;
+            }
+            ClientToServerMsg::ClientExited => {
+                // the client might have gone away without quitting or detaching first
+                to_server
+                    .send(ServerInstruction::RemoveClient(client_id))
+                    .unwrap();
+                break;
             }
-            ClientToServerMsg::ClientExited => break,
         }
     }
 }
@@ -1,34 +1,99 @@
 //! Things related to [`Screen`]s.
 
 use std::collections::BTreeMap;
+use std::env;
 use std::os::unix::io::RawFd;
+use std::path::PathBuf;
 use std::str;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
-use zellij_utils::{input::layout::Layout, position::Position, zellij_tile};
+use zellij_utils::{
+    input::layout::{Layout, TabSnapshot},
+    position::Position,
+    zellij_tile,
+};
 
 use crate::{
-    panes::PaneId,
-    pty::{PtyInstruction, VteBytes},
+    panes::{PaneId, DEFAULT_SCROLL_BUFFER_SIZE, DEFAULT_WORD_SEPARATORS},
+    pty::PtyInstruction,
     tab::Tab,
     thread_bus::Bus,
     wasm_vm::PluginInstruction,
     ServerInstruction, SessionState,
 };
-use zellij_tile::data::{Event, ModeInfo, Palette, PluginCapabilities, TabInfo};
+use zellij_tile::data::{Event, InputMode, ModeInfo, Palette, PluginCapabilities, TabInfo};
 use zellij_utils::{
+    channels::RecvTimeoutError,
     errors::{ContextType, ScreenContext},
-    input::{get_mode_info, options::Options},
-    ipc::ClientAttributes,
-    pane_size::PositionAndSize,
+    input::{
+        actions::{CopyModeMotion, PipeTarget, SearchOption, SelectionKind},
+        get_mode_info,
+        options::Options,
+    },
+    ipc::{ClientAttributes, ClientId, SessionInfo},
+    pane_size::{PositionAndSize, SizeInPixels},
 };
 
+/// How many times per second at most the output of busy panes is rendered, unless configured
+const DEFAULT_MAX_FPS: u16 = 60;
+
+/// Renders requested along with the output of panes are coalesced, so that busy panes are drawn
+/// at most `max_fps` times per second instead of slowing everything else down
+struct RenderThrottle {
+    interval: Duration,
+    last_render: Option<Instant>,
+    /// When the renders requested since the last one are due
+    pending_render: Option<Instant>,
+}
+
+impl RenderThrottle {
+    fn new(max_fps: u16) -> Self {
+        RenderThrottle {
+            interval: Duration::from_secs(1) / u32::from(max_fps.max(1)),
+            last_render: None,
+            pending_render: None,
+        }
+    }
+    /// Whether to render right away when asked to at `now`, otherwise the render is deferred
+    /// until the interval since the last one is over
+    fn request_render(&mut self, now: Instant) -> bool {
+        match self.last_render {
+            Some(last_render) if now < last_render + self.interval => {
+                self.pending_render
+                    .get_or_insert(last_render + self.interval);
+                false
+            }
+            _ => {
+                self.rendered(now);
+                true
+            }
+        }
+    }
+    /// Whether to do the deferred render at `now`
+    fn deferred_render_due(&mut self, now: Instant) -> bool {
+        match self.pending_render {
+            Some(deadline) if now >= deadline => {
+                self.rendered(now);
+                true
+            }
+            _ => false,
+        }
+    }
+    fn rendered(&mut self, now: Instant) {
+        self.last_render = Some(now);
+        self.pending_render = None;
+    }
+}
+
 /// Instructions that can be sent to the [`Screen`].
 #[derive(Debug, Clone)]
 pub(crate) enum ScreenInstruction {
-    PtyBytes(RawFd, VteBytes),
     Render,
+    ForceRender,
     NewPane(PaneId),
+    /// A pane that takes no input, whose grid is fed by the pty thread
+    NewReadOnlyPane(PaneId),
     HorizontalSplit(PaneId),
     VerticalSplit(PaneId),
     WriteCharacter(Vec<u8>),
@@ -69,20 +134,44 @@ pub(crate) enum ScreenInstruction {
     CloseTab,
     GoToTab(u32),
     UpdateTabName(Vec<u8>),
-    TerminalResize(PositionAndSize),
+    TerminalResize(PositionAndSize, Option<SizeInPixels>),
     ChangeMode(ModeInfo),
     LeftClick(Position),
+    RightClick(Position),
+    MiddleClick(Position),
     MouseRelease(Position),
     MouseHold(Position),
+    MouseMotion(Position),
     Copy,
+    QuerySessionInfo(ClientId),
+    SerializeSession(PathBuf, bool),
+    DumpLayout(PathBuf),
+    Paste(Vec<u8>),
+    OpenLink,
+    SearchInput(Vec<u8>),
+    SearchReset,
+    SearchUp,
+    SearchDown,
+    SearchToggleOption(SearchOption),
+    EditScrollback(bool),
+    DumpScrollback(PathBuf, bool),
+    CopyModeMove(CopyModeMotion),
+    CopyModeSelect(SelectionKind),
+    PipePane(PipeTarget, bool),
+    StopPipePane,
+    /// Shows a message to the user in the status bar, such as why a pipe stopped
+    ShowNotice(String),
+    StartRecording(PathBuf),
+    StopRecording,
 }
 
 impl From<&ScreenInstruction> for ScreenContext {
     fn from(screen_instruction: &ScreenInstruction) -> Self {
         match *screen_instruction {
-            ScreenInstruction::PtyBytes(..) => ScreenContext::HandlePtyBytes,
             ScreenInstruction::Render => ScreenContext::Render,
+            ScreenInstruction::ForceRender => ScreenContext::ForceRender,
             ScreenInstruction::NewPane(_) => ScreenContext::NewPane,
+            ScreenInstruction::NewReadOnlyPane(_) => ScreenContext::NewReadOnlyPane,
             ScreenInstruction::HorizontalSplit(_) => ScreenContext::HorizontalSplit,
             ScreenInstruction::VerticalSplit(_) => ScreenContext::VerticalSplit,
             ScreenInstruction::WriteCharacter(_) => ScreenContext::WriteCharacter,
@@ -124,15 +213,36 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::CloseTab => ScreenContext::CloseTab,
             ScreenInstruction::GoToTab(_) => ScreenContext::GoToTab,
             ScreenInstruction::UpdateTabName(_) => ScreenContext::UpdateTabName,
-            ScreenInstruction::TerminalResize(_) => ScreenContext::TerminalResize,
+            ScreenInstruction::TerminalResize(..) => ScreenContext::TerminalResize,
             ScreenInstruction::ChangeMode(_) => ScreenContext::ChangeMode,
             ScreenInstruction::ToggleActiveSyncTab => ScreenContext::ToggleActiveSyncTab,
             ScreenInstruction::ScrollUpAt(_) => ScreenContext::ScrollUpAt,
             ScreenInstruction::ScrollDownAt(_) => ScreenContext::ScrollDownAt,
             ScreenInstruction::LeftClick(_) => ScreenContext::LeftClick,
+            ScreenInstruction::RightClick(_) => ScreenContext::RightClick,
+            ScreenInstruction::MiddleClick(_) => ScreenContext::MiddleClick,
             ScreenInstruction::MouseRelease(_) => ScreenContext::MouseRelease,
             ScreenInstruction::MouseHold(_) => ScreenContext::MouseHold,
+            ScreenInstruction::MouseMotion(_) => ScreenContext::MouseMotion,
             ScreenInstruction::Copy => ScreenContext::Copy,
+            ScreenInstruction::QuerySessionInfo(_) => ScreenContext::QuerySessionInfo,
+            ScreenInstruction::SerializeSession(..) => ScreenContext::SerializeSession,
+            ScreenInstruction::DumpLayout(_) => ScreenContext::DumpLayout,
+            ScreenInstruction::Paste(_) => ScreenContext::Paste,
+            ScreenInstruction::OpenLink => ScreenContext::OpenLink,
+            ScreenInstruction::SearchInput(_) => ScreenContext::SearchInput,
+            ScreenInstruction::SearchReset => ScreenContext::SearchReset,
+            ScreenInstruction::SearchUp | ScreenInstruction::SearchDown => ScreenContext::Search,
+            ScreenInstruction::SearchToggleOption(_) => ScreenContext::SearchToggleOption,
+            ScreenInstruction::EditScrollback(_) => ScreenContext::EditScrollback,
+            ScreenInstruction::DumpScrollback(..) => ScreenContext::DumpScrollback,
+            ScreenInstruction::CopyModeMove(_) => ScreenContext::CopyModeMove,
+            ScreenInstruction::CopyModeSelect(_) => ScreenContext::CopyModeSelect,
+            ScreenInstruction::PipePane(..) => ScreenContext::PipePane,
+            ScreenInstruction::StopPipePane => ScreenContext::StopPipePane,
+            ScreenInstruction::ShowNotice(_) => ScreenContext::ShowNotice,
+            ScreenInstruction::StartRecording(_) => ScreenContext::StartRecording,
+            ScreenInstruction::StopRecording => ScreenContext::StopRecording,
         }
     }
 }
@@ -153,6 +263,15 @@ pub(crate) struct Screen {
     mode_info: ModeInfo,
     colors: Palette,
     session_state: Arc<RwLock<SessionState>>,
+    /// The focused terminal of the active [`Tab`], as last reported to the pty thread.
+    active_terminal: Option<RawFd>,
+    /// The size of a character cell of the clients' terminal, used to place images, as told
+    /// by the last client to attach or resize.
+    character_cell_size: Option<SizeInPixels>,
+    /// The characters that end a word selected by double clicking, besides whitespace.
+    word_separators: String,
+    /// The scrollback size of panes whose layout does not set their own.
+    scroll_buffer_size: usize,
 }
 
 impl Screen {
@@ -163,6 +282,8 @@ impl Screen {
         max_panes: Option<usize>,
         mode_info: ModeInfo,
         session_state: Arc<RwLock<SessionState>>,
+        word_separators: String,
+        scroll_buffer_size: usize,
     ) -> Self {
         Screen {
             bus,
@@ -173,6 +294,10 @@ impl Screen {
             tabs: BTreeMap::new(),
             mode_info,
             session_state,
+            active_terminal: None,
+            character_cell_size: client_attributes.character_cell_size,
+            word_separators,
+            scroll_buffer_size,
         }
     }
 
@@ -193,6 +318,9 @@ impl Screen {
             self.mode_info.clone(),
             self.colors,
             self.session_state.clone(),
+            self.character_cell_size,
+            self.word_separators.clone(),
+            self.scroll_buffer_size,
         );
         self.active_tab_index = Some(tab_index);
         self.tabs.insert(tab_index, tab);
@@ -277,7 +405,7 @@ impl Screen {
             .unwrap();
         if self.tabs.is_empty() {
             self.active_tab_index = None;
-            if *self.session_state.read().unwrap() == SessionState::Attached {
+            if self.session_state.read().unwrap().has_clients() {
                 self.bus
                     .senders
                     .send_to_server(ServerInstruction::Render(None))
@@ -293,6 +421,18 @@ impl Screen {
         }
     }
 
+    /// The clients' terminal might have changed its font, or a client with another terminal
+    /// might have attached
+    pub fn update_character_cell_size(&mut self, character_cell_size: Option<SizeInPixels>) {
+        if self.character_cell_size == character_cell_size {
+            return;
+        }
+        self.character_cell_size = character_cell_size;
+        for tab in self.tabs.values_mut() {
+            tab.update_character_cell_size(character_cell_size);
+        }
+    }
+
     pub fn resize_to_screen(&mut self, new_screen_size: PositionAndSize) {
         self.position_and_size = new_screen_size;
         for (_, tab) in self.tabs.iter_mut() {
@@ -304,7 +444,7 @@ impl Screen {
 
     /// Renders this [`Screen`], which amounts to rendering its active [`Tab`].
     pub fn render(&mut self) {
-        if *self.session_state.read().unwrap() != SessionState::Attached {
+        if !self.session_state.read().unwrap().has_clients() {
             return;
         }
         if let Some(active_tab) = self.get_active_tab_mut() {
@@ -314,6 +454,12 @@ impl Screen {
                 self.close_tab();
             }
         };
+        if self.mode_info.mode == InputMode::Search
+            && self.get_active_tab().and_then(|tab| tab.search_info()) != self.mode_info.search
+        {
+            // the matches were found again in the new output
+            self.update_search_info();
+        }
     }
 
     /// Returns a mutable reference to this [`Screen`]'s tabs.
@@ -350,7 +496,7 @@ impl Screen {
         let mut tab = Tab::new(
             tab_index,
             position,
-            String::new(),
+            layout.name.clone().unwrap_or_default(),
             &self.position_and_size,
             self.bus.os_input.as_ref().unwrap().clone(),
             self.bus.senders.clone(),
@@ -359,6 +505,9 @@ impl Screen {
             self.mode_info.clone(),
             self.colors,
             self.session_state.clone(),
+            self.character_cell_size,
+            self.word_separators.clone(),
+            self.scroll_buffer_size,
         );
         tab.apply_layout(layout, new_pids, tab_index);
         self.active_tab_index = Some(tab_index);
@@ -383,6 +532,117 @@ impl Screen {
             .unwrap();
     }
 
+    /// Returns the parts of the session's metadata that this [`Screen`] knows about.
+    pub fn session_info(&self) -> SessionInfo {
+        let mut tabs: Vec<&Tab> = self.tabs.values().collect();
+        tabs.sort_by_key(|tab| tab.position);
+        let panes = tabs
+            .iter()
+            .flat_map(|tab| tab.get_pane_ids())
+            .filter(|pane_id| matches!(pane_id, PaneId::Terminal(_)))
+            .count();
+        SessionInfo {
+            tabs: tabs.iter().map(|tab| tab.name.clone()).collect(),
+            panes,
+            ..Default::default()
+        }
+    }
+
+    /// Lets the pty thread know when the focused terminal changes, so that new terminals
+    /// can be started in its working directory.
+    pub fn report_active_terminal(&mut self) {
+        let active_terminal = self
+            .get_active_tab()
+            .and_then(|tab| tab.get_active_terminal_id());
+        if active_terminal != self.active_terminal {
+            self.active_terminal = active_terminal;
+            // the pty thread might be gone already when the session is exiting
+            let _ = self
+                .bus
+                .senders
+                .send_to_pty(PtyInstruction::UpdateActiveTerminal(active_terminal));
+        }
+    }
+
+    /// The id of the focused terminal pane along with its scrollback
+    fn active_pane_scrollback(&self, with_ansi_styles: bool) -> Option<(RawFd, String)> {
+        let active_tab = self.get_active_tab().unwrap();
+        active_tab.get_active_terminal_id().map(|pid| {
+            let scrollback =
+                active_tab.dump_pane_scrollback(PaneId::Terminal(pid), with_ansi_styles);
+            (pid, scrollback)
+        })
+    }
+    /// Saves the scrollback of the focused terminal pane to `path`, the file is written by the
+    /// pty thread
+    pub fn dump_scrollback(&self, path: PathBuf, with_ansi_styles: bool) {
+        if let Some((_, scrollback)) = self.active_pane_scrollback(with_ansi_styles) {
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::DumpScrollback(path, scrollback))
+                .unwrap();
+        }
+    }
+    /// Starts recording the focused terminal pane to an asciicast file at `path`, which is
+    /// written by the pty thread
+    pub fn start_recording(&self, path: PathBuf) {
+        let active_tab = self.get_active_tab().unwrap();
+        if let (Some(pid), Some(pane)) = (
+            active_tab.get_active_terminal_id(),
+            active_tab.get_active_pane(),
+        ) {
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::StartRecording(
+                    pid,
+                    path,
+                    pane.columns() as u16,
+                    pane.rows() as u16,
+                ))
+                .unwrap();
+        }
+    }
+    /// Opens the scrollback of the focused terminal pane in the default editor, in a new pane
+    pub fn edit_scrollback(&self, with_ansi_styles: bool) {
+        if env::var("EDITOR").is_err() && env::var("VISUAL").is_err() {
+            log::error!("Can't edit the scrollback if an editor is not defined, set the EDITOR or VISUAL environment variables");
+            return;
+        }
+        if let Some((pid, scrollback)) = self.active_pane_scrollback(with_ansi_styles) {
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::EditScrollback(pid, scrollback))
+                .unwrap();
+        }
+    }
+
+    /// Describes each [`Tab`] as a [`TabSnapshot`], along with the ids of the terminal
+    /// panes of its layout. Running the panes is up to the pty thread to fill in.
+    pub fn session_snapshot(&self, with_scrollback: bool) -> Vec<(TabSnapshot, Vec<RawFd>)> {
+        let mut tabs: Vec<&Tab> = self.tabs.values().collect();
+        tabs.sort_by_key(|tab| tab.position);
+        tabs.iter()
+            .map(|tab| {
+                let (layout, terminal_ids) = tab.dump_layout();
+                let scrollback = if with_scrollback {
+                    terminal_ids
+                        .iter()
+                        .map(|pid| tab.dump_pane_screen(PaneId::Terminal(*pid)))
+                        .collect()
+                } else {
+                    vec![]
+                };
+                let tab_snapshot = TabSnapshot {
+                    name: tab.name.clone(),
+                    active: self.active_tab_index == Some(tab.index),
+                    layout,
+                    scrollback,
+                };
+                (tab_snapshot, terminal_ids)
+            })
+            .collect()
+    }
+
     pub fn update_active_tab_name(&mut self, buf: Vec<u8>) {
         let s = str::from_utf8(&buf).unwrap();
         let active_tab = self.get_active_tab_mut().unwrap();
@@ -401,11 +661,53 @@ impl Screen {
         self.update_tabs();
     }
     pub fn change_mode(&mut self, mode_info: ModeInfo) {
+        if mode_info.mode != InputMode::Search {
+            for tab in self.tabs.values_mut() {
+                tab.clear_search();
+            }
+        }
+        let copy_mode_changed =
+            (mode_info.mode == InputMode::Copy) != (self.mode_info.mode == InputMode::Copy);
+        if copy_mode_changed {
+            for tab in self.tabs.values_mut() {
+                tab.exit_copy_mode();
+            }
+            if mode_info.mode == InputMode::Copy {
+                if let Some(tab) = self.get_active_tab_mut() {
+                    tab.start_copy_mode();
+                }
+            }
+        }
         self.colors = mode_info.palette;
         self.mode_info = mode_info;
         for tab in self.tabs.values_mut() {
             tab.mode_info = self.mode_info.clone();
         }
+        if copy_mode_changed && !self.tabs.is_empty() {
+            self.render();
+        }
+    }
+    /// Shows `notice` in the status bar until the mode changes
+    pub fn show_notice(&mut self, notice: String) {
+        self.mode_info.notice = Some(notice);
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(
+                None,
+                Event::ModeUpdate(self.mode_info.clone()),
+            ))
+            .unwrap();
+    }
+    /// Lets the plugins know about the state of the search in the focused pane
+    pub fn update_search_info(&mut self) {
+        self.mode_info.search = self.get_active_tab().and_then(|tab| tab.search_info());
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(
+                None,
+                Event::ModeUpdate(self.mode_info.clone()),
+            ))
+            .unwrap();
     }
     pub fn move_focus_left_or_previous_tab(&mut self) {
         if !self.get_active_tab_mut().unwrap().move_focus_left() {
@@ -443,33 +745,44 @@ pub(crate) fn screen_thread_main(
             },
         ),
         session_state,
+        config_options
+            .word_separators
+            .clone()
+            .unwrap_or_else(|| DEFAULT_WORD_SEPARATORS.to_string()),
+        config_options
+            .scroll_buffer_size
+            .unwrap_or(DEFAULT_SCROLL_BUFFER_SIZE),
     );
+    let mut render_throttle =
+        RenderThrottle::new(config_options.max_fps.unwrap_or(DEFAULT_MAX_FPS));
     loop {
-        let (event, mut err_ctx) = screen
-            .bus
-            .recv()
-            .expect("failed to receive event on channel");
+        if render_throttle.deferred_render_due(Instant::now()) {
+            screen.render();
+        }
+        let (event, mut err_ctx) = match render_throttle.pending_render {
+            Some(deadline) => match screen.bus.recv_deadline(deadline) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => {
+                    panic!("failed to receive event on channel")
+                }
+            },
+            None => screen
+                .bus
+                .recv()
+                .expect("failed to receive event on channel"),
+        };
         err_ctx.add_call(ContextType::Screen((&event).into()));
         match event {
-            ScreenInstruction::PtyBytes(pid, vte_bytes) => {
-                let active_tab = screen.get_active_tab_mut().unwrap();
-                if active_tab.has_terminal_pid(pid) {
-                    // it's most likely that this event is directed at the active tab
-                    // look there first
-                    active_tab.handle_pty_bytes(pid, vte_bytes);
-                } else {
-                    // if this event wasn't directed at the active tab, start looking
-                    // in other tabs
-                    let all_tabs = screen.get_tabs_mut();
-                    for tab in all_tabs.values_mut() {
-                        if tab.has_terminal_pid(pid) {
-                            tab.handle_pty_bytes(pid, vte_bytes);
-                            break;
-                        }
-                    }
+            ScreenInstruction::Render => {
+                if render_throttle.request_render(Instant::now()) {
+                    screen.render();
                 }
             }
-            ScreenInstruction::Render => {
+            ScreenInstruction::ForceRender => {
+                if let Some(active_tab) = screen.get_active_tab_mut() {
+                    active_tab.set_force_render();
+                }
                 screen.render();
             }
             ScreenInstruction::NewPane(pid) => {
@@ -480,6 +793,9 @@ pub(crate) fn screen_thread_main(
                     .send_to_server(ServerInstruction::UnblockInputThread)
                     .unwrap();
             }
+            ScreenInstruction::NewReadOnlyPane(pid) => {
+                screen.get_active_tab_mut().unwrap().new_read_only_pane(pid);
+            }
             ScreenInstruction::HorizontalSplit(pid) => {
                 screen.get_active_tab_mut().unwrap().horizontal_split(pid);
                 screen
@@ -503,6 +819,83 @@ pub(crate) fn screen_thread_main(
                     false => active_tab.write_to_active_terminal(bytes),
                 }
             }
+            ScreenInstruction::Paste(bytes) => {
+                screen.get_active_tab_mut().unwrap().paste(bytes);
+            }
+            ScreenInstruction::OpenLink => {
+                if let Some(uri) = screen.get_active_tab().unwrap().link_to_open() {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_pty(PtyInstruction::OpenLink(uri))
+                        .unwrap();
+                }
+            }
+            ScreenInstruction::SearchInput(bytes) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .update_search_term(&bytes);
+                screen.update_search_info();
+            }
+            ScreenInstruction::SearchReset => {
+                screen.get_active_tab_mut().unwrap().reset_search_term();
+                screen.update_search_info();
+            }
+            ScreenInstruction::SearchUp => {
+                screen.get_active_tab_mut().unwrap().search_up();
+                screen.update_search_info();
+            }
+            ScreenInstruction::SearchDown => {
+                screen.get_active_tab_mut().unwrap().search_down();
+                screen.update_search_info();
+            }
+            ScreenInstruction::SearchToggleOption(option) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .toggle_search_option(option);
+                screen.update_search_info();
+            }
+            ScreenInstruction::CopyModeMove(motion) => {
+                screen.get_active_tab_mut().unwrap().copy_mode_move(motion);
+            }
+            ScreenInstruction::CopyModeSelect(kind) => {
+                screen.get_active_tab_mut().unwrap().copy_mode_select(kind);
+            }
+            ScreenInstruction::PipePane(target, strip_ansi) => {
+                if let Some(id) = screen.get_active_tab().unwrap().get_active_terminal_id() {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_pty(PtyInstruction::PipePane(id, target, strip_ansi))
+                        .unwrap();
+                }
+            }
+            ScreenInstruction::StopPipePane => {
+                if let Some(id) = screen.get_active_tab().unwrap().get_active_terminal_id() {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_pty(PtyInstruction::StopPipePane(id))
+                        .unwrap();
+                }
+            }
+            ScreenInstruction::ShowNotice(notice) => {
+                screen.show_notice(notice);
+            }
+            ScreenInstruction::StartRecording(path) => {
+                screen.start_recording(path);
+            }
+            ScreenInstruction::StopRecording => {
+                if let Some(id) = screen.get_active_tab().unwrap().get_active_terminal_id() {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_pty(PtyInstruction::StopRecording(id))
+                        .unwrap();
+                }
+            }
             ScreenInstruction::ResizeLeft => {
                 screen.get_active_tab_mut().unwrap().resize_left();
             }
@@ -714,7 +1107,8 @@ pub(crate) fn screen_thread_main(
             ScreenInstruction::UpdateTabName(c) => {
                 screen.update_active_tab_name(c);
             }
-            ScreenInstruction::TerminalResize(new_size) => {
+            ScreenInstruction::TerminalResize(new_size, character_cell_size) => {
+                screen.update_character_cell_size(character_cell_size);
                 screen.resize_to_screen(new_size);
             }
             ScreenInstruction::ChangeMode(mode_info) => {
@@ -733,6 +1127,18 @@ pub(crate) fn screen_thread_main(
                     .unwrap()
                     .handle_left_click(&point);
             }
+            ScreenInstruction::RightClick(point) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .handle_right_click(&point);
+            }
+            ScreenInstruction::MiddleClick(point) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .handle_middle_click(&point);
+            }
             ScreenInstruction::MouseRelease(point) => {
                 screen
                     .get_active_tab_mut()
@@ -745,13 +1151,56 @@ pub(crate) fn screen_thread_main(
                     .unwrap()
                     .handle_mouse_hold(&point);
             }
+            ScreenInstruction::MouseMotion(point) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .handle_mouse_motion(&point);
+            }
             ScreenInstruction::Copy => {
                 screen.get_active_tab().unwrap().copy_selection();
             }
+            ScreenInstruction::QuerySessionInfo(client_id) => {
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::SessionInfo(
+                        client_id,
+                        screen.session_info(),
+                    ))
+                    .unwrap();
+            }
+            ScreenInstruction::SerializeSession(path, with_scrollback) => {
+                if !screen.tabs.is_empty() {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_pty(PtyInstruction::SerializeSession(
+                            path,
+                            screen.session_snapshot(with_scrollback),
+                        ))
+                        .unwrap();
+                }
+            }
+            ScreenInstruction::DumpLayout(path) => {
+                let (layout, terminal_ids) = screen.get_active_tab().unwrap().dump_layout();
+                screen
+                    .bus
+                    .senders
+                    .send_to_pty(PtyInstruction::DumpLayout(path, layout, terminal_ids))
+                    .unwrap();
+            }
+            ScreenInstruction::EditScrollback(with_ansi_styles) => {
+                screen.edit_scrollback(with_ansi_styles);
+            }
+            ScreenInstruction::DumpScrollback(path, with_ansi_styles) => {
+                screen.dump_scrollback(path, with_ansi_styles);
+            }
             ScreenInstruction::Exit => {
                 break;
             }
         }
+        screen.report_active_terminal();
     }
 }
 
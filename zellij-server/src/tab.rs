@@ -9,28 +9,34 @@ use crate::ui::pane_resizer::PaneResizer;
 use crate::ui::pane_resizer_beta::PaneResizer;
 use crate::{
     os_input_output::ServerOsApi,
-    panes::{PaneId, PluginPane, TerminalPane},
-    pty::{PtyInstruction, VteBytes},
+    panes::{ImagePlacement, MouseEvent, PaneId, PluginPane, TerminalPane},
+    pty::PtyInstruction,
     thread_bus::ThreadSenders,
-    ui::boundaries::Boundaries,
+    ui::{
+        boundaries::Boundaries,
+        frame::{Frame, FrameChunk},
+    },
     wasm_vm::PluginInstruction,
     ServerInstruction, SessionState,
 };
 use serde::{Deserialize, Serialize};
 use std::os::unix::io::RawFd;
+use std::path::PathBuf;
 use std::sync::{mpsc::channel, Arc, RwLock};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{
     cmp::Reverse,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
 };
-use zellij_tile::data::{Event, ModeInfo, Palette};
+use zellij_tile::data::{Event, ModeInfo, Palette, SearchInfo};
 use zellij_utils::{
     input::{
-        layout::{Layout, Run},
+        actions::{CopyModeMotion, SearchOption, SelectionKind},
+        layout::{Direction, Layout, Run, SplitSize},
+        mouse::MouseButton,
         parse_keys,
     },
-    pane_size::PositionAndSize,
+    pane_size::{PositionAndSize, SizeInPixels},
     shared::adjust_to_size,
 };
 
@@ -41,7 +47,13 @@ const CURSOR_HEIGHT_WIDTH_RATIO: usize = 4; // this is not accurate and kind of
 const MIN_TERMINAL_HEIGHT: usize = 3;
 const MIN_TERMINAL_WIDTH: usize = 4;
 
+/// Clicks at the same position within this interval select a word (two clicks) or a line (three)
+const MULTIPLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+const BRACKETED_PASTE_START: &[u8] = b"\x1b[200~";
+const BRACKETED_PASTE_END: &[u8] = b"\x1b[201~";
+
 type BorderAndPaneIds = (usize, Vec<PaneId>);
+type PanePosition = (PaneId, PositionAndSize);
 
 fn split_vertically_with_gap(rect: &PositionAndSize) -> (PositionAndSize, PositionAndSize) {
     let width_of_each_half = (rect.cols - 1) / 2;
@@ -71,12 +83,49 @@ fn split_horizontally_with_gap(rect: &PositionAndSize) -> (PositionAndSize, Posi
     (first_rect, second_rect)
 }
 
+/// Groups panes whose spans (as returned by `span`) overlap, sorted by where they start.
+fn group_panes(
+    panes: &[PanePosition],
+    span: impl Fn(&PositionAndSize) -> (usize, usize),
+) -> Vec<Vec<PanePosition>> {
+    let mut panes = panes.to_vec();
+    panes.sort_by_key(|(_, p)| span(p).0);
+    let mut groups: Vec<Vec<PanePosition>> = vec![];
+    let mut group_end = 0;
+    for pane in panes {
+        let (start, len) = span(&pane.1);
+        match groups.last_mut() {
+            Some(group) if start < group_end => group.push(pane),
+            _ => groups.push(vec![pane]),
+        }
+        group_end = group_end.max(start + len);
+    }
+    groups
+}
+
+fn strip_bracketed_paste_end(bytes: &[u8]) -> Vec<u8> {
+    let mut stripped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(BRACKETED_PASTE_END) {
+            i += BRACKETED_PASTE_END.len();
+        } else {
+            stripped.push(bytes[i]);
+            i += 1;
+        }
+    }
+    stripped
+}
+
 pub(crate) struct Tab {
     pub index: usize,
     pub position: usize,
     pub name: String,
     panes: BTreeMap<PaneId, Box<dyn Pane>>,
     panes_to_hide: HashSet<PaneId>,
+    /// The terminal panes that take no input, which have no pty to be resized
+    read_only_terminals: HashSet<RawFd>,
+    plugin_paths: HashMap<u32, PathBuf>,
     active_terminal: Option<PaneId>,
     max_panes: Option<usize>,
     full_screen_ws: PositionAndSize,
@@ -88,6 +137,23 @@ pub(crate) struct Tab {
     session_state: Arc<RwLock<SessionState>>,
     pub mode_info: ModeInfo,
     pub colors: Palette,
+    mouse_reporting_pane: Option<PaneId>,
+    /// The button of the last press, until it is released
+    held_mouse_button: Option<MouseButton>,
+    last_mouse_hold_position: Option<Position>,
+    last_click_position: Option<Position>,
+    /// When the last click happened, and how many clicks in a row it ended
+    last_click_time: Option<Instant>,
+    click_count: usize,
+    character_cell_size: Option<SizeInPixels>,
+    word_separators: String,
+    /// The scrollback size of new panes, unless their layout sets another one
+    scroll_buffer_size: usize,
+    /// What the clients display, to only send them what changed
+    frame: Frame,
+    /// The images the clients display, drawn over the frame
+    rendered_images: Vec<(PaneId, ImagePlacement)>,
+    last_cursor_output: Option<String>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -110,7 +176,6 @@ pub trait Pane {
     fn reset_size_and_position_override(&mut self);
     fn change_pos_and_size(&mut self, position_and_size: &PositionAndSize);
     fn override_size_and_position(&mut self, x: usize, y: usize, size: &PositionAndSize);
-    fn handle_pty_bytes(&mut self, bytes: VteBytes);
     fn cursor_coordinates(&self) -> Option<(usize, usize)>;
     fn adjust_input_to_terminal(&self, input_bytes: Vec<u8>) -> Vec<u8>;
     fn position_and_size(&self) -> PositionAndSize;
@@ -123,6 +188,11 @@ pub trait Pane {
     fn set_fixed_height(&mut self, fixed_height: usize);
     fn set_fixed_width(&mut self, fixed_width: usize);
     fn render(&mut self) -> Option<String>;
+    /// The characters that changed since the last render, for panes whose output can be
+    /// composited with the others instead of being drawn as is
+    fn render_chunks(&mut self) -> Option<Vec<FrameChunk>> {
+        None
+    }
     fn pid(&self) -> PaneId;
     fn reduce_height_down(&mut self, count: usize);
     fn increase_height_down(&mut self, count: usize);
@@ -151,12 +221,60 @@ pub trait Pane {
         }
     }
     fn start_selection(&mut self, _start: &Position) {}
+    fn select_word(&mut self, _position: &Position, _word_separators: &str) {}
+    fn select_line(&mut self, _position: &Position) {}
     fn update_selection(&mut self, _position: &Position) {}
     fn end_selection(&mut self, _end: Option<&Position>) {}
     fn reset_selection(&mut self) {}
     fn get_selected_text(&self) -> Option<String> {
         None
     }
+    fn dump_screen(&self) -> String {
+        String::new()
+    }
+    fn dump_scrollback(&self, _with_ansi_styles: bool) -> String {
+        String::new()
+    }
+    fn mouse_reporting(&self) -> bool {
+        false
+    }
+    fn bracketed_paste(&self) -> bool {
+        false
+    }
+    /// Whether the pane takes no input, like the panes replaying a recording
+    fn read_only(&self) -> bool {
+        false
+    }
+    fn set_read_only(&mut self, _read_only: bool) {}
+    fn image_placements(&self) -> Vec<ImagePlacement> {
+        vec![]
+    }
+    fn render_image(&self, _placement: &ImagePlacement) -> Option<String> {
+        None
+    }
+    fn set_character_cell_size(&mut self, _character_cell_size: Option<SizeInPixels>) {}
+    fn link_at(&self, _position: &Position) -> Option<String> {
+        None
+    }
+    fn link_under_cursor(&self) -> Option<String> {
+        None
+    }
+    fn update_search_term(&mut self, _bytes: &[u8]) {}
+    fn reset_search_term(&mut self) {}
+    fn search_up(&mut self) {}
+    fn search_down(&mut self) {}
+    fn toggle_search_option(&mut self, _option: SearchOption) {}
+    fn clear_search(&mut self) {}
+    fn start_copy_mode(&mut self) {}
+    fn exit_copy_mode(&mut self) {}
+    fn copy_mode_move(&mut self, _motion: CopyModeMotion) {}
+    fn copy_mode_select(&mut self, _kind: SelectionKind) {}
+    fn search_info(&self) -> Option<SearchInfo> {
+        None
+    }
+    fn mouse_event_report(&self, _event: MouseEvent, _position: &Position) -> Option<Vec<u8>> {
+        None
+    }
 
     fn right_boundary_x_coords(&self) -> usize {
         self.x() + self.columns()
@@ -231,11 +349,6 @@ pub trait Pane {
     fn invisible_borders(&self) -> bool {
         false
     }
-    fn drain_messages_to_pty(&mut self) -> Vec<Vec<u8>> {
-        // TODO: this is only relevant to terminal panes
-        // we should probably refactor away from this trait at some point
-        vec![]
-    }
     fn render_full_viewport(&mut self) {}
     fn relative_position(&self, position: &Position) -> Position {
         match self.position_and_size_override() {
@@ -260,14 +373,26 @@ impl Tab {
         mode_info: ModeInfo,
         colors: Palette,
         session_state: Arc<RwLock<SessionState>>,
+        character_cell_size: Option<SizeInPixels>,
+        word_separators: String,
+        scroll_buffer_size: usize,
     ) -> Self {
         let panes = if let Some(PaneId::Terminal(pid)) = pane_id {
-            let new_terminal = TerminalPane::new(pid, *full_screen_ws, colors);
+            let new_terminal = TerminalPane::new(
+                pid,
+                *full_screen_ws,
+                colors,
+                character_cell_size,
+                scroll_buffer_size,
+            );
             os_api.set_terminal_size_using_fd(
                 new_terminal.pid,
                 new_terminal.columns() as u16,
                 new_terminal.rows() as u16,
             );
+            senders
+                .send_to_pty(PtyInstruction::ShareGrid(pid, new_terminal.shared_grid()))
+                .unwrap();
             let mut panes: BTreeMap<PaneId, Box<dyn Pane>> = BTreeMap::new();
             panes.insert(PaneId::Terminal(pid), Box::new(new_terminal));
             panes
@@ -288,6 +413,8 @@ impl Tab {
             name,
             max_panes,
             panes_to_hide: HashSet::new(),
+            read_only_terminals: HashSet::new(),
+            plugin_paths: HashMap::new(),
             active_terminal: pane_id,
             full_screen_ws: *full_screen_ws,
             fullscreen_is_active: false,
@@ -298,6 +425,18 @@ impl Tab {
             mode_info,
             colors,
             session_state,
+            mouse_reporting_pane: None,
+            held_mouse_button: None,
+            last_mouse_hold_position: None,
+            last_click_position: None,
+            last_click_time: None,
+            click_count: 0,
+            character_cell_size,
+            word_separators,
+            scroll_buffer_size,
+            frame: Frame::new(full_screen_ws.cols, full_screen_ws.rows),
+            rendered_images: vec![],
+            last_cursor_output: None,
         }
     }
 
@@ -313,6 +452,7 @@ impl Tab {
         self.panes_to_hide.clear();
         let positions_in_layout = layout.position_panes_in_space(&free_space);
         let mut positions_and_size = positions_in_layout.iter();
+        let mut pty_sizes = vec![];
         for (pane_kind, terminal_pane) in self.panes.iter_mut() {
             // for now the layout only supports terminal panes
             if let PaneId::Terminal(pid) = pane_kind {
@@ -320,11 +460,11 @@ impl Tab {
                     Some((_, position_and_size)) => {
                         terminal_pane.reset_size_and_position_override();
                         terminal_pane.change_pos_and_size(position_and_size);
-                        self.os_api.set_terminal_size_using_fd(
+                        pty_sizes.push((
                             *pid,
                             position_and_size.cols as u16,
                             position_and_size.rows as u16,
-                        );
+                        ));
                     }
                     None => {
                         // we filled the entire layout, no room for this pane
@@ -334,7 +474,11 @@ impl Tab {
                 }
             }
         }
+        for (pid, columns, rows) in pty_sizes {
+            self.set_pty_size(pid, columns, rows);
+        }
         let mut new_pids = new_pids.iter();
+        let mut focused_pane = None;
         for (layout, position_and_size) in positions_and_size {
             // A plugin pane
             if let Some(Run::Plugin(Some(plugin))) = &layout.run {
@@ -343,12 +487,16 @@ impl Tab {
                     .send_to_plugin(PluginInstruction::Load(pid_tx, plugin.clone(), tab_index))
                     .unwrap();
                 let pid = pid_rx.recv().unwrap();
+                self.plugin_paths.insert(pid, plugin.clone());
                 let new_plugin = PluginPane::new(
                     pid,
                     *position_and_size,
                     self.senders.to_plugin.as_ref().unwrap().clone(),
                 );
                 self.panes.insert(PaneId::Plugin(pid), Box::new(new_plugin));
+                if layout.focus {
+                    focused_pane = Some(PaneId::Plugin(pid));
+                }
                 // Send an initial mode update to the newly loaded plugin only!
                 self.senders
                     .send_to_plugin(PluginInstruction::Update(
@@ -359,14 +507,22 @@ impl Tab {
             } else {
                 // there are still panes left to fill, use the pids we received in this method
                 let pid = new_pids.next().unwrap(); // if this crashes it means we got less pids than there are panes in this layout
-                let new_terminal = TerminalPane::new(*pid, *position_and_size, self.colors);
-                self.os_api.set_terminal_size_using_fd(
+                let new_terminal = TerminalPane::new(
+                    *pid,
+                    *position_and_size,
+                    self.colors,
+                    self.character_cell_size,
+                    layout.scroll_buffer_size.unwrap_or(self.scroll_buffer_size),
+                );
+                self.set_pty_size(
                     new_terminal.pid,
                     new_terminal.columns() as u16,
                     new_terminal.rows() as u16,
                 );
-                self.panes
-                    .insert(PaneId::Terminal(*pid), Box::new(new_terminal));
+                self.insert_terminal_pane(new_terminal);
+                if layout.focus {
+                    focused_pane = Some(PaneId::Terminal(*pid));
+                }
             }
         }
         for unused_pid in new_pids {
@@ -377,9 +533,137 @@ impl Tab {
                 .send_to_pty(PtyInstruction::ClosePane(PaneId::Terminal(*unused_pid)))
                 .unwrap();
         }
-        self.active_terminal = self.panes.iter().map(|(id, _)| id.to_owned()).next();
+        self.active_terminal =
+            focused_pane.or_else(|| self.panes.iter().map(|(id, _)| id.to_owned()).next());
         self.render();
     }
+    /// Describes the panes of this tab as a [`Layout`], along with the ids of its terminal
+    /// panes in the order in which [`Tab::apply_layout`] would assign them. Panes hidden by a
+    /// fullscreen pane are included, in the place they had before it went fullscreen. Read-only
+    /// panes are left out, the others take up their space.
+    pub fn dump_layout(&self) -> (Layout, Vec<RawFd>) {
+        let panes: Vec<PanePosition> = self
+            .panes
+            .iter()
+            .filter(|(_, pane)| !pane.read_only())
+            .map(|(id, pane)| (*id, pane.position_and_size()))
+            .collect();
+        let mut terminal_ids = vec![];
+        let mut layout = self.layout_for_panes(panes, &mut terminal_ids);
+        if layout.parts.is_empty() {
+            // a layout is split into its parts, so a lone pane has to be one of them
+            layout = Layout {
+                parts: vec![layout],
+                ..Default::default()
+            };
+        }
+        (layout, terminal_ids)
+    }
+    pub fn dump_pane_screen(&self, id: PaneId) -> String {
+        self.panes
+            .get(&id)
+            .map(|pane| pane.dump_screen())
+            .unwrap_or_default()
+    }
+    pub fn dump_pane_scrollback(&self, id: PaneId, with_ansi_styles: bool) -> String {
+        self.panes
+            .get(&id)
+            .map(|pane| pane.dump_scrollback(with_ansi_styles))
+            .unwrap_or_default()
+    }
+    fn layout_for_panes(&self, panes: Vec<PanePosition>, terminal_ids: &mut Vec<RawFd>) -> Layout {
+        if panes.len() == 1 {
+            let run = match panes[0].0 {
+                PaneId::Terminal(pid) => {
+                    terminal_ids.push(pid);
+                    None
+                }
+                PaneId::Plugin(pid) => self
+                    .plugin_paths
+                    .get(&pid)
+                    .map(|path| Run::Plugin(Some(path.clone()))),
+            };
+            return Layout {
+                run,
+                focus: self.active_terminal == Some(panes[0].0),
+                ..Default::default()
+            };
+        }
+        // panes were created by splitting, so they can be cut into side by side columns
+        // or stacked rows, unless resizing made them interlock
+        let columns = group_panes(&panes, |p| (p.x, p.cols));
+        let (direction, groups, is_regular) = if columns.len() > 1 {
+            (Direction::Vertical, columns, true)
+        } else {
+            let rows = group_panes(&panes, |p| (p.y, p.rows));
+            if rows.len() > 1 {
+                (Direction::Horizontal, rows, true)
+            } else {
+                let mut panes = panes;
+                panes.sort_by_key(|(_, p)| (p.y, p.x));
+                let rows = panes.into_iter().map(|pane| vec![pane]).collect();
+                (Direction::Horizontal, rows, false)
+            }
+        };
+        let span = |p: &PositionAndSize| match direction {
+            Direction::Vertical => (p.x, p.cols, p.cols_fixed),
+            Direction::Horizontal => (p.y, p.rows, p.rows_fixed),
+        };
+        let sizes: Vec<usize> = groups
+            .iter()
+            .map(|group| {
+                let start = group.iter().map(|(_, p)| span(p).0).min().unwrap();
+                let end = group
+                    .iter()
+                    .map(|(_, p)| span(p).0 + span(p).1)
+                    .max()
+                    .unwrap();
+                end - start
+            })
+            .collect();
+        let total: usize = sizes.iter().sum();
+        let last_flexible_index = groups
+            .iter()
+            .rposition(|group| group.len() > 1 || !span(&group[0].1).2);
+        let mut parts = vec![];
+        for (idx, (group, size)) in groups.into_iter().zip(sizes).enumerate() {
+            let is_fixed = group.len() == 1 && span(&group[0].1).2;
+            let split_size = if !is_regular || Some(idx) == last_flexible_index {
+                // this part grows to fill whatever space the others left over
+                None
+            } else if is_fixed {
+                Some(SplitSize::Fixed(size as u16))
+            } else {
+                let percent = (size as f32 * 100.0 / total as f32).round() as u8;
+                Some(SplitSize::Percent(percent.max(1)))
+            };
+            let mut part = self.layout_for_panes(group, terminal_ids);
+            part.split_size = split_size;
+            parts.push(part);
+        }
+        Layout {
+            direction,
+            parts,
+            ..Default::default()
+        }
+    }
+    /// Tells the pty of a terminal pane its size. Read-only panes have no pty, their id is the
+    /// descriptor of the recording they replay.
+    fn set_pty_size(&self, pid: RawFd, columns: u16, rows: u16) {
+        if !self.read_only_terminals.contains(&pid) {
+            self.os_api.set_terminal_size_using_fd(pid, columns, rows);
+        }
+    }
+    /// Adds a pane that takes no input, such as the panes replaying a recording
+    pub fn new_read_only_pane(&mut self, pid: PaneId) {
+        if let PaneId::Terminal(pid) = pid {
+            self.read_only_terminals.insert(pid);
+        }
+        self.new_pane(pid);
+        if let Some(pane) = self.panes.get_mut(&pid) {
+            pane.set_read_only(true);
+        }
+    }
     pub fn new_pane(&mut self, pid: PaneId) {
         self.close_down_to_max_terminals();
         if self.fullscreen_is_active {
@@ -387,13 +671,19 @@ impl Tab {
         }
         if !self.has_panes() {
             if let PaneId::Terminal(term_pid) = pid {
-                let new_terminal = TerminalPane::new(term_pid, self.full_screen_ws, self.colors);
-                self.os_api.set_terminal_size_using_fd(
+                let new_terminal = TerminalPane::new(
+                    term_pid,
+                    self.full_screen_ws,
+                    self.colors,
+                    self.character_cell_size,
+                    self.scroll_buffer_size,
+                );
+                self.set_pty_size(
                     new_terminal.pid,
                     new_terminal.columns() as u16,
                     new_terminal.rows() as u16,
                 );
-                self.panes.insert(pid, Box::new(new_terminal));
+                self.insert_terminal_pane(new_terminal);
                 self.active_terminal = Some(pid);
             }
         } else {
@@ -437,16 +727,22 @@ impl Tab {
             {
                 if let PaneId::Terminal(term_pid) = pid {
                     let (top_winsize, bottom_winsize) = split_horizontally_with_gap(&terminal_ws);
-                    let new_terminal = TerminalPane::new(term_pid, bottom_winsize, self.colors);
-                    self.os_api.set_terminal_size_using_fd(
+                    let new_terminal = TerminalPane::new(
+                        term_pid,
+                        bottom_winsize,
+                        self.colors,
+                        self.character_cell_size,
+                        self.scroll_buffer_size,
+                    );
+                    terminal_to_split.change_pos_and_size(&top_winsize);
+                    self.set_pty_size(
                         new_terminal.pid,
                         bottom_winsize.cols as u16,
                         bottom_winsize.rows as u16,
                     );
-                    terminal_to_split.change_pos_and_size(&top_winsize);
-                    self.panes.insert(pid, Box::new(new_terminal));
+                    self.insert_terminal_pane(new_terminal);
                     if let PaneId::Terminal(terminal_id_to_split) = terminal_id_to_split {
-                        self.os_api.set_terminal_size_using_fd(
+                        self.set_pty_size(
                             terminal_id_to_split,
                             top_winsize.cols as u16,
                             top_winsize.rows as u16,
@@ -457,16 +753,22 @@ impl Tab {
             } else if terminal_to_split.columns() > terminal_to_split.min_width() * 2 {
                 if let PaneId::Terminal(term_pid) = pid {
                     let (left_winsize, right_winsize) = split_vertically_with_gap(&terminal_ws);
-                    let new_terminal = TerminalPane::new(term_pid, right_winsize, self.colors);
-                    self.os_api.set_terminal_size_using_fd(
+                    let new_terminal = TerminalPane::new(
+                        term_pid,
+                        right_winsize,
+                        self.colors,
+                        self.character_cell_size,
+                        self.scroll_buffer_size,
+                    );
+                    terminal_to_split.change_pos_and_size(&left_winsize);
+                    self.set_pty_size(
                         new_terminal.pid,
                         right_winsize.cols as u16,
                         right_winsize.rows as u16,
                     );
-                    terminal_to_split.change_pos_and_size(&left_winsize);
-                    self.panes.insert(pid, Box::new(new_terminal));
+                    self.insert_terminal_pane(new_terminal);
                     if let PaneId::Terminal(terminal_id_to_split) = terminal_id_to_split {
-                        self.os_api.set_terminal_size_using_fd(
+                        self.set_pty_size(
                             terminal_id_to_split,
                             left_winsize.cols as u16,
                             left_winsize.rows as u16,
@@ -485,13 +787,19 @@ impl Tab {
         }
         if !self.has_panes() {
             if let PaneId::Terminal(term_pid) = pid {
-                let new_terminal = TerminalPane::new(term_pid, self.full_screen_ws, self.colors);
-                self.os_api.set_terminal_size_using_fd(
+                let new_terminal = TerminalPane::new(
+                    term_pid,
+                    self.full_screen_ws,
+                    self.colors,
+                    self.character_cell_size,
+                    self.scroll_buffer_size,
+                );
+                self.set_pty_size(
                     new_terminal.pid,
                     new_terminal.columns() as u16,
                     new_terminal.rows() as u16,
                 );
-                self.panes.insert(pid, Box::new(new_terminal));
+                self.insert_terminal_pane(new_terminal);
                 self.active_terminal = Some(pid);
             }
         } else if let PaneId::Terminal(term_pid) = pid {
@@ -515,16 +823,22 @@ impl Tab {
 
             active_pane.change_pos_and_size(&top_winsize);
 
-            let new_terminal = TerminalPane::new(term_pid, bottom_winsize, self.colors);
-            self.os_api.set_terminal_size_using_fd(
+            let new_terminal = TerminalPane::new(
+                term_pid,
+                bottom_winsize,
+                self.colors,
+                self.character_cell_size,
+                self.scroll_buffer_size,
+            );
+            self.set_pty_size(
                 new_terminal.pid,
                 bottom_winsize.cols as u16,
                 bottom_winsize.rows as u16,
             );
-            self.panes.insert(pid, Box::new(new_terminal));
+            self.insert_terminal_pane(new_terminal);
 
             if let PaneId::Terminal(active_terminal_pid) = active_pane_id {
-                self.os_api.set_terminal_size_using_fd(
+                self.set_pty_size(
                     *active_terminal_pid,
                     top_winsize.cols as u16,
                     top_winsize.rows as u16,
@@ -542,13 +856,19 @@ impl Tab {
         }
         if !self.has_panes() {
             if let PaneId::Terminal(term_pid) = pid {
-                let new_terminal = TerminalPane::new(term_pid, self.full_screen_ws, self.colors);
-                self.os_api.set_terminal_size_using_fd(
+                let new_terminal = TerminalPane::new(
+                    term_pid,
+                    self.full_screen_ws,
+                    self.colors,
+                    self.character_cell_size,
+                    self.scroll_buffer_size,
+                );
+                self.set_pty_size(
                     new_terminal.pid,
                     new_terminal.columns() as u16,
                     new_terminal.rows() as u16,
                 );
-                self.panes.insert(pid, Box::new(new_terminal));
+                self.insert_terminal_pane(new_terminal);
                 self.active_terminal = Some(pid);
             }
         } else if let PaneId::Terminal(term_pid) = pid {
@@ -572,16 +892,22 @@ impl Tab {
 
             active_pane.change_pos_and_size(&left_winsize);
 
-            let new_terminal = TerminalPane::new(term_pid, right_winsize, self.colors);
-            self.os_api.set_terminal_size_using_fd(
+            let new_terminal = TerminalPane::new(
+                term_pid,
+                right_winsize,
+                self.colors,
+                self.character_cell_size,
+                self.scroll_buffer_size,
+            );
+            self.set_pty_size(
                 new_terminal.pid,
                 right_winsize.cols as u16,
                 right_winsize.rows as u16,
             );
-            self.panes.insert(pid, Box::new(new_terminal));
+            self.insert_terminal_pane(new_terminal);
 
             if let PaneId::Terminal(active_terminal_pid) = active_pane_id {
-                self.os_api.set_terminal_size_using_fd(
+                self.set_pty_size(
                     *active_terminal_pid,
                     left_winsize.cols as u16,
                     left_winsize.rows as u16,
@@ -602,7 +928,7 @@ impl Tab {
     fn get_active_pane_id(&self) -> Option<PaneId> {
         self.active_terminal
     }
-    fn get_active_terminal_id(&self) -> Option<RawFd> {
+    pub fn get_active_terminal_id(&self) -> Option<RawFd> {
         // FIXME: Is there a better way to do this?
         if let Some(PaneId::Terminal(pid)) = self.active_terminal {
             Some(pid)
@@ -610,24 +936,18 @@ impl Tab {
             None
         }
     }
+    /// Adds a new terminal pane, whose grid is fed with its output by the task reading its pty
+    fn insert_terminal_pane(&mut self, new_terminal: TerminalPane) {
+        let pid = new_terminal.pid;
+        self.senders
+            .send_to_pty(PtyInstruction::ShareGrid(pid, new_terminal.shared_grid()))
+            .unwrap();
+        self.panes
+            .insert(PaneId::Terminal(pid), Box::new(new_terminal));
+    }
     pub fn has_terminal_pid(&self, pid: RawFd) -> bool {
         self.panes.contains_key(&PaneId::Terminal(pid))
     }
-    pub fn handle_pty_bytes(&mut self, pid: RawFd, bytes: VteBytes) {
-        // if we don't have the terminal in self.terminals it's probably because
-        // of a race condition where the terminal was created in pty but has not
-        // yet been created in Screen. These events are currently not buffered, so
-        // if you're debugging seemingly randomly missing stdout data, this is
-        // the reason
-        if let Some(terminal_output) = self.panes.get_mut(&PaneId::Terminal(pid)) {
-            terminal_output.handle_pty_bytes(bytes);
-            let messages_to_pty = terminal_output.drain_messages_to_pty();
-            for message in messages_to_pty {
-                self.write_to_pane_id(message, PaneId::Terminal(pid));
-            }
-            // self.render();
-        }
-    }
     pub fn write_to_terminals_on_current_tab(&mut self, input_bytes: Vec<u8>) {
         let pane_ids = self.get_pane_ids();
         pane_ids.iter().for_each(|&pane_id| {
@@ -642,12 +962,7 @@ impl Tab {
             PaneId::Terminal(active_terminal_id) => {
                 let active_terminal = self.panes.get(&pane_id).unwrap();
                 let adjusted_input = active_terminal.adjust_input_to_terminal(input_bytes);
-                self.os_api
-                    .write_to_tty_stdin(active_terminal_id, &adjusted_input)
-                    .expect("failed to write to terminal");
-                self.os_api
-                    .tcdrain(active_terminal_id)
-                    .expect("failed to drain terminal");
+                self.write_to_terminal(active_terminal_id, &adjusted_input);
             }
             PaneId::Plugin(pid) => {
                 for key in parse_keys(&input_bytes) {
@@ -658,6 +973,55 @@ impl Tab {
             }
         }
     }
+    /// Writes to the program running in the terminal pane, unless the pane is read-only
+    fn write_to_terminal(&self, terminal_id: RawFd, bytes: &[u8]) {
+        let read_only = self
+            .panes
+            .get(&PaneId::Terminal(terminal_id))
+            .map_or(false, |pane| pane.read_only());
+        if read_only {
+            return;
+        }
+        self.os_api
+            .write_to_tty_stdin(terminal_id, bytes)
+            .expect("failed to write to terminal");
+        self.os_api
+            .tcdrain(terminal_id)
+            .expect("failed to drain terminal");
+    }
+    pub fn paste(&mut self, pasted_bytes: Vec<u8>) {
+        if self.is_sync_panes_active() {
+            for pane_id in self.get_pane_ids() {
+                self.paste_to_pane_id(pasted_bytes.clone(), pane_id);
+            }
+        } else if let Some(active_pane_id) = self.get_active_pane_id() {
+            self.paste_to_pane_id(pasted_bytes, active_pane_id);
+        }
+    }
+    fn paste_to_pane_id(&mut self, pasted_bytes: Vec<u8>, pane_id: PaneId) {
+        match pane_id {
+            PaneId::Terminal(terminal_id) => {
+                let bracketed_paste = self
+                    .panes
+                    .get(&pane_id)
+                    .map(|pane| pane.bracketed_paste())
+                    .unwrap_or(false);
+                // the pasted text is written as a whole, without any adjustments for the
+                // terminal state, so that the application receives exactly what was pasted
+                let bytes = if bracketed_paste {
+                    // an end marker inside the pasted text would let it escape the paste
+                    let mut bytes = BRACKETED_PASTE_START.to_vec();
+                    bytes.extend(strip_bracketed_paste_end(&pasted_bytes));
+                    bytes.extend_from_slice(BRACKETED_PASTE_END);
+                    bytes
+                } else {
+                    pasted_bytes
+                };
+                self.write_to_terminal(terminal_id, &bytes);
+            }
+            PaneId::Plugin(_) => self.write_to_pane_id(pasted_bytes, pane_id),
+        }
+    }
     pub fn get_active_terminal_cursor_position(&self) -> Option<(usize, usize)> {
         // (x, y)
         let active_terminal = &self.get_active_pane()?;
@@ -708,7 +1072,7 @@ impl Tab {
             }
             let active_terminal = self.panes.get(&active_pane_id).unwrap();
             if let PaneId::Terminal(active_pid) = active_pane_id {
-                self.os_api.set_terminal_size_using_fd(
+                self.set_pty_size(
                     active_pid,
                     active_terminal.columns() as u16,
                     active_terminal.rows() as u16,
@@ -727,6 +1091,52 @@ impl Tab {
             pane.set_should_render(true);
             pane.render_full_viewport();
         }
+        // the clients might display something else, like another tab
+        self.frame.invalidate();
+        self.rendered_images.clear();
+        self.last_cursor_output = None;
+    }
+    /// The changes to the frame followed by the images to draw over it: the ones that are new
+    /// or moved, and the ones the changes are drawn over. The text under the images that moved
+    /// or went away is drawn again.
+    fn render_frame_and_images(
+        &mut self,
+        image_placements: Vec<(PaneId, ImagePlacement)>,
+    ) -> String {
+        for (pane_id, placement) in &self.rendered_images {
+            if !image_placements.contains(&(*pane_id, *placement)) {
+                self.frame
+                    .redraw_area(placement.x, placement.y, placement.columns, placement.rows);
+            }
+        }
+        let mut output = String::new();
+        for (pane_id, placement) in &image_placements {
+            let is_drawn_over = self.frame.area_changed(
+                placement.x,
+                placement.y,
+                placement.columns,
+                placement.rows,
+            );
+            if is_drawn_over || !self.rendered_images.contains(&(*pane_id, *placement)) {
+                if let Some(image_output) = self
+                    .panes
+                    .get(pane_id)
+                    .and_then(|pane| pane.render_image(placement))
+                {
+                    output.push_str(&image_output);
+                }
+            }
+        }
+        self.rendered_images = image_placements;
+        self.frame.render_changes() + &output
+    }
+    /// Images are placed using the size of a character cell of the clients' terminal
+    pub fn update_character_cell_size(&mut self, character_cell_size: Option<SizeInPixels>) {
+        self.character_cell_size = character_cell_size;
+        for pane in self.panes.values_mut() {
+            pane.set_character_cell_size(character_cell_size);
+        }
+        self.set_force_render();
     }
     pub fn is_sync_panes_active(&self) -> bool {
         self.synchronize_is_active
@@ -735,26 +1145,30 @@ impl Tab {
         self.synchronize_is_active = !self.synchronize_is_active;
     }
     pub fn render(&mut self) {
-        if self.active_terminal.is_none()
-            || *self.session_state.read().unwrap() != SessionState::Attached
-        {
+        if self.active_terminal.is_none() || !self.session_state.read().unwrap().has_clients() {
             // we might not have an active terminal if we closed the last pane
             // in that case, we should not render as the app is exiting
             // or if this session is not attached to a client, we do not have to render
             return;
         }
         let mut output = String::new();
+        // drawn as is, after the frame
+        let mut pane_outputs = String::new();
         let mut boundaries = Boundaries::new(
             self.full_screen_ws.cols as u16,
             self.full_screen_ws.rows as u16,
         );
-        let hide_cursor = "\u{1b}[?25l";
-        output.push_str(hide_cursor);
+        if self.frame.size() != (self.full_screen_ws.cols, self.full_screen_ws.rows) {
+            self.frame = Frame::new(self.full_screen_ws.cols, self.full_screen_ws.rows);
+        }
         if self.should_clear_display_before_rendering {
             let clear_display = "\u{1b}[2J";
             output.push_str(clear_display);
+            self.frame.invalidate();
+            self.rendered_images.clear();
             self.should_clear_display_before_rendering = false;
         }
+        let mut image_placements = vec![];
         for (kind, pane) in self.panes.iter_mut() {
             if !self.panes_to_hide.contains(&pane.pid()) {
                 match self.active_terminal.unwrap() == pane.pid() {
@@ -764,27 +1178,36 @@ impl Tab {
                     }
                     false => boundaries.add_rect(pane.as_ref(), self.mode_info.mode, None),
                 }
-                if let Some(vte_output) = pane.render() {
-                    let vte_output = if let PaneId::Terminal(_) = kind {
-                        vte_output
-                    } else {
-                        adjust_to_size(&vte_output, pane.rows(), pane.columns())
-                    };
+                if let PaneId::Terminal(_) = kind {
+                    if let Some(chunks) = pane.render_chunks() {
+                        for chunk in chunks {
+                            self.frame.add_chunk(chunk, pane.x() + pane.columns());
+                        }
+                    }
+                } else if let Some(vte_output) = pane.render() {
+                    let vte_output = adjust_to_size(&vte_output, pane.rows(), pane.columns());
                     // FIXME: Use Termion for cursor and style clearing?
-                    output.push_str(&format!(
+                    pane_outputs.push_str(&format!(
                         "\u{1b}[{};{}H\u{1b}[m{}",
                         pane.y() + 1,
                         pane.x() + 1,
                         vte_output
                     ));
+                    self.frame
+                        .invalidate_area(pane.x(), pane.y(), pane.columns(), pane.rows());
                 }
+                image_placements.extend(
+                    pane.image_placements()
+                        .into_iter()
+                        .map(|placement| (*kind, placement)),
+                );
             }
         }
+        boundaries.add_to_frame(&mut self.frame);
+        output.push_str(&self.render_frame_and_images(image_placements));
+        output.push_str(&pane_outputs);
 
-        // TODO: only render (and calculate) boundaries if there was a resize
-        output.push_str(&boundaries.vte_output());
-
-        match self.get_active_terminal_cursor_position() {
+        let cursor_output = match self.get_active_terminal_cursor_position() {
             Some((cursor_position_x, cursor_position_y)) => {
                 let show_cursor = "\u{1b}[?25h";
                 let change_cursor_shape = self.get_active_pane().unwrap().cursor_shape_csi();
@@ -794,14 +1217,21 @@ impl Tab {
                     cursor_position_x + 1,
                     change_cursor_shape
                 ); // goto row/col
-                output.push_str(show_cursor);
-                output.push_str(goto_cursor_position);
+                format!("{}{}", show_cursor, goto_cursor_position)
             }
             None => {
                 let hide_cursor = "\u{1b}[?25l";
-                output.push_str(hide_cursor);
+                hide_cursor.to_string()
             }
+        };
+        if output.is_empty() && self.last_cursor_output.as_ref() == Some(&cursor_output) {
+            // the clients are up to date
+            return;
         }
+        let hide_cursor = "\u{1b}[?25l";
+        output.insert_str(0, hide_cursor);
+        output.push_str(&cursor_output);
+        self.last_cursor_output = Some(cursor_output);
 
         self.senders
             .send_to_server(ServerInstruction::Render(Some(output)))
@@ -1257,89 +1687,65 @@ impl Tab {
     fn reduce_pane_height_down(&mut self, id: &PaneId, count: usize) {
         let terminal = self.panes.get_mut(id).unwrap();
         terminal.reduce_height_down(count);
-        if let PaneId::Terminal(pid) = id {
-            self.os_api.set_terminal_size_using_fd(
-                *pid,
-                terminal.columns() as u16,
-                terminal.rows() as u16,
-            );
+        let (columns, rows) = (terminal.columns() as u16, terminal.rows() as u16);
+        if let PaneId::Terminal(pid) = *id {
+            self.set_pty_size(pid, columns, rows);
         }
     }
     fn reduce_pane_height_up(&mut self, id: &PaneId, count: usize) {
         let terminal = self.panes.get_mut(id).unwrap();
         terminal.reduce_height_up(count);
-        if let PaneId::Terminal(pid) = id {
-            self.os_api.set_terminal_size_using_fd(
-                *pid,
-                terminal.columns() as u16,
-                terminal.rows() as u16,
-            );
+        let (columns, rows) = (terminal.columns() as u16, terminal.rows() as u16);
+        if let PaneId::Terminal(pid) = *id {
+            self.set_pty_size(pid, columns, rows);
         }
     }
     fn increase_pane_height_down(&mut self, id: &PaneId, count: usize) {
         let terminal = self.panes.get_mut(id).unwrap();
         terminal.increase_height_down(count);
-        if let PaneId::Terminal(pid) = terminal.pid() {
-            self.os_api.set_terminal_size_using_fd(
-                pid,
-                terminal.columns() as u16,
-                terminal.rows() as u16,
-            );
+        let (columns, rows) = (terminal.columns() as u16, terminal.rows() as u16);
+        if let PaneId::Terminal(pid) = *id {
+            self.set_pty_size(pid, columns, rows);
         }
     }
     fn increase_pane_height_up(&mut self, id: &PaneId, count: usize) {
         let terminal = self.panes.get_mut(id).unwrap();
         terminal.increase_height_up(count);
-        if let PaneId::Terminal(pid) = terminal.pid() {
-            self.os_api.set_terminal_size_using_fd(
-                pid,
-                terminal.columns() as u16,
-                terminal.rows() as u16,
-            );
+        let (columns, rows) = (terminal.columns() as u16, terminal.rows() as u16);
+        if let PaneId::Terminal(pid) = *id {
+            self.set_pty_size(pid, columns, rows);
         }
     }
     fn increase_pane_width_right(&mut self, id: &PaneId, count: usize) {
         let terminal = self.panes.get_mut(id).unwrap();
         terminal.increase_width_right(count);
-        if let PaneId::Terminal(pid) = terminal.pid() {
-            self.os_api.set_terminal_size_using_fd(
-                pid,
-                terminal.columns() as u16,
-                terminal.rows() as u16,
-            );
+        let (columns, rows) = (terminal.columns() as u16, terminal.rows() as u16);
+        if let PaneId::Terminal(pid) = *id {
+            self.set_pty_size(pid, columns, rows);
         }
     }
     fn increase_pane_width_left(&mut self, id: &PaneId, count: usize) {
         let terminal = self.panes.get_mut(id).unwrap();
         terminal.increase_width_left(count);
-        if let PaneId::Terminal(pid) = terminal.pid() {
-            self.os_api.set_terminal_size_using_fd(
-                pid,
-                terminal.columns() as u16,
-                terminal.rows() as u16,
-            );
+        let (columns, rows) = (terminal.columns() as u16, terminal.rows() as u16);
+        if let PaneId::Terminal(pid) = *id {
+            self.set_pty_size(pid, columns, rows);
         }
     }
     fn reduce_pane_width_right(&mut self, id: &PaneId, count: usize) {
         let terminal = self.panes.get_mut(id).unwrap();
         terminal.reduce_width_right(count);
-        if let PaneId::Terminal(pid) = terminal.pid() {
-            self.os_api.set_terminal_size_using_fd(
-                pid,
-                terminal.columns() as u16,
-                terminal.rows() as u16,
-            );
+        let (columns, rows) = (terminal.columns() as u16, terminal.rows() as u16);
+        if let PaneId::Terminal(pid) = *id {
+            self.set_pty_size(pid, columns, rows);
         }
     }
     fn reduce_pane_width_left(&mut self, id: &PaneId, count: usize) {
         let terminal = self.panes.get_mut(id).unwrap();
         terminal.reduce_width_left(count);
-        if let PaneId::Terminal(pid) = terminal.pid() {
-            self.os_api.set_terminal_size_using_fd(
-                pid,
-                terminal.columns() as u16,
-                terminal.rows() as u16,
-            );
+        let (columns, rows) = (terminal.columns() as u16, terminal.rows() as u16);
+        if let PaneId::Terminal(pid) = *id {
+            self.set_pty_size(pid, columns, rows);
         }
     }
     fn pane_is_between_vertical_borders(
@@ -2153,6 +2559,9 @@ impl Tab {
         }
     }
     pub fn close_pane_without_rerender(&mut self, id: PaneId) {
+        if let PaneId::Terminal(pid) = id {
+            self.read_only_terminals.remove(&pid);
+        }
         if self.fullscreen_is_active {
             self.toggle_active_pane_fullscreen();
         }
@@ -2299,13 +2708,116 @@ impl Tab {
             active_terminal.clear_scroll();
         }
     }
+    pub fn update_search_term(&mut self, bytes: &[u8]) {
+        if let Some(active_terminal_id) = self.get_active_terminal_id() {
+            let active_terminal = self
+                .panes
+                .get_mut(&PaneId::Terminal(active_terminal_id))
+                .unwrap();
+            active_terminal.update_search_term(bytes);
+            self.render();
+        }
+    }
+    pub fn reset_search_term(&mut self) {
+        if let Some(active_terminal_id) = self.get_active_terminal_id() {
+            let active_terminal = self
+                .panes
+                .get_mut(&PaneId::Terminal(active_terminal_id))
+                .unwrap();
+            active_terminal.reset_search_term();
+            self.render();
+        }
+    }
+    pub fn search_up(&mut self) {
+        if let Some(active_terminal_id) = self.get_active_terminal_id() {
+            let active_terminal = self
+                .panes
+                .get_mut(&PaneId::Terminal(active_terminal_id))
+                .unwrap();
+            active_terminal.search_up();
+            self.render();
+        }
+    }
+    pub fn search_down(&mut self) {
+        if let Some(active_terminal_id) = self.get_active_terminal_id() {
+            let active_terminal = self
+                .panes
+                .get_mut(&PaneId::Terminal(active_terminal_id))
+                .unwrap();
+            active_terminal.search_down();
+            self.render();
+        }
+    }
+    pub fn toggle_search_option(&mut self, option: SearchOption) {
+        if let Some(active_terminal_id) = self.get_active_terminal_id() {
+            let active_terminal = self
+                .panes
+                .get_mut(&PaneId::Terminal(active_terminal_id))
+                .unwrap();
+            active_terminal.toggle_search_option(option);
+            self.render();
+        }
+    }
+    pub fn clear_search(&mut self) {
+        for pane in self.panes.values_mut() {
+            pane.clear_search();
+        }
+    }
+    pub fn start_copy_mode(&mut self) {
+        if let Some(active_terminal_id) = self.get_active_terminal_id() {
+            let active_terminal = self
+                .panes
+                .get_mut(&PaneId::Terminal(active_terminal_id))
+                .unwrap();
+            active_terminal.start_copy_mode();
+        }
+    }
+    pub fn exit_copy_mode(&mut self) {
+        for pane in self.panes.values_mut() {
+            pane.exit_copy_mode();
+        }
+    }
+    pub fn copy_mode_move(&mut self, motion: CopyModeMotion) {
+        if let Some(active_terminal_id) = self.get_active_terminal_id() {
+            let active_terminal = self
+                .panes
+                .get_mut(&PaneId::Terminal(active_terminal_id))
+                .unwrap();
+            active_terminal.copy_mode_move(motion);
+            self.render();
+        }
+    }
+    pub fn copy_mode_select(&mut self, kind: SelectionKind) {
+        if let Some(active_terminal_id) = self.get_active_terminal_id() {
+            let active_terminal = self
+                .panes
+                .get_mut(&PaneId::Terminal(active_terminal_id))
+                .unwrap();
+            active_terminal.copy_mode_select(kind);
+            self.render();
+        }
+    }
+    pub fn search_info(&self) -> Option<SearchInfo> {
+        self.get_active_pane()
+            .and_then(|active_pane| active_pane.search_info())
+    }
     pub fn scroll_terminal_up(&mut self, point: &Position, lines: usize) {
+        if let Some(pane_id) = self.get_pane_id_at(point) {
+            if self.report_mouse_event(pane_id, MouseEvent::WheelUp, point) {
+                return;
+            }
+        }
         if let Some(pane) = self.get_pane_at(point) {
             pane.scroll_up(lines);
             self.render();
         }
     }
     pub fn scroll_terminal_down(&mut self, point: &Position, lines: usize) {
+        if let Some(pane_id) = self.get_pane_id_at(point) {
+            if self.report_mouse_event(pane_id, MouseEvent::WheelDown, point) {
+                return;
+            }
+        }
         if let Some(pane) = self.get_pane_at(point) {
             pane.scroll_down(lines);
             self.render();
@@ -2327,15 +2839,82 @@ impl Tab {
             .find(|(_, p)| p.contains(point))
             .map(|(&id, _)| id)
     }
+    /// Send a mouse event to the pane if the application in it asked for mouse reporting,
+    /// returns false if the event should be handled by us instead
+    fn report_mouse_event(&mut self, pane_id: PaneId, event: MouseEvent, point: &Position) -> bool {
+        let (terminal_id, report) = match (pane_id, self.panes.get(&pane_id)) {
+            // read-only panes are scrolled and selected in by us
+            (PaneId::Terminal(terminal_id), Some(pane))
+                if pane.mouse_reporting() && !pane.read_only() =>
+            {
+                let relative_position = pane.relative_position(point);
+                (
+                    terminal_id,
+                    pane.mouse_event_report(event, &relative_position),
+                )
+            }
+            _ => return false,
+        };
+        if let Some(report) = report {
+            self.write_to_terminal(terminal_id, &report);
+        }
+        true
+    }
     pub fn handle_left_click(&mut self, position: &Position) {
+        self.held_mouse_button = Some(MouseButton::Left);
         self.focus_pane_at(position);
+        let is_repeated_click = self.last_click_position == Some(*position)
+            && self
+                .last_click_time
+                .map(|last_click_time| last_click_time.elapsed() < MULTIPLE_CLICK_INTERVAL)
+                .unwrap_or(false);
+        // a fourth click starts over with a character selection
+        self.click_count = if is_repeated_click {
+            self.click_count % 3 + 1
+        } else {
+            1
+        };
+        self.last_click_position = Some(*position);
+        self.last_click_time = Some(Instant::now());
+
+        if let Some(pane_id) = self.get_pane_id_at(position) {
+            if self.report_mouse_event(pane_id, MouseEvent::LeftPress, position) {
+                // keep sending events to this pane until the button is released
+                self.mouse_reporting_pane = Some(pane_id);
+                self.last_mouse_hold_position = Some(*position);
+                return;
+            }
+        }
 
+        let click_count = self.click_count;
+        let word_separators = self.word_separators.clone();
         if let Some(pane) = self.get_pane_at(position) {
             let relative_position = pane.relative_position(position);
-            pane.start_selection(&relative_position);
+            match click_count {
+                2 => pane.select_word(&relative_position, &word_separators),
+                3 => pane.select_line(&relative_position),
+                _ => pane.start_selection(&relative_position),
+            }
             self.render();
         };
     }
+    pub fn handle_right_click(&mut self, position: &Position) {
+        self.report_click(MouseButton::Right, MouseEvent::RightPress, position);
+    }
+    pub fn handle_middle_click(&mut self, position: &Position) {
+        self.report_click(MouseButton::Middle, MouseEvent::MiddlePress, position);
+    }
+    /// Right and middle clicks are only reported to the pane under them, they do nothing in the
+    /// panes that did not ask for mouse reporting
+    fn report_click(&mut self, button: MouseButton, event: MouseEvent, position: &Position) {
+        self.held_mouse_button = Some(button);
+        if let Some(pane_id) = self.get_pane_id_at(position) {
+            if self.report_mouse_event(pane_id, event, position) {
+                self.mouse_reporting_pane = Some(pane_id);
+                self.last_mouse_hold_position = Some(*position);
+            }
+        }
+    }
     fn focus_pane_at(&mut self, point: &Position) {
         if let Some(clicked_pane) = self.get_pane_id_at(point) {
             self.active_terminal = Some(clicked_pane);
@@ -2343,6 +2922,20 @@ impl Tab {
         }
     }
     pub fn handle_mouse_release(&mut self, position: &Position) {
+        let button = self.held_mouse_button.take().unwrap_or(MouseButton::Left);
+        if let Some(pane_id) = self.mouse_reporting_pane.take() {
+            self.last_mouse_hold_position = None;
+            let event = match button {
+                MouseButton::Right => MouseEvent::RightRelease,
+                MouseButton::Middle => MouseEvent::MiddleRelease,
+                _ => MouseEvent::LeftRelease,
+            };
+            self.report_mouse_event(pane_id, event, position);
+            return;
+        }
+        if button != MouseButton::Left {
+            return;
+        }
         let active_pane_id = self.get_active_pane_id();
         // on release, get the selected text from the active pane, and reset it's selection
         let mut selected_text = None;
@@ -2355,6 +2948,14 @@ impl Tab {
                     self.render();
                 }
             }
+        } else if self.click_count > 1 {
+            // the word or line selected by the click is kept as it is
+            if let Some(pane) = self.get_pane_at(position) {
+                pane.end_selection(None);
+                selected_text = pane.get_selected_text();
+                pane.reset_selection();
+                self.render();
+            }
         } else if let Some(pane) = self.get_pane_at(position) {
             let relative_position = pane.relative_position(position);
             pane.end_selection(Some(&relative_position));
@@ -2368,6 +2969,24 @@ impl Tab {
         }
     }
     pub fn handle_mouse_hold(&mut self, position: &Position) {
+        let button = self.held_mouse_button.unwrap_or(MouseButton::Left);
+        if let Some(pane_id) = self.mouse_reporting_pane {
+            // the client repeats hold events while the mouse is still
+            if self.last_mouse_hold_position != Some(*position) {
+                self.last_mouse_hold_position = Some(*position);
+                let event = match button {
+                    MouseButton::Right => MouseEvent::RightDrag,
+                    MouseButton::Middle => MouseEvent::MiddleDrag,
+                    _ => MouseEvent::LeftDrag,
+                };
+                self.report_mouse_event(pane_id, event, position);
+            }
+            return;
+        }
+        if button != MouseButton::Left || self.click_count > 1 {
+            // only the left button selects, and dragging does not change a word or line selection
+            return;
+        }
         if let Some(active_pane_id) = self.get_active_pane_id() {
             if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
                 let relative_position = active_pane.relative_position(position);
@@ -2376,6 +2995,21 @@ impl Tab {
         }
         self.render();
     }
+    /// The mouse moved with no button held, which only the panes tracking all motion hear of
+    pub fn handle_mouse_motion(&mut self, position: &Position) {
+        if let Some(pane_id) = self.get_pane_id_at(position) {
+            self.report_mouse_event(pane_id, MouseEvent::Motion, position);
+        }
+    }
+
+    /// The link under the last mouse click in the active pane, or else under its cursor
+    pub fn link_to_open(&self) -> Option<String> {
+        let active_pane = self.get_active_pane()?;
+        self.last_click_position
+            .filter(|position| active_pane.contains(position))
+            .and_then(|position| active_pane.link_at(&active_pane.relative_position(&position)))
+            .or_else(|| active_pane.link_under_cursor())
+    }
 
     pub fn copy_selection(&self) {
         let selected_text = self.get_active_pane().and_then(|p| p.get_selected_text());
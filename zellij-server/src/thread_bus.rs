@@ -4,6 +4,7 @@ use crate::{
     os_input_output::ServerOsApi, pty::PtyInstruction, screen::ScreenInstruction,
     wasm_vm::PluginInstruction, ServerInstruction,
 };
+use std::time::Instant;
 use zellij_utils::{channels, channels::SenderWithContext, errors::ErrorContext};
 
 /// A container for senders to the different threads in zellij on the server side
@@ -135,7 +136,12 @@ impl<T> Bus<T> {
         }
     }
 
+    /// Receives from the first receiver with a pending event, so that they are handled in order
+    /// of priority, eg. user input before pty output
     pub fn recv(&self) -> Result<(T, ErrorContext), channels::RecvError> {
+        if let Some(event) = self.try_recv_by_priority() {
+            return Ok(event);
+        }
         let mut selector = channels::Select::new();
         self.receivers.iter().for_each(|r| {
             selector.recv(r);
@@ -144,4 +150,34 @@ impl<T> Bus<T> {
         let idx = oper.index();
         oper.recv(&self.receivers[idx])
     }
+
+    /// Like [`Bus::recv`], but gives up once `deadline` is reached
+    pub fn recv_deadline(
+        &self,
+        deadline: Instant,
+    ) -> Result<(T, ErrorContext), channels::RecvTimeoutError> {
+        if let Some(event) = self.try_recv_by_priority() {
+            return Ok(event);
+        }
+        let mut selector = channels::Select::new();
+        self.receivers.iter().for_each(|r| {
+            selector.recv(r);
+        });
+        let oper = selector
+            .select_deadline(deadline)
+            .map_err(|_| channels::RecvTimeoutError::Timeout)?;
+        let idx = oper.index();
+        oper.recv(&self.receivers[idx])
+            .map_err(|_| channels::RecvTimeoutError::Disconnected)
+    }
+
+    fn try_recv_by_priority(&self) -> Option<(T, ErrorContext)> {
+        self.receivers
+            .iter()
+            .find_map(|receiver| receiver.try_recv().ok())
+    }
 }
+
+#[cfg(test)]
+#[path = "./unit/thread_bus_tests.rs"]
+mod thread_bus_tests;
@@ -1,6 +1,7 @@
 use zellij_utils::zellij_tile;
 
 use crate::tab::Pane;
+use crate::ui::frame::Frame;
 use ansi_term::Colour::{Fixed, RGB};
 use std::collections::HashMap;
 use zellij_tile::data::{InputMode, Palette, PaletteColor};
@@ -577,17 +578,10 @@ impl Boundaries {
             }
         }
     }
-    pub fn vte_output(&self) -> String {
-        let mut vte_output = String::new();
+    pub fn add_to_frame(&self, frame: &mut Frame) {
         for (coordinates, boundary_character) in &self.boundary_characters {
-            vte_output.push_str(&format!(
-                "\u{1b}[{};{}H\u{1b}[m{}",
-                coordinates.y + 1,
-                coordinates.x + 1,
-                boundary_character
-            )); // goto row/col + boundary character
-        }
-        vte_output
+            frame.add_boundary(coordinates.x, coordinates.y, boundary_character.to_string());
+        }
     }
     fn rect_right_boundary_is_before_screen_edge(&self, rect: &dyn Pane) -> bool {
         rect.x() + rect.columns() < self.columns
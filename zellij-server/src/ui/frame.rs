@@ -0,0 +1,271 @@
+//! A copy of what the clients display, made of the cells of the panes and their boundaries,
+//! so that only the cells that changed since the previous render are sent to them.
+
+use std::rc::Rc;
+
+use crate::panes::{AnsiCode, CharacterStyles};
+
+const END_LINK: &str = "\u{1b}]8;;\u{1b}\\";
+
+/// A character drawn by a pane, with the zero width characters that combine with it
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameCharacter {
+    pub character: char,
+    pub combining: Option<Box<str>>,
+    pub width: usize,
+    pub styles: CharacterStyles,
+    /// The OSC 8 sequence starting the hyperlink of this character
+    pub link: Option<Rc<str>>,
+}
+
+impl FrameCharacter {
+    pub fn new(character: char, width: usize, styles: CharacterStyles) -> Self {
+        FrameCharacter {
+            character,
+            combining: None,
+            width,
+            styles: with_explicit_resets(styles),
+            link: None,
+        }
+    }
+    pub fn add_combining_character(&mut self, character: char) {
+        let mut combining = self.combining.take().map(String::from).unwrap_or_default();
+        combining.push(character);
+        self.combining = Some(combining.into_boxed_str());
+    }
+    fn push_to(&self, output: &mut String) {
+        output.push(self.character);
+        if let Some(combining) = self.combining.as_ref() {
+            output.push_str(combining);
+        }
+    }
+}
+
+/// Characters drawn by a pane on a line of the screen, starting at column `x`
+#[derive(Clone, Debug)]
+pub struct FrameChunk {
+    pub x: usize,
+    pub y: usize,
+    pub characters: Vec<FrameCharacter>,
+}
+
+impl FrameChunk {
+    /// The chunk as it would be drawn on its own
+    pub fn vte_output(&self) -> String {
+        let mut vte_output = format!("\u{1b}[{};{}H\u{1b}[m", self.y + 1, self.x + 1);
+        let mut character_styles = with_explicit_resets(CharacterStyles::new());
+        let mut link = None;
+        for character in &self.characters {
+            push_styles_diff(&mut vte_output, &mut character_styles, &character.styles);
+            push_link_diff(&mut vte_output, &mut link, &character.link);
+            character.push_to(&mut vte_output);
+        }
+        push_link_diff(&mut vte_output, &mut link, &None);
+        vte_output
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FrameCell {
+    /// What the clients display here is not known, eg. after it was drawn by a plugin
+    Unknown,
+    /// The right half of a wide character
+    Covered,
+    Character(FrameCharacter),
+    /// A boundary between panes, already rendered with its color
+    Boundary(String),
+}
+
+#[derive(Debug)]
+pub struct Frame {
+    columns: usize,
+    rows: usize,
+    cells: Vec<FrameCell>,
+    changed: Vec<bool>,
+}
+
+impl Frame {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Frame {
+            columns,
+            rows,
+            cells: vec![FrameCell::Unknown; columns * rows],
+            changed: vec![false; columns * rows],
+        }
+    }
+    pub fn size(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+    /// Forgets what the clients display, so that everything is drawn again
+    pub fn invalidate(&mut self) {
+        for (cell, changed) in self.cells.iter_mut().zip(self.changed.iter_mut()) {
+            *cell = FrameCell::Unknown;
+            *changed = false;
+        }
+    }
+    pub fn invalidate_area(&mut self, x: usize, y: usize, columns: usize, rows: usize) {
+        for line in y..(y + rows).min(self.rows) {
+            for column in x..(x + columns).min(self.columns) {
+                let index = line * self.columns + column;
+                self.cells[index] = FrameCell::Unknown;
+                self.changed[index] = false;
+            }
+        }
+    }
+    /// Draws the known cells of the area again, eg. after something else was drawn over them
+    pub fn redraw_area(&mut self, x: usize, y: usize, columns: usize, rows: usize) {
+        for line in y..(y + rows).min(self.rows) {
+            for column in x..(x + columns).min(self.columns) {
+                let index = line * self.columns + column;
+                match self.cells[index] {
+                    FrameCell::Unknown => {}
+                    // the wide character it is the right half of
+                    FrameCell::Covered if column > 0 => self.changed[index - 1] = true,
+                    _ => self.changed[index] = true,
+                }
+            }
+        }
+    }
+    /// Whether the next render draws over any cell of the area
+    pub fn area_changed(&self, x: usize, y: usize, columns: usize, rows: usize) -> bool {
+        (y..(y + rows).min(self.rows)).any(|line| {
+            (x..(x + columns).min(self.columns))
+                .any(|column| self.changed[line * self.columns + column])
+        })
+    }
+    /// Draws the chunk, clipped to `max_x` (excluded)
+    pub fn add_chunk(&mut self, chunk: FrameChunk, max_x: usize) {
+        let mut x = chunk.x;
+        for character in chunk.characters {
+            let width = character.width.max(1);
+            if x + width > max_x.min(self.columns) {
+                break;
+            }
+            self.set(x, chunk.y, FrameCell::Character(character));
+            for covered_x in x + 1..x + width {
+                self.set(covered_x, chunk.y, FrameCell::Covered);
+            }
+            x += width;
+        }
+    }
+    pub fn add_boundary(&mut self, x: usize, y: usize, boundary: String) {
+        self.set(x, y, FrameCell::Boundary(boundary));
+    }
+    /// The output that brings the clients up to date with this frame
+    pub fn render_changes(&mut self) -> String {
+        let mut output = String::new();
+        let mut character_styles = with_explicit_resets(CharacterStyles::new());
+        let mut link = None;
+        for y in 0..self.rows {
+            // where the cursor of the clients is after the last character drawn on this line
+            let mut cursor_x = None;
+            for x in 0..self.columns {
+                let index = y * self.columns + x;
+                if !self.changed[index] {
+                    continue;
+                }
+                self.changed[index] = false;
+                if output.is_empty() {
+                    output.push_str("\u{1b}[m");
+                }
+                match &self.cells[index] {
+                    FrameCell::Character(character) => {
+                        if cursor_x != Some(x) {
+                            output.push_str(&format!("\u{1b}[{};{}H", y + 1, x + 1));
+                        }
+                        push_styles_diff(&mut output, &mut character_styles, &character.styles);
+                        push_link_diff(&mut output, &mut link, &character.link);
+                        character.push_to(&mut output);
+                        cursor_x = Some(x + character.width.max(1));
+                    }
+                    FrameCell::Boundary(boundary) => {
+                        if cursor_x != Some(x) {
+                            output.push_str(&format!("\u{1b}[{};{}H", y + 1, x + 1));
+                        }
+                        push_link_diff(&mut output, &mut link, &None);
+                        // boundaries set their own color and reset the styles after it
+                        let reset_styles = with_explicit_resets(CharacterStyles::new());
+                        push_styles_diff(&mut output, &mut character_styles, &reset_styles);
+                        output.push_str(boundary);
+                        cursor_x = Some(x + 1);
+                    }
+                    FrameCell::Covered | FrameCell::Unknown => {}
+                }
+            }
+        }
+        push_link_diff(&mut output, &mut link, &None);
+        output
+    }
+    fn set(&mut self, x: usize, y: usize, cell: FrameCell) {
+        if x >= self.columns || y >= self.rows {
+            return;
+        }
+        let index = y * self.columns + x;
+        if self.cells[index] == cell {
+            return;
+        }
+        // a wide character loses both of its halves when one of them is drawn over, like in
+        // terminals, so whatever is left of it is replaced by a space
+        let blank = || FrameCell::Character(FrameCharacter::new(' ', 1, CharacterStyles::new()));
+        if self.cells[index] == FrameCell::Covered && cell != FrameCell::Covered && x > 0 {
+            self.replace(index - 1, blank());
+        }
+        if let FrameCell::Character(character) = &self.cells[index] {
+            if character.width > 1
+                && x + 1 < self.columns
+                && self.cells[index + 1] == FrameCell::Covered
+            {
+                self.replace(index + 1, blank());
+            }
+        }
+        self.replace(index, cell);
+    }
+    fn replace(&mut self, index: usize, cell: FrameCell) {
+        if self.cells[index] != cell {
+            self.cells[index] = cell;
+            self.changed[index] = true;
+        }
+    }
+}
+
+/// The styles with the attributes that are not set explicitly reset, as characters that do not
+/// set them are drawn without them
+fn with_explicit_resets(styles: CharacterStyles) -> CharacterStyles {
+    let reset = |code: Option<AnsiCode>| code.or(Some(AnsiCode::Reset));
+    CharacterStyles {
+        foreground: reset(styles.foreground),
+        background: reset(styles.background),
+        strike: reset(styles.strike),
+        hidden: reset(styles.hidden),
+        reverse: reset(styles.reverse),
+        slow_blink: reset(styles.slow_blink),
+        fast_blink: reset(styles.fast_blink),
+        underline: reset(styles.underline),
+        bold: reset(styles.bold),
+        dim: reset(styles.dim),
+        italic: reset(styles.italic),
+    }
+}
+
+fn push_styles_diff(
+    output: &mut String,
+    current_styles: &mut CharacterStyles,
+    new_styles: &CharacterStyles,
+) {
+    if current_styles != new_styles {
+        if let Some(styles_diff) = current_styles.update_and_return_diff(new_styles) {
+            output.push_str(&styles_diff.to_string());
+        }
+    }
+}
+
+fn push_link_diff(output: &mut String, current_link: &mut Option<Rc<str>>, link: &Option<Rc<str>>) {
+    if current_link != link {
+        output.push_str(link.as_deref().unwrap_or(END_LINK));
+        *current_link = link.clone();
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/frame_tests.rs"]
+mod frame_tests;
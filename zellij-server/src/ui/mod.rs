@@ -1,3 +1,4 @@
 pub mod boundaries;
+pub mod frame;
 pub mod pane_resizer;
 pub mod pane_resizer_beta;
@@ -203,7 +203,8 @@ impl<'a> PaneResizer<'a> {
     fn reduce_pane_height_up(&mut self, id: &PaneId, count: usize) {
         let pane = self.panes.get_mut(id).unwrap();
         pane.reduce_height_up(count);
-        if let PaneId::Terminal(pid) = id {
+        // read-only panes have no pty
+        if let (PaneId::Terminal(pid), false) = (id, pane.read_only()) {
             self.os_api
                 .set_terminal_size_using_fd(*pid, pane.columns() as u16, pane.rows() as u16);
         }
@@ -211,7 +212,8 @@ impl<'a> PaneResizer<'a> {
     fn increase_pane_height_down(&mut self, id: &PaneId, count: usize) {
         let pane = self.panes.get_mut(id).unwrap();
         pane.increase_height_down(count);
-        if let PaneId::Terminal(pid) = pane.pid() {
+        // read-only panes have no pty
+        if let (PaneId::Terminal(pid), false) = (pane.pid(), pane.read_only()) {
             self.os_api
                 .set_terminal_size_using_fd(pid, pane.columns() as u16, pane.rows() as u16);
         }
@@ -219,7 +221,8 @@ impl<'a> PaneResizer<'a> {
     fn increase_pane_width_right(&mut self, id: &PaneId, count: usize) {
         let pane = self.panes.get_mut(id).unwrap();
         pane.increase_width_right(count);
-        if let PaneId::Terminal(pid) = pane.pid() {
+        // read-only panes have no pty
+        if let (PaneId::Terminal(pid), false) = (pane.pid(), pane.read_only()) {
             self.os_api
                 .set_terminal_size_using_fd(pid, pane.columns() as u16, pane.rows() as u16);
         }
@@ -227,7 +230,8 @@ impl<'a> PaneResizer<'a> {
     fn reduce_pane_width_left(&mut self, id: &PaneId, count: usize) {
         let pane = self.panes.get_mut(id).unwrap();
         pane.reduce_width_left(count);
-        if let PaneId::Terminal(pid) = pane.pid() {
+        // read-only panes have no pty
+        if let (PaneId::Terminal(pid), false) = (pane.pid(), pane.read_only()) {
             self.os_api
                 .set_terminal_size_using_fd(pid, pane.columns() as u16, pane.rows() as u16);
         }
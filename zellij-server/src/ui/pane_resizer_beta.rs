@@ -196,7 +196,8 @@ impl<'a> PaneResizer<'a> {
                     ..pane.position_and_size()
                 }),
             }
-            if let PaneId::Terminal(pid) = pane.pid() {
+            // read-only panes have no pty
+            if let (PaneId::Terminal(pid), false) = (pane.pid(), pane.read_only()) {
                 self.os_api.set_terminal_size_using_fd(
                     pid,
                     pane.columns() as u16,
@@ -0,0 +1,127 @@
+use super::*;
+
+fn chunk(x: usize, y: usize, text: &str) -> FrameChunk {
+    FrameChunk {
+        x,
+        y,
+        characters: text
+            .chars()
+            .map(|character| FrameCharacter::new(character, 1, CharacterStyles::new()))
+            .collect(),
+    }
+}
+
+#[test]
+fn only_changed_cells_are_rendered() {
+    let mut frame = Frame::new(5, 2);
+    frame.add_chunk(chunk(0, 0, "abc"), 5);
+    frame.add_chunk(chunk(0, 1, "def"), 5);
+    assert_eq!(
+        frame.render_changes(),
+        "\u{1b}[m\u{1b}[1;1Habc\u{1b}[2;1Hdef"
+    );
+
+    frame.add_chunk(chunk(0, 0, "abc"), 5);
+    frame.add_chunk(chunk(0, 1, "dxy"), 5);
+    assert_eq!(frame.render_changes(), "\u{1b}[m\u{1b}[2;2Hxy");
+    assert_eq!(frame.render_changes(), "");
+}
+
+#[test]
+fn chunks_are_clipped() {
+    let mut frame = Frame::new(5, 1);
+    frame.add_chunk(chunk(1, 0, "abcde"), 3);
+    assert_eq!(frame.render_changes(), "\u{1b}[m\u{1b}[1;2Hab");
+}
+
+#[test]
+fn boundaries_are_rendered_when_they_change() {
+    let mut frame = Frame::new(3, 1);
+    frame.add_boundary(1, 0, String::from("│"));
+    assert_eq!(frame.render_changes(), "\u{1b}[m\u{1b}[1;2H│");
+
+    frame.add_boundary(1, 0, String::from("│"));
+    assert_eq!(frame.render_changes(), "");
+
+    frame.add_boundary(1, 0, String::from("┼"));
+    assert_eq!(frame.render_changes(), "\u{1b}[m\u{1b}[1;2H┼");
+}
+
+#[test]
+fn drawing_over_half_of_a_wide_character_clears_it() {
+    let mut frame = Frame::new(4, 1);
+    let wide_character = FrameCharacter::new('中', 2, CharacterStyles::new());
+    frame.add_chunk(
+        FrameChunk {
+            x: 0,
+            y: 0,
+            characters: vec![wide_character],
+        },
+        4,
+    );
+    assert_eq!(frame.render_changes(), "\u{1b}[m\u{1b}[1;1H中");
+
+    frame.add_chunk(chunk(1, 0, "a"), 4);
+    assert_eq!(frame.render_changes(), "\u{1b}[m\u{1b}[1;1H a");
+}
+
+#[test]
+fn invalidated_cells_are_rendered_again() {
+    let mut frame = Frame::new(4, 2);
+    frame.add_chunk(chunk(0, 0, "ab"), 4);
+    frame.add_chunk(chunk(0, 1, "cd"), 4);
+    frame.render_changes();
+
+    frame.invalidate_area(1, 1, 1, 1);
+    frame.add_chunk(chunk(0, 0, "ab"), 4);
+    frame.add_chunk(chunk(0, 1, "cd"), 4);
+    assert_eq!(frame.render_changes(), "\u{1b}[m\u{1b}[2;2Hd");
+
+    frame.invalidate();
+    frame.add_chunk(chunk(0, 0, "ab"), 4);
+    assert_eq!(frame.render_changes(), "\u{1b}[m\u{1b}[1;1Hab");
+}
+
+#[test]
+fn areas_are_drawn_again_after_something_was_drawn_over_them() {
+    let mut frame = Frame::new(4, 2);
+    frame.add_chunk(chunk(0, 0, "ab"), 4);
+    frame.add_chunk(chunk(0, 1, "cd"), 4);
+    frame.render_changes();
+    assert!(!frame.area_changed(0, 0, 4, 2));
+
+    frame.redraw_area(1, 1, 5, 5);
+    assert!(frame.area_changed(0, 1, 2, 1));
+    assert!(!frame.area_changed(0, 0, 4, 1));
+    assert_eq!(frame.render_changes(), "\u{1b}[m\u{1b}[2;2Hd");
+}
+
+#[test]
+fn redrawing_the_right_half_of_a_wide_character_draws_all_of_it() {
+    let mut frame = Frame::new(4, 1);
+    let wide_character = FrameCharacter::new('中', 2, CharacterStyles::new());
+    frame.add_chunk(
+        FrameChunk {
+            x: 0,
+            y: 0,
+            characters: vec![wide_character],
+        },
+        4,
+    );
+    frame.render_changes();
+    frame.redraw_area(1, 0, 1, 1);
+    assert_eq!(frame.render_changes(), "\u{1b}[m\u{1b}[1;1H中");
+}
+
+#[test]
+fn links_are_ended_after_their_characters() {
+    let link: Rc<str> = Rc::from("\u{1b}]8;;https://zellij.dev\u{1b}\\");
+    let mut linked_chunk = chunk(0, 0, "ab");
+    linked_chunk.characters[0].link = Some(link.clone());
+    let mut frame = Frame::new(3, 1);
+    frame.add_chunk(linked_chunk, 3);
+    assert_eq!(
+        frame.render_changes(),
+        format!("\u{1b}[m\u{1b}[1;1H{}a{}b", link, END_LINK)
+    );
+}
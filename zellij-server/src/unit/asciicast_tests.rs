@@ -0,0 +1,154 @@
+use super::*;
+use std::fs;
+use std::io::Cursor;
+use std::sync::mpsc::channel;
+
+fn temp_path(file_name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}-{}", file_name, std::process::id()))
+}
+
+fn read_events(path: &Path) -> Vec<Value> {
+    let recording = fs::read_to_string(path).unwrap();
+    fs::remove_file(path).unwrap();
+    recording
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn record(file_name: &str, record: impl FnOnce(&mut AsciicastRecorder)) -> Vec<Value> {
+    let path = temp_path(file_name);
+    let mut recorder = AsciicastRecorder::new(&path, 80, 24).unwrap();
+    record(&mut recorder);
+    drop(recorder);
+    read_events(&path)
+}
+
+#[test]
+fn recording_starts_with_header() {
+    let lines = record("recording_starts_with_header", |_| {});
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["version"], 2);
+    assert_eq!(lines[0]["width"], 80);
+    assert_eq!(lines[0]["height"], 24);
+}
+
+#[test]
+fn output_is_recorded() {
+    let lines = record("output_is_recorded", |recorder| {
+        recorder.output(1.5, b"\x1b[31mred\r\n").unwrap();
+    });
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1], json!([1.5, "o", "\u{1b}[31mred\r\n"]));
+}
+
+#[test]
+fn characters_split_between_reads_are_recorded_whole() {
+    let lines = record("characters_split_between_reads", |recorder| {
+        recorder.output(0.0, &[b'a', 0xc3]).unwrap();
+        recorder.output(0.5, &[0xa9]).unwrap();
+    });
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[1][2], "a");
+    assert_eq!(lines[2][2], "é");
+}
+
+#[test]
+fn resizes_are_recorded() {
+    let lines = record("resizes_are_recorded", |recorder| {
+        recorder.resize(2.0, 100, 30).unwrap();
+    });
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1], json!([2.0, "r", "100x30"]));
+}
+
+#[test]
+fn pane_is_recorded_by_a_thread_of_its_own() {
+    let path = temp_path("pane_is_recorded_by_a_thread");
+    let (failures, failure_receiver) = channel();
+    let mut recording = PaneRecording::new(&path, 80, 24, move |e| {
+        let _ = failures.send(e);
+    })
+    .unwrap();
+    recording.record(b"first", Some((80, 24))).unwrap();
+    recording.record(b"second", Some((100, 30))).unwrap();
+    recording.record(b"third", None).unwrap();
+    drop(recording);
+    // the thread writes everything it was handed before it ends
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while fs::read_to_string(&path).unwrap().lines().count() < 5 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let events: Vec<Value> = read_events(&path)[1..]
+        .iter()
+        .map(|event| Value::Array(event.as_array().unwrap()[1..].to_vec()))
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            json!(["o", "first"]),
+            json!(["r", "100x30"]),
+            json!(["o", "second"]),
+            json!(["o", "third"]),
+        ]
+    );
+    assert!(failure_receiver.try_recv().is_err());
+}
+
+#[test]
+fn output_of_a_recording_is_read() {
+    let recording = concat!(
+        "{\"version\": 2, \"width\": 80, \"height\": 24}\n",
+        "[0.5, \"o\", \"first\"]\n",
+        "[1.0, \"r\", \"100x30\"]\n",
+        "not an event\n",
+        "[1.5, \"o\", \"second\"]\n",
+    );
+    let output: Vec<RecordedOutput> = recorded_output(Cursor::new(recording)).unwrap().collect();
+    assert_eq!(
+        output,
+        vec![
+            RecordedOutput {
+                time: 0.5,
+                text: "first".into()
+            },
+            RecordedOutput {
+                time: 1.5,
+                text: "second".into()
+            },
+        ]
+    );
+}
+
+#[test]
+fn files_that_are_not_recordings_are_not_read() {
+    for file in &["", "[0.5, \"o\", \"no header\"]\n", "{\"version\": 1}\n"] {
+        assert!(
+            recorded_output(Cursor::new(file)).is_err(),
+            "{:?} was read as a recording",
+            file
+        );
+    }
+}
+
+#[test]
+fn replay_delay_is_scaled_by_the_speed() {
+    assert_eq!(replay_delay(3.0, 1.0), Duration::from_secs(3));
+    assert_eq!(replay_delay(3.0, 2.0), Duration::from_millis(1500));
+    assert_eq!(replay_delay(3.0, 0.5), Duration::from_secs(6));
+    assert_eq!(replay_delay(3.0, f64::INFINITY), Duration::from_secs(0));
+}
+
+#[test]
+fn replay_delay_is_clamped() {
+    let slowest = replay_delay(3.0, MIN_REPLAY_SPEED);
+    for speed in &[0.0, 1e-300, -1.0, f64::NAN] {
+        assert_eq!(replay_delay(3.0, *speed), slowest, "speed {}", speed);
+    }
+    for time in &[-1.0, f64::NAN, f64::NEG_INFINITY] {
+        assert_eq!(replay_delay(*time, 1.0), Duration::from_secs(0));
+    }
+    let longest = Duration::from_secs_f64(MAX_REPLAY_TIME);
+    assert_eq!(replay_delay(1e300, 1.0), longest);
+    assert_eq!(replay_delay(f64::INFINITY, 1e-300), longest);
+}
@@ -0,0 +1,62 @@
+use super::*;
+use std::fs;
+
+/// The working directory and the value of `$ZELLIJ_TEST_VARIABLE` the command runs with
+fn run_in_terminal_environment(cmd: RunCommand) -> (PathBuf, String) {
+    let output = command_for(cmd).output().unwrap();
+    let output = String::from_utf8(output.stdout).unwrap();
+    let mut lines = output.lines();
+    let cwd = PathBuf::from(lines.next().unwrap());
+    let variable = lines.next().unwrap_or_default().to_string();
+    (cwd, variable)
+}
+
+fn print_cwd_and_variable(cwd: Option<PathBuf>, env: HashMap<String, String>) -> RunCommand {
+    RunCommand {
+        command: PathBuf::from("sh"),
+        args: vec![
+            "-c".into(),
+            "pwd -P; printf '%s' \"$ZELLIJ_TEST_VARIABLE\"".into(),
+        ],
+        cwd,
+        env,
+    }
+}
+
+#[test]
+fn command_runs_in_its_cwd_with_its_environment() {
+    let cwd = env::temp_dir().canonicalize().unwrap();
+    let mut env = HashMap::new();
+    env.insert("ZELLIJ_TEST_VARIABLE".to_string(), "value".to_string());
+    let (command_cwd, variable) =
+        run_in_terminal_environment(print_cwd_and_variable(Some(cwd.clone()), env));
+    assert_eq!(command_cwd, cwd);
+    assert_eq!(variable, "value");
+}
+
+#[test]
+fn command_without_cwd_runs_in_the_server_cwd() {
+    let (command_cwd, variable) =
+        run_in_terminal_environment(print_cwd_and_variable(None, HashMap::new()));
+    assert_eq!(
+        command_cwd,
+        env::current_dir().unwrap().canonicalize().unwrap()
+    );
+    assert_eq!(variable, "");
+}
+
+#[test]
+fn command_runs_in_the_server_cwd_if_its_cwd_is_not_a_directory() {
+    let file = env::temp_dir().join(format!("not_a_directory-{}", std::process::id()));
+    fs::write(&file, "").unwrap();
+    let missing_directory = env::temp_dir().join(format!("missing-{}", std::process::id()));
+    for cwd in vec![file.clone(), missing_directory] {
+        let (command_cwd, _) =
+            run_in_terminal_environment(print_cwd_and_variable(Some(cwd), HashMap::new()));
+        assert_eq!(
+            command_cwd,
+            env::current_dir().unwrap().canonicalize().unwrap()
+        );
+    }
+    fs::remove_file(&file).unwrap();
+}
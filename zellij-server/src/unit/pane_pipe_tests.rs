@@ -0,0 +1,115 @@
+use super::*;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+fn temp_path(file_name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}", file_name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// Reads the file once the thread of the pipe wrote `expected` to it, or after a few seconds
+fn read_piped(path: &Path, expected: &str) -> String {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let piped = fs::read_to_string(path).unwrap_or_default();
+        if piped == expected || Instant::now() > deadline {
+            let _ = fs::remove_file(path);
+            return piped;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn pipe_with_failures(
+    target: &PipeTarget,
+    strip_ansi: bool,
+    cwd: Option<PathBuf>,
+) -> (PanePipe, Receiver<io::Error>) {
+    let (failures, failure_receiver) = channel();
+    let pane_pipe = PanePipe::new(target, strip_ansi, cwd, move |e| {
+        let _ = failures.send(e);
+    })
+    .unwrap();
+    (pane_pipe, failure_receiver)
+}
+
+fn pipe_to_file(file_name: &str, strip_ansi: bool, output: &[&[u8]], expected: &str) -> String {
+    let path = temp_path(file_name);
+    let (pane_pipe, _failures) =
+        pipe_with_failures(&PipeTarget::File(path.clone()), strip_ansi, None);
+    for bytes in output {
+        pane_pipe.write(bytes).unwrap();
+    }
+    read_piped(&path, expected)
+}
+
+#[test]
+fn pipe_raw_output() {
+    let output = "\u{1b}[31mred\u{1b}[m\r\nplain\r\n";
+    let piped = pipe_to_file("pipe_raw_output", false, &[output.as_bytes()], output);
+    assert_eq!(piped, output);
+}
+
+#[test]
+fn pipe_stripped_output() {
+    // split in the middle of an escape sequence
+    let expected = "red\ntab\there\n";
+    let piped = pipe_to_file(
+        "pipe_stripped_output",
+        true,
+        &[b"\x1b[31mred\x1b", b"[m\r\n\x1b]0;title\x07tab\there\r\n"],
+        expected,
+    );
+    assert_eq!(piped, expected);
+}
+
+#[test]
+fn pipe_to_a_command_in_the_cwd_of_the_pane() {
+    let path = temp_path("pipe_to_a_command");
+    let file_name = path.file_name().unwrap().to_str().unwrap();
+    let target = PipeTarget::Command(format!("cat > {}", file_name));
+    let cwd = Some(path.parent().unwrap().to_path_buf());
+    let (pane_pipe, failures) = pipe_with_failures(&target, true, cwd);
+    pane_pipe.write(b"\x1b[1mfirst\x1b[m\r\n").unwrap();
+    pane_pipe.write(b"second\r\n").unwrap();
+    // the command gets to the end of its input
+    drop(pane_pipe);
+    assert_eq!(read_piped(&path, "first\nsecond\n"), "first\nsecond\n");
+    assert!(failures.try_recv().is_err());
+}
+
+#[test]
+fn failures_to_write_are_reported_and_stop_the_pipe() {
+    let target = PipeTarget::Command("exit 0".into());
+    let (pane_pipe, failures) = pipe_with_failures(&target, false, None);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let failure = loop {
+        let _ = pane_pipe.write(b"output\r\n");
+        if let Ok(failure) = failures.recv_timeout(Duration::from_millis(10)) {
+            break failure;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "writing to the command never failed"
+        );
+    };
+    assert_eq!(failure.kind(), io::ErrorKind::BrokenPipe);
+    assert!(matches!(
+        pane_pipe.write(b"output\r\n"),
+        Err(TrySendError::Disconnected(_))
+    ));
+}
+
+#[test]
+fn pipe_that_does_not_keep_up_overflows() {
+    // never reads its input
+    let target = PipeTarget::Command("sleep 2".into());
+    let (pane_pipe, _failures) = pipe_with_failures(&target, false, None);
+    let chunk = vec![b'a'; 65536];
+    let overflowed = (0..MAX_PENDING_WRITES * 2)
+        .any(|_| matches!(pane_pipe.write(&chunk), Err(TrySendError::Full(_))));
+    assert!(overflowed);
+}
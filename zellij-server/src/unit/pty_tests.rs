@@ -0,0 +1,316 @@
+use super::*;
+use crate::panes::{Grid, DEFAULT_SCROLL_BUFFER_SIZE};
+use crate::zellij_tile::data::Palette;
+use std::io;
+use zellij_utils::{
+    channels::{self, SenderWithContext},
+    errors::ErrorContext,
+    interprocess::local_socket::LocalSocketStream,
+    ipc::{ClientId, ClientToServerMsg, IpcReceiverWithContext, ServerToClientMsg},
+    nix,
+};
+
+#[derive(Clone, Default)]
+struct FakeInputOutput {
+    /// The working directory of every terminal
+    cwd: Option<PathBuf>,
+    spawned_terminals: Arc<Mutex<Vec<Option<TerminalAction>>>>,
+}
+
+impl FakeInputOutput {
+    fn with_cwd(cwd: &str) -> Self {
+        FakeInputOutput {
+            cwd: Some(PathBuf::from(cwd)),
+            ..Default::default()
+        }
+    }
+    /// The command and working directory each terminal was spawned with
+    fn spawned_commands(&self) -> Vec<Option<(PathBuf, Option<PathBuf>)>> {
+        self.spawned_terminals
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|terminal_action| match terminal_action {
+                Some(TerminalAction::RunCommand(command)) => {
+                    Some((command.command.clone(), command.cwd.clone()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl ServerOsApi for FakeInputOutput {
+    fn set_terminal_size_using_fd(&self, _fd: RawFd, _cols: u16, _rows: u16) {}
+    fn spawn_terminal(&self, terminal_action: Option<TerminalAction>) -> (RawFd, Pid) {
+        let mut spawned_terminals = self.spawned_terminals.lock().unwrap();
+        spawned_terminals.push(terminal_action);
+        let id = spawned_terminals.len() as RawFd;
+        (id, Pid::from_raw(id))
+    }
+    fn read_from_tty_stdout(&self, _fd: RawFd, _buf: &mut [u8]) -> Result<usize, nix::Error> {
+        unimplemented!()
+    }
+    fn async_file_reader(&self, _fd: RawFd) -> Box<dyn AsyncReader> {
+        unimplemented!()
+    }
+    fn write_to_tty_stdin(&self, _fd: RawFd, _buf: &[u8]) -> Result<usize, nix::Error> {
+        unimplemented!()
+    }
+    fn tcdrain(&self, _fd: RawFd) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn box_clone(&self) -> Box<dyn ServerOsApi> {
+        Box::new(self.clone())
+    }
+    fn force_kill(&self, _pid: Pid) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn kill(&self, _pid: Pid) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn send_to_client(&self, _client_id: ClientId, _msg: ServerToClientMsg) -> io::Result<()> {
+        unimplemented!()
+    }
+    fn new_client(
+        &self,
+        _client_id: ClientId,
+        _stream: LocalSocketStream,
+    ) -> IpcReceiverWithContext<ClientToServerMsg> {
+        unimplemented!()
+    }
+    fn remove_client(&self, _client_id: ClientId) {
+        unimplemented!()
+    }
+    fn load_palette(&self) -> Palette {
+        unimplemented!()
+    }
+    fn get_cwd(&self, _fd: RawFd) -> Option<PathBuf> {
+        self.cwd.clone()
+    }
+}
+
+fn create_pty(os_input: &FakeInputOutput, inherit_cwd: bool) -> Pty {
+    let bus = Bus::new(
+        vec![],
+        None,
+        None,
+        None,
+        None,
+        Some(Box::new(os_input.clone())),
+    );
+    Pty::new(bus, false, inherit_cwd, None)
+}
+
+/// A pty whose instructions to the screen thread are received by the returned receiver
+fn create_pty_with_screen(
+    os_input: &FakeInputOutput,
+) -> (Pty, channels::Receiver<(ScreenInstruction, ErrorContext)>) {
+    let mut pty = create_pty(os_input, false);
+    let (to_screen, screen_receiver) = channels::unbounded();
+    pty.bus.senders.to_screen = Some(SenderWithContext::new(to_screen));
+    (pty, screen_receiver)
+}
+
+fn shell() -> Option<TerminalAction> {
+    Some(TerminalAction::RunCommand(RunCommand {
+        command: PathBuf::from("sh"),
+        ..Default::default()
+    }))
+}
+
+fn command_in(command: &str, cwd: Option<&str>) -> Run {
+    Run::Command(RunCommand {
+        command: PathBuf::from(command),
+        cwd: cwd.map(PathBuf::from),
+        ..Default::default()
+    })
+}
+
+/// A layout of a pane running the default shell, one running `htop` and one running
+/// `tail` in its own directory
+fn layout_of_three_panes() -> Layout {
+    let pane = |run: Option<Run>| Layout {
+        run,
+        ..Default::default()
+    };
+    Layout {
+        parts: vec![
+            pane(None),
+            pane(Some(command_in("htop", None))),
+            pane(Some(command_in("tail", Some("/var/log")))),
+        ],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn command_cwd_is_kept() {
+    let terminal_action = Some(TerminalAction::RunCommand(RunCommand {
+        command: PathBuf::from("htop"),
+        cwd: Some(PathBuf::from("/var/log")),
+        ..Default::default()
+    }));
+    match with_cwd(terminal_action, Some(PathBuf::from("/home"))) {
+        Some(TerminalAction::RunCommand(command)) => {
+            assert_eq!(command.cwd, Some(PathBuf::from("/var/log")))
+        }
+        terminal_action => panic!("unexpected terminal action {:?}", terminal_action),
+    }
+}
+
+#[test]
+fn command_without_cwd_runs_in_the_given_cwd() {
+    match with_cwd(shell(), Some(PathBuf::from("/home"))) {
+        Some(TerminalAction::RunCommand(command)) => {
+            assert_eq!(command.command, PathBuf::from("sh"));
+            assert_eq!(command.cwd, Some(PathBuf::from("/home")));
+        }
+        terminal_action => panic!("unexpected terminal action {:?}", terminal_action),
+    }
+    assert!(with_cwd(None, None).is_none());
+}
+
+#[test]
+fn new_terminal_inherits_the_cwd_of_the_active_terminal() {
+    let os_input = FakeInputOutput::with_cwd("/home/project");
+    let mut pty = create_pty(&os_input, true);
+    // no terminal is active yet
+    pty.spawn_layout_terminals(&layout_of_three_panes(), shell());
+    pty.active_terminal = Some(1);
+    pty.spawn_layout_terminals(&layout_of_three_panes(), shell());
+    assert_eq!(
+        os_input.spawned_commands(),
+        vec![
+            Some((PathBuf::from("sh"), None)),
+            Some((PathBuf::from("htop"), None)),
+            Some((PathBuf::from("tail"), Some(PathBuf::from("/var/log")))),
+            Some((PathBuf::from("sh"), Some(PathBuf::from("/home/project")))),
+            Some((PathBuf::from("htop"), Some(PathBuf::from("/home/project")))),
+            Some((PathBuf::from("tail"), Some(PathBuf::from("/var/log")))),
+        ]
+    );
+}
+
+#[test]
+fn new_terminal_does_not_inherit_the_cwd_if_disabled() {
+    let os_input = FakeInputOutput::with_cwd("/home/project");
+    let mut pty = create_pty(&os_input, false);
+    pty.active_terminal = Some(1);
+    pty.spawn_layout_terminals(&layout_of_three_panes(), shell());
+    assert_eq!(
+        os_input.spawned_commands(),
+        vec![
+            Some((PathBuf::from("sh"), None)),
+            Some((PathBuf::from("htop"), None)),
+            Some((PathBuf::from("tail"), Some(PathBuf::from("/var/log")))),
+        ]
+    );
+}
+
+#[test]
+fn run_instruction_uses_the_current_cwd_of_the_terminal() {
+    let os_input = FakeInputOutput::with_cwd("/home/project");
+    let mut pty = create_pty(&os_input, false);
+    let ids = pty.spawn_layout_terminals(&layout_of_three_panes(), shell());
+    let runs: Vec<Option<(PathBuf, Option<PathBuf>)>> = ids
+        .iter()
+        .map(|id| match pty.run_instruction(*id) {
+            Some(Run::Command(command)) => Some((command.command, command.cwd)),
+            _ => None,
+        })
+        .collect();
+    let in_project = Some(PathBuf::from("/home/project"));
+    assert_eq!(
+        runs,
+        vec![
+            Some((PathBuf::from("sh"), in_project.clone())),
+            Some((PathBuf::from("htop"), in_project.clone())),
+            Some((PathBuf::from("tail"), in_project)),
+        ]
+    );
+}
+
+#[test]
+fn scrollback_file_is_removed_once_dropped() {
+    let file = write_scrollback_file(1, "line 1\nline 2\n").unwrap();
+    let path = file.0.clone();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "line 1\nline 2\n");
+    let other_file = write_scrollback_file(1, "line 1\n").unwrap();
+    assert_ne!(
+        other_file.0, path,
+        "editing the scrollback again does not replace the file"
+    );
+    drop(file);
+    assert!(!path.exists());
+    assert!(other_file.0.exists());
+}
+
+#[test]
+fn output_is_parsed_into_the_grid_once_it_is_shared() {
+    let pty = create_pty(&FakeInputOutput::default(), false);
+    let mut grid_feeder = pty.grid_feeder(1, b"resurrected\r\n".to_vec());
+    assert!(grid_feeder.feed(b"before ").is_empty());
+    assert!(grid_feeder.is_waiting_for_grid());
+
+    let grid = Grid::new(5, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    let grid = Arc::new(Mutex::new(grid));
+    pty.pane_grids.lock().unwrap().insert(1, grid.clone());
+    // a request for the position of the cursor
+    let answers = grid_feeder.feed(b"after\x1b[6n");
+    assert!(!grid_feeder.is_waiting_for_grid());
+    assert_eq!(answers, vec![b"\x1b[2;13R".to_vec()]);
+    let grid = grid.lock().unwrap();
+    assert!(grid.dump_screen().starts_with("resurrected\nbefore after"));
+    assert!(grid.should_render);
+}
+
+#[test]
+fn recording_is_replayed_in_a_read_only_pane() {
+    let path = std::env::temp_dir().join(format!("replayed-recording-{}", process::id()));
+    let recording = concat!(
+        "{\"version\": 2, \"width\": 80, \"height\": 24}\n",
+        "[0.0, \"o\", \"first \"]\n",
+        "[0.5, \"i\", \"typed\"]\n",
+        "[1.0, \"o\", \"second\"]\n",
+    );
+    fs::write(&path, recording).unwrap();
+    let (mut pty, screen_receiver) = create_pty_with_screen(&FakeInputOutput::default());
+    // a hundred times faster than it was recorded
+    pty.replay_recording(&path, 100.0);
+    let id = match screen_receiver.recv() {
+        Ok((ScreenInstruction::NewReadOnlyPane(PaneId::Terminal(id)), _)) => id,
+        _ => panic!("no pane was opened for the replay"),
+    };
+
+    let grid = Grid::new(5, 20, Palette::default(), DEFAULT_SCROLL_BUFFER_SIZE);
+    let grid = Arc::new(Mutex::new(grid));
+    pty.pane_grids.lock().unwrap().insert(id, grid.clone());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !grid
+        .lock()
+        .unwrap()
+        .dump_screen()
+        .starts_with("first second")
+    {
+        assert!(Instant::now() < deadline, "the recording was not replayed");
+        thread::sleep(Duration::from_millis(10));
+    }
+    // there is no terminal to close
+    pty.close_pane(PaneId::Terminal(id));
+    assert!(pty.replays.is_empty());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn files_that_cannot_be_replayed_are_reported() {
+    let (mut pty, screen_receiver) = create_pty_with_screen(&FakeInputOutput::default());
+    pty.replay_recording(Path::new("/does/not/exist.cast"), 1.0);
+    match screen_receiver.try_recv() {
+        Ok((ScreenInstruction::ShowNotice(notice), _)) => {
+            assert!(notice.starts_with("Failed to replay /does/not/exist.cast"))
+        }
+        _ => panic!("the user was not told the recording could not be replayed"),
+    }
+    assert!(pty.replays.is_empty());
+}
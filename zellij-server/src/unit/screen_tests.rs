@@ -1,22 +1,24 @@
-use super::{Screen, ScreenInstruction};
+use super::{RenderThrottle, Screen, ScreenInstruction};
 use crate::zellij_tile::data::{ModeInfo, Palette};
 use crate::{
     os_input_output::{AsyncReader, Pid, ServerOsApi},
+    panes::{DEFAULT_SCROLL_BUFFER_SIZE, DEFAULT_WORD_SEPARATORS},
     thread_bus::Bus,
     SessionState,
 };
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use zellij_utils::{input::command::TerminalAction, pane_size::PositionAndSize};
 
 use std::os::unix::io::RawFd;
+use std::path::PathBuf;
 
 use zellij_utils::ipc::ClientAttributes;
 use zellij_utils::nix;
 
 use zellij_utils::{
-    errors::ErrorContext,
     interprocess::local_socket::LocalSocketStream,
-    ipc::{ClientToServerMsg, ServerToClientMsg},
+    ipc::{ClientId, ClientToServerMsg, IpcReceiverWithContext, ServerToClientMsg},
 };
 
 #[derive(Clone)]
@@ -50,25 +52,23 @@ impl ServerOsApi for FakeInputOutput {
     fn kill(&self, _pid: Pid) -> Result<(), nix::Error> {
         unimplemented!()
     }
-    fn recv_from_client(&self) -> (ClientToServerMsg, ErrorContext) {
+    fn send_to_client(&self, _client_id: ClientId, _msg: ServerToClientMsg) -> std::io::Result<()> {
         unimplemented!()
     }
-    fn send_to_client(&self, _msg: ServerToClientMsg) {
+    fn new_client(
+        &self,
+        _client_id: ClientId,
+        _stream: LocalSocketStream,
+    ) -> IpcReceiverWithContext<ClientToServerMsg> {
         unimplemented!()
     }
-    fn add_client_sender(&self) {
+    fn remove_client(&self, _client_id: ClientId) {
         unimplemented!()
     }
-    fn send_to_temp_client(&self, _msg: ServerToClientMsg) {
-        unimplemented!()
-    }
-    fn remove_client_sender(&self) {
+    fn load_palette(&self) -> Palette {
         unimplemented!()
     }
-    fn update_receiver(&mut self, _stream: LocalSocketStream) {
-        unimplemented!()
-    }
-    fn load_palette(&self) -> Palette {
+    fn get_cwd(&self, _fd: RawFd) -> Option<PathBuf> {
         unimplemented!()
     }
 }
@@ -81,8 +81,17 @@ fn create_new_screen(position_and_size: PositionAndSize) -> Screen {
     client_attributes.position_and_size = position_and_size;
     let max_panes = None;
     let mode_info = ModeInfo::default();
-    let session_state = Arc::new(RwLock::new(SessionState::Attached));
-    Screen::new(bus, &client_attributes, max_panes, mode_info, session_state)
+    let session_state = Arc::new(RwLock::new(SessionState::new()));
+    session_state.write().unwrap().new_client(1);
+    Screen::new(
+        bus,
+        &client_attributes,
+        max_panes,
+        mode_info,
+        session_state,
+        DEFAULT_WORD_SEPARATORS.to_string(),
+        DEFAULT_SCROLL_BUFFER_SIZE,
+    )
 }
 
 #[test]
@@ -247,3 +256,48 @@ fn move_focus_right_at_right_screen_edge_changes_tab() {
         "Active tab switched to next"
     );
 }
+
+#[test]
+pub fn renders_requested_too_soon_are_coalesced_into_one() {
+    let start = Instant::now();
+    let at = |millis: u64| start + Duration::from_millis(millis);
+    let mut render_throttle = RenderThrottle::new(10);
+    assert!(render_throttle.request_render(at(0)));
+    assert!(!render_throttle.request_render(at(10)));
+    assert!(!render_throttle.request_render(at(50)));
+    assert_eq!(render_throttle.pending_render, Some(at(100)));
+    assert!(!render_throttle.deferred_render_due(at(99)));
+    assert!(render_throttle.deferred_render_due(at(100)));
+    assert!(
+        !render_throttle.deferred_render_due(at(150)),
+        "the pending renders were all done at once"
+    );
+    assert!(!render_throttle.request_render(at(150)));
+    assert!(render_throttle.request_render(at(400)));
+    assert_eq!(render_throttle.pending_render, None);
+}
+
+#[test]
+pub fn renders_are_limited_to_max_fps() {
+    let start = Instant::now();
+    let mut render_throttle = RenderThrottle::new(20);
+    let mut render_count = 0;
+    // a busy pane asking for a render every millisecond for a second
+    for millis in 0..1000 {
+        let now = start + Duration::from_millis(millis);
+        if render_throttle.deferred_render_due(now) {
+            render_count += 1;
+        }
+        if render_throttle.request_render(now) {
+            render_count += 1;
+        }
+    }
+    assert_eq!(render_count, 20);
+
+    let render_throttle = RenderThrottle::new(0);
+    assert_eq!(
+        render_throttle.interval,
+        Duration::from_secs(1),
+        "at least one render per second"
+    );
+}
@@ -0,0 +1,189 @@
+use super::*;
+use crate::os_input_output::{AsyncReader, Pid};
+use crate::zellij_tile::data::Palette;
+use std::collections::HashSet;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use zellij_utils::{
+    input::command::TerminalAction,
+    interprocess::local_socket::LocalSocketStream,
+    ipc::{ClientId, ClientToServerMsg, IpcReceiverWithContext, ServerToClientMsg},
+    nix,
+};
+
+#[derive(Clone, Default)]
+struct FakeInputOutput {
+    /// Clients whose connection is closed
+    disconnected_clients: HashSet<ClientId>,
+    sent_messages: Arc<Mutex<Vec<(ClientId, ServerToClientMsg)>>>,
+    removed_clients: Arc<Mutex<Vec<ClientId>>>,
+}
+
+impl FakeInputOutput {
+    fn with_disconnected_client(mut self, client_id: ClientId) -> Self {
+        self.disconnected_clients.insert(client_id);
+        self
+    }
+    fn clients_sent_to(&self) -> Vec<ClientId> {
+        let mut client_ids: Vec<ClientId> = self
+            .sent_messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(client_id, _)| *client_id)
+            .collect();
+        client_ids.sort_unstable();
+        client_ids
+    }
+}
+
+impl ServerOsApi for FakeInputOutput {
+    fn set_terminal_size_using_fd(&self, _fd: RawFd, _cols: u16, _rows: u16) {
+        unimplemented!()
+    }
+    fn spawn_terminal(&self, _file_to_open: Option<TerminalAction>) -> (RawFd, Pid) {
+        unimplemented!()
+    }
+    fn read_from_tty_stdout(&self, _fd: RawFd, _buf: &mut [u8]) -> Result<usize, nix::Error> {
+        unimplemented!()
+    }
+    fn async_file_reader(&self, _fd: RawFd) -> Box<dyn AsyncReader> {
+        unimplemented!()
+    }
+    fn write_to_tty_stdin(&self, _fd: RawFd, _buf: &[u8]) -> Result<usize, nix::Error> {
+        unimplemented!()
+    }
+    fn tcdrain(&self, _fd: RawFd) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn box_clone(&self) -> Box<dyn ServerOsApi> {
+        Box::new(self.clone())
+    }
+    fn force_kill(&self, _pid: Pid) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn kill(&self, _pid: Pid) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) -> io::Result<()> {
+        if self.disconnected_clients.contains(&client_id) {
+            return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+        }
+        self.sent_messages.lock().unwrap().push((client_id, msg));
+        Ok(())
+    }
+    fn new_client(
+        &self,
+        _client_id: ClientId,
+        _stream: LocalSocketStream,
+    ) -> IpcReceiverWithContext<ClientToServerMsg> {
+        unimplemented!()
+    }
+    fn remove_client(&self, client_id: ClientId) {
+        self.removed_clients.lock().unwrap().push(client_id);
+    }
+    fn load_palette(&self) -> Palette {
+        unimplemented!()
+    }
+    fn get_cwd(&self, _fd: RawFd) -> Option<PathBuf> {
+        unimplemented!()
+    }
+}
+
+fn session_state_with_clients(client_ids: &[ClientId]) -> Arc<RwLock<SessionState>> {
+    let mut session_state = SessionState::new();
+    for client_id in client_ids {
+        session_state.new_client(*client_id);
+    }
+    Arc::new(RwLock::new(session_state))
+}
+
+#[test]
+fn session_state_keeps_track_of_clients() {
+    let mut session_state = SessionState::new();
+    assert!(!session_state.has_clients());
+
+    session_state.new_client(1);
+    session_state.new_client(2);
+    session_state.new_client(2);
+    let mut client_ids = session_state.client_ids();
+    client_ids.sort_unstable();
+    assert_eq!(client_ids, vec![1, 2]);
+
+    session_state.remove_client(1);
+    assert_eq!(session_state.client_ids(), vec![2]);
+    session_state.remove_client(1);
+    assert!(session_state.has_clients());
+
+    session_state.remove_client(2);
+    assert!(!session_state.has_clients());
+}
+
+#[test]
+fn messages_are_sent_to_every_client() {
+    let os_input = FakeInputOutput::default();
+    let session_state = session_state_with_clients(&[1, 2, 3]);
+    send_to_all_clients(
+        &os_input,
+        &session_state,
+        ServerToClientMsg::Render("output".into()),
+    );
+    assert_eq!(os_input.clients_sent_to(), vec![1, 2, 3]);
+    assert!(os_input
+        .sent_messages
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|(_, msg)| matches!(msg, ServerToClientMsg::Render(output) if output == "output")));
+    assert!(os_input.removed_clients.lock().unwrap().is_empty());
+}
+
+#[test]
+fn disconnected_client_is_removed_without_affecting_the_others() {
+    let os_input = FakeInputOutput::default().with_disconnected_client(2);
+    let session_state = session_state_with_clients(&[1, 2, 3]);
+    send_to_all_clients(
+        &os_input,
+        &session_state,
+        ServerToClientMsg::Render("output".into()),
+    );
+    assert_eq!(os_input.clients_sent_to(), vec![1, 3]);
+    assert_eq!(*os_input.removed_clients.lock().unwrap(), vec![2]);
+    let mut client_ids = session_state.read().unwrap().client_ids();
+    client_ids.sort_unstable();
+    assert_eq!(client_ids, vec![1, 3]);
+
+    send_to_all_clients(
+        &os_input,
+        &session_state,
+        ServerToClientMsg::UnblockInputThread,
+    );
+    assert_eq!(os_input.clients_sent_to(), vec![1, 1, 3, 3]);
+}
+
+#[test]
+fn removing_a_client_only_affects_that_client() {
+    let os_input = FakeInputOutput::default();
+    let session_state = session_state_with_clients(&[1, 2]);
+    remove_client(&os_input, &session_state, 1);
+    assert_eq!(session_state.read().unwrap().client_ids(), vec![2]);
+    assert_eq!(*os_input.removed_clients.lock().unwrap(), vec![1]);
+    assert!(os_input.sent_messages.lock().unwrap().is_empty());
+}
+
+#[test]
+fn force_attaching_detaches_the_other_clients() {
+    let os_input = FakeInputOutput::default();
+    let session_state = session_state_with_clients(&[1, 2, 3]);
+    detach_other_clients(&os_input, &session_state, 2);
+    assert_eq!(session_state.read().unwrap().client_ids(), vec![2]);
+    assert_eq!(os_input.clients_sent_to(), vec![1, 3]);
+    assert!(os_input
+        .sent_messages
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|(_, msg)| matches!(msg, ServerToClientMsg::Exit(ExitReason::ForceDetached))));
+}
@@ -1,23 +1,28 @@
-use super::Tab;
+use super::{strip_bracketed_paste_end, Tab};
 use crate::zellij_tile::data::{ModeInfo, Palette};
 use crate::{
     os_input_output::{AsyncReader, Pid, ServerOsApi},
-    panes::PaneId,
+    panes::{PaneId, SharedGrid, DEFAULT_SCROLL_BUFFER_SIZE, DEFAULT_WORD_SEPARATORS},
+    pty::PtyInstruction,
     thread_bus::ThreadSenders,
-    SessionState,
+    ServerInstruction, SessionState,
 };
 use std::sync::{Arc, RwLock};
-use zellij_utils::pane_size::PositionAndSize;
+use zellij_utils::channels::{self, SenderWithContext};
+use zellij_utils::pane_size::{PositionAndSize, SizeInPixels};
 
 use std::os::unix::io::RawFd;
+use std::path::PathBuf;
 
-use zellij_utils::nix;
+use zellij_utils::{nix, vte};
 
 use zellij_utils::{
-    errors::ErrorContext,
-    input::command::TerminalAction,
+    input::{
+        command::TerminalAction,
+        layout::{Layout, SessionSnapshot, TabSnapshot},
+    },
     interprocess::local_socket::LocalSocketStream,
-    ipc::{ClientToServerMsg, ServerToClientMsg},
+    ipc::{ClientId, ClientToServerMsg, IpcReceiverWithContext, ServerToClientMsg},
 };
 
 struct FakeInputOutput {}
@@ -50,40 +55,50 @@ impl ServerOsApi for FakeInputOutput {
     fn kill(&self, _pid: Pid) -> Result<(), nix::Error> {
         unimplemented!()
     }
-    fn recv_from_client(&self) -> (ClientToServerMsg, ErrorContext) {
+    fn send_to_client(&self, _client_id: ClientId, _msg: ServerToClientMsg) -> std::io::Result<()> {
         unimplemented!()
     }
-    fn send_to_client(&self, _msg: ServerToClientMsg) {
+    fn new_client(
+        &self,
+        _client_id: ClientId,
+        _stream: LocalSocketStream,
+    ) -> IpcReceiverWithContext<ClientToServerMsg> {
         unimplemented!()
     }
-    fn add_client_sender(&self) {
+    fn remove_client(&self, _client_id: ClientId) {
         unimplemented!()
     }
-    fn send_to_temp_client(&self, _msg: ServerToClientMsg) {
-        unimplemented!()
-    }
-    fn remove_client_sender(&self) {
+    fn load_palette(&self) -> Palette {
         unimplemented!()
     }
-    fn update_receiver(&mut self, _stream: LocalSocketStream) {
-        unimplemented!()
-    }
-    fn load_palette(&self) -> Palette {
+    fn get_cwd(&self, _fd: RawFd) -> Option<PathBuf> {
         unimplemented!()
     }
 }
 
 fn create_new_tab(position_and_size: PositionAndSize) -> Tab {
+    create_tab(position_and_size, Some(PaneId::Terminal(1)))
+}
+
+fn create_tab(position_and_size: PositionAndSize, first_pane_id: Option<PaneId>) -> Tab {
+    let senders = ThreadSenders::default().silently_fail_on_send();
+    create_tab_with_senders(position_and_size, first_pane_id, senders)
+}
+
+fn create_tab_with_senders(
+    position_and_size: PositionAndSize,
+    first_pane_id: Option<PaneId>,
+    senders: ThreadSenders,
+) -> Tab {
     let index = 0;
     let position = 0;
     let name = String::new();
     let os_api = Box::new(FakeInputOutput {});
-    let senders = ThreadSenders::default().silently_fail_on_send();
     let max_panes = None;
-    let first_pane_id = Some(PaneId::Terminal(1));
     let mode_info = ModeInfo::default();
     let colors = Palette::default();
-    let session_state = Arc::new(RwLock::new(SessionState::Attached));
+    let session_state = Arc::new(RwLock::new(SessionState::new()));
+    session_state.write().unwrap().new_client(1);
     Tab::new(
         index,
         position,
@@ -96,6 +111,9 @@ fn create_new_tab(position_and_size: PositionAndSize) -> Tab {
         mode_info,
         colors,
         session_state,
+        None,
+        DEFAULT_WORD_SEPARATORS.to_string(),
+        DEFAULT_SCROLL_BUFFER_SIZE,
     )
 }
 
@@ -11839,3 +11857,324 @@ pub fn cannot_resize_up_when_pane_above_is_at_minimum_height() {
         "pane 2 height stayed the same"
     );
 }
+
+#[test]
+fn dump_layout_of_split_panes() {
+    let position_and_size = PositionAndSize {
+        cols: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+        ..Default::default()
+    };
+    let mut tab = create_new_tab(position_and_size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.horizontal_split(PaneId::Terminal(3));
+    let (layout, terminal_ids) = tab.dump_layout();
+    let positions = layout.position_panes_in_space(&position_and_size);
+    assert_eq!(positions.len(), 3, "the layout has a part for every pane");
+    assert_eq!(terminal_ids.len(), 3, "every pane is a terminal");
+    for ((_, position), pid) in positions.iter().zip(terminal_ids) {
+        let pane = tab
+            .panes
+            .get(&PaneId::Terminal(pid))
+            .unwrap()
+            .position_and_size();
+        assert_eq!(
+            (position.x, position.y, position.cols, position.rows),
+            (pane.x, pane.y, pane.cols, pane.rows),
+            "pane {} is placed where it was",
+            pid
+        );
+    }
+}
+
+#[test]
+fn dumped_layout_is_read_back_with_the_same_arrangement() {
+    let position_and_size = PositionAndSize {
+        cols: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+        ..Default::default()
+    };
+    let mut tab = create_new_tab(position_and_size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.horizontal_split(PaneId::Terminal(3));
+    tab.vertical_split(PaneId::Terminal(4));
+    let (layout, _) = tab.dump_layout();
+    let path = std::env::temp_dir().join(format!(
+        "dumped_layout_is_read_back-{}.yaml",
+        std::process::id()
+    ));
+    layout.write_to_path(&path).unwrap();
+    let read_layout = Layout::new(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let positions = |layout: &Layout| -> Vec<(usize, usize, usize, usize)> {
+        layout
+            .position_panes_in_space(&position_and_size)
+            .iter()
+            .map(|(_, p)| (p.x, p.y, p.cols, p.rows))
+            .collect()
+    };
+    assert_eq!(positions(&read_layout), positions(&layout));
+    assert_eq!(positions(&read_layout).len(), 4);
+}
+
+/// Writes `tab` to a session snapshot and rebuilds it from the file, like resurrecting its
+/// session does. The terminals of the resurrected tab get the ids of the original ones plus 100.
+fn resurrect_tab(tab: &Tab, position_and_size: PositionAndSize, file_name: &str) -> Tab {
+    let (layout, terminal_ids) = tab.dump_layout();
+    let snapshot = SessionSnapshot {
+        tabs: vec![TabSnapshot {
+            name: String::new(),
+            active: true,
+            layout,
+            scrollback: vec![],
+        }],
+    };
+    let path = std::env::temp_dir().join(format!("{}-{}.yaml", file_name, std::process::id()));
+    snapshot.write_to_path(&path).unwrap();
+    let mut snapshot = SessionSnapshot::from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut resurrected_tab = create_tab(position_and_size, None);
+    let new_pids = terminal_ids.iter().map(|pid| pid + 100).collect();
+    resurrected_tab.apply_layout(snapshot.tabs.remove(0).layout, new_pids, 0);
+    resurrected_tab
+}
+
+fn assert_panes_resurrected(tab: &Tab, resurrected_tab: &Tab) {
+    assert_eq!(
+        resurrected_tab.panes.len(),
+        tab.panes.len(),
+        "every pane is resurrected"
+    );
+    for (id, pane) in tab.panes.iter() {
+        let resurrected_id = match id {
+            PaneId::Terminal(pid) => PaneId::Terminal(pid + 100),
+            PaneId::Plugin(_) => unreachable!(),
+        };
+        let position = pane.position_and_size();
+        let resurrected_position = resurrected_tab
+            .panes
+            .get(&resurrected_id)
+            .unwrap()
+            .position_and_size();
+        assert_eq!(
+            (
+                resurrected_position.x,
+                resurrected_position.y,
+                resurrected_position.cols,
+                resurrected_position.rows
+            ),
+            (position.x, position.y, position.cols, position.rows),
+            "pane {:?} is resurrected where it was",
+            id
+        );
+    }
+}
+
+#[test]
+fn resurrect_single_pane() {
+    let position_and_size = PositionAndSize {
+        cols: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+        ..Default::default()
+    };
+    let tab = create_new_tab(position_and_size);
+    let resurrected_tab = resurrect_tab(&tab, position_and_size, "resurrect_single_pane");
+    assert_panes_resurrected(&tab, &resurrected_tab);
+    assert_eq!(
+        resurrected_tab.get_active_pane_id(),
+        Some(PaneId::Terminal(101)),
+        "the pane is focused"
+    );
+}
+
+#[test]
+fn resurrect_split_panes() {
+    let position_and_size = PositionAndSize {
+        cols: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+        ..Default::default()
+    };
+    let mut tab = create_new_tab(position_and_size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.horizontal_split(PaneId::Terminal(3));
+    let resurrected_tab = resurrect_tab(&tab, position_and_size, "resurrect_split_panes");
+    assert_panes_resurrected(&tab, &resurrected_tab);
+    assert_eq!(
+        resurrected_tab.get_active_pane_id(),
+        Some(PaneId::Terminal(103)),
+        "the focused pane is focused again"
+    );
+}
+
+#[test]
+fn resurrect_panes_hidden_by_a_fullscreen_pane() {
+    let position_and_size = PositionAndSize {
+        cols: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+        ..Default::default()
+    };
+    let mut tab = create_new_tab(position_and_size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.horizontal_split(PaneId::Terminal(3));
+    tab.toggle_active_pane_fullscreen();
+    let resurrected_tab = resurrect_tab(
+        &tab,
+        position_and_size,
+        "resurrect_panes_hidden_by_a_fullscreen_pane",
+    );
+    assert_eq!(
+        resurrected_tab.panes.len(),
+        3,
+        "hidden panes are resurrected"
+    );
+    // the fullscreen pane is resurrected in the place it had before going fullscreen
+    assert_panes_resurrected(&tab, &resurrected_tab);
+}
+
+#[test]
+fn pasted_text_cannot_end_the_bracketed_paste() {
+    assert_eq!(
+        strip_bracketed_paste_end(b"echo hi\x1b[201~rm -rf ~\n"),
+        b"echo hirm -rf ~\n".to_vec()
+    );
+    assert_eq!(strip_bracketed_paste_end(b"\x1b[201"), b"\x1b[201".to_vec());
+}
+
+/// What the tab sends to the clients when rendered
+fn render_output(tab: &mut Tab) -> String {
+    let (to_server, server_receiver) = channels::unbounded();
+    tab.senders.to_server = Some(SenderWithContext::new(to_server));
+    tab.render();
+    tab.senders.to_server = None;
+    match server_receiver.try_recv() {
+        Ok((ServerInstruction::Render(Some(output)), _)) => output,
+        _ => String::new(),
+    }
+}
+
+/// Creates a tab with a single terminal pane, along with the grid it shares with the pty thread
+fn create_new_tab_and_grid(position_and_size: PositionAndSize) -> (Tab, SharedGrid) {
+    let (to_pty, pty_receiver) = channels::unbounded();
+    let mut senders = ThreadSenders::default().silently_fail_on_send();
+    senders.to_pty = Some(SenderWithContext::new(to_pty));
+    let tab = create_tab_with_senders(position_and_size, Some(PaneId::Terminal(1)), senders);
+    match pty_receiver.try_recv() {
+        Ok((PtyInstruction::ShareGrid(1, grid), _)) => (tab, grid),
+        _ => panic!("the grid of the pane was not shared with the pty thread"),
+    }
+}
+
+/// Parses output into the grid, as the task reading the pty of the pane does
+fn feed_grid(grid: &SharedGrid, bytes: &[u8]) {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = grid.lock().unwrap();
+    for byte in bytes {
+        vte_parser.advance(&mut *grid, *byte);
+    }
+    grid.should_render = true;
+}
+
+#[test]
+fn new_panes_share_their_grid_with_the_pty_thread() {
+    let position_and_size = PositionAndSize {
+        cols: 121,
+        rows: 20,
+        ..Default::default()
+    };
+    let (mut tab, first_grid) = create_new_tab_and_grid(position_and_size);
+    let (to_pty, pty_receiver) = channels::unbounded();
+    tab.senders.to_pty = Some(SenderWithContext::new(to_pty));
+    tab.vertical_split(PaneId::Terminal(2));
+    let second_grid = match pty_receiver.try_recv() {
+        Ok((PtyInstruction::ShareGrid(2, grid), _)) => grid,
+        _ => panic!("the grid of the new pane was not shared with the pty thread"),
+    };
+    feed_grid(&second_grid, b"second pane");
+    feed_grid(&first_grid, b"first pane");
+    let output = render_output(&mut tab);
+    assert!(output.contains("first pane"));
+    assert!(output.contains("second pane"));
+}
+
+#[test]
+fn read_only_panes_take_no_input() {
+    let position_and_size = PositionAndSize {
+        cols: 121,
+        rows: 20,
+        ..Default::default()
+    };
+    let (mut tab, _grid) = create_new_tab_and_grid(position_and_size);
+    tab.new_read_only_pane(PaneId::Terminal(2));
+    assert_eq!(tab.get_active_pane_id(), Some(PaneId::Terminal(2)));
+    assert!(tab.get_active_pane().unwrap().read_only());
+    assert!(!tab.panes.get(&PaneId::Terminal(1)).unwrap().read_only());
+    // the fake terminals panic when written to
+    tab.write_to_active_terminal(b"typed".to_vec());
+    tab.paste(b"pasted".to_vec());
+}
+
+#[test]
+fn read_only_panes_are_left_out_of_the_layout() {
+    let position_and_size = PositionAndSize {
+        cols: 121,
+        rows: 20,
+        ..Default::default()
+    };
+    let mut tab = create_new_tab(position_and_size);
+    tab.new_read_only_pane(PaneId::Terminal(2));
+    let (layout, terminal_ids) = tab.dump_layout();
+    assert_eq!(terminal_ids, vec![1]);
+    let positions = layout.position_panes_in_space(&position_and_size);
+    assert_eq!(positions.len(), 1, "the other pane takes up the whole tab");
+    assert_eq!(
+        (positions[0].1.cols, positions[0].1.rows),
+        (position_and_size.cols, position_and_size.rows)
+    );
+}
+
+#[test]
+fn images_are_only_drawn_again_when_they_change() {
+    let position_and_size = PositionAndSize {
+        cols: 20,
+        rows: 10,
+        ..Default::default()
+    };
+    let (mut tab, grid) = create_new_tab_and_grid(position_and_size);
+    tab.update_character_cell_size(Some(SizeInPixels {
+        height: 10,
+        width: 5,
+    }));
+    let start_of_image = "\u{1b}P0;1;0q";
+    let red_image = "\u{1b}Pq#1;2;100;0;0#1!10~-!10~\u{1b}\\";
+    feed_grid(&grid, red_image.as_bytes());
+    assert!(render_output(&mut tab).contains(start_of_image));
+
+    // text away from the image changes
+    feed_grid(&grid, b"\x1b[6;1Hab");
+    let output = render_output(&mut tab);
+    assert!(output.contains("ab"));
+    assert!(!output.contains(start_of_image));
+
+    // the image scrolls up by a line, the text it was drawn over is drawn again
+    feed_grid(&grid, b"\x1b[10;1H\n");
+    let output = render_output(&mut tab);
+    assert!(output.contains("\u{1b}[1;1H\u{1b}P0;1;0q"));
+    assert!(output.contains("\u{1b}[2;1H"));
+
+    tab.update_character_cell_size(Some(SizeInPixels {
+        height: 20,
+        width: 10,
+    }));
+    assert!(render_output(&mut tab).contains(start_of_image));
+}
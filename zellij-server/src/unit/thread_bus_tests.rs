@@ -0,0 +1,57 @@
+use super::*;
+use std::time::{Duration, Instant};
+use zellij_utils::channels::ChannelWithContext;
+
+fn screen_channels() -> (
+    SenderWithContext<ScreenInstruction>,
+    SenderWithContext<ScreenInstruction>,
+    Bus<ScreenInstruction>,
+) {
+    let (to_screen, screen_receiver): ChannelWithContext<ScreenInstruction> = channels::unbounded();
+    let (to_screen_bounded, bounded_screen_receiver): ChannelWithContext<ScreenInstruction> =
+        channels::bounded(50);
+    let bus = Bus::new(
+        vec![screen_receiver, bounded_screen_receiver],
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    (
+        SenderWithContext::new(to_screen),
+        SenderWithContext::new(to_screen_bounded),
+        bus,
+    )
+}
+
+#[test]
+fn input_is_received_before_queued_pty_output() {
+    let (to_screen, to_screen_bounded, bus) = screen_channels();
+    to_screen_bounded.send(ScreenInstruction::Render).unwrap();
+    to_screen_bounded.send(ScreenInstruction::Render).unwrap();
+    to_screen.send(ScreenInstruction::ScrollUp).unwrap();
+    assert_eq!(to_screen_bounded.pending_count(), 2);
+
+    let (first, _) = bus.recv().unwrap();
+    assert!(matches!(first, ScreenInstruction::ScrollUp));
+    let (second, _) = bus.recv().unwrap();
+    assert!(matches!(second, ScreenInstruction::Render));
+
+    to_screen.send(ScreenInstruction::ScrollDown).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(1);
+    let (third, _) = bus.recv_deadline(deadline).unwrap();
+    assert!(matches!(third, ScreenInstruction::ScrollDown));
+    assert_eq!(to_screen_bounded.pending_count(), 1);
+}
+
+#[test]
+fn receiving_gives_up_at_the_deadline() {
+    let (_to_screen, _to_screen_bounded, bus) = screen_channels();
+    let deadline = Instant::now() + Duration::from_millis(10);
+    assert!(matches!(
+        bus.recv_deadline(deadline),
+        Err(channels::RecvTimeoutError::Timeout)
+    ));
+    assert!(Instant::now() >= deadline);
+}
@@ -63,6 +63,12 @@ pub enum InputMode {
     /// `Session` mode allows detaching sessions
     #[serde(alias = "session")]
     Session,
+    /// `Search` mode allows searching the scrollback of a pane, the input is the searched text
+    #[serde(alias = "search")]
+    Search,
+    /// `Copy` mode allows selecting text in a pane, including its scrollback, with the keyboard
+    #[serde(alias = "copy")]
+    Copy,
 }
 
 impl Default for InputMode {
@@ -106,6 +112,8 @@ impl FromStr for InputMode {
             "scroll" => Ok(InputMode::Scroll),
             "renametab" => Ok(InputMode::RenameTab),
             "session" => Ok(InputMode::Session),
+            "search" => Ok(InputMode::Search),
+            "copy" => Ok(InputMode::Copy),
             e => Err(e.to_string().into()),
         }
     }
@@ -149,6 +157,22 @@ pub struct ModeInfo {
     pub palette: Palette,
     pub capabilities: PluginCapabilities,
     pub session_name: Option<String>,
+    /// The search in the focused pane, while in [`InputMode::Search`]
+    pub search: Option<SearchInfo>,
+    /// A message for the user, such as something failing in the background, until the mode
+    /// changes
+    pub notice: Option<String>,
+}
+
+/// The state of a search through the scrollback of a pane.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SearchInfo {
+    pub query: String,
+    pub case_sensitive: bool,
+    pub regex: bool,
+    pub match_count: usize,
+    /// The index of the match the pane was scrolled to, if any
+    pub active_match: Option<usize>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
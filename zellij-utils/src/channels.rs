@@ -4,7 +4,9 @@ use async_std::task_local;
 use std::cell::RefCell;
 
 use crate::errors::{get_current_ctx, ErrorContext};
-pub use crossbeam::channel::{bounded, unbounded, Receiver, RecvError, Select, SendError, Sender};
+pub use crossbeam::channel::{
+    bounded, unbounded, Receiver, RecvError, RecvTimeoutError, Select, SendError, Sender,
+};
 
 /// An [MPSC](mpsc) asynchronous channel with added error context.
 pub type ChannelWithContext<T> = (Sender<(T, ErrorContext)>, Receiver<(T, ErrorContext)>);
@@ -27,6 +29,11 @@ impl<T: Clone> SenderWithContext<T> {
         let err_ctx = get_current_ctx();
         self.sender.send((event, err_ctx))
     }
+
+    /// The number of events waiting to be received on this [`SenderWithContext`]'s channel.
+    pub fn pending_count(&self) -> usize {
+        self.sender.len()
+    }
 }
 
 thread_local!(
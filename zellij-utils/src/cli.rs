@@ -1,10 +1,23 @@
 use crate::consts::{ZELLIJ_CONFIG_DIR_ENV, ZELLIJ_CONFIG_FILE_ENV};
+use crate::input::actions::Direction;
 use crate::input::options::Options;
 use crate::setup::Setup;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
+/// The slowest a recording can be replayed at, slower speeds wait for far too long between events
+pub const MIN_REPLAY_SPEED: f64 = 0.01;
+
+fn parse_replay_speed(speed: &str) -> Result<f64, String> {
+    let speed: f64 = speed.parse().map_err(|e| format!("{}", e))?;
+    if speed.is_finite() && speed >= MIN_REPLAY_SPEED {
+        Ok(speed)
+    } else {
+        Err(format!("the speed must be at least {}", MIN_REPLAY_SPEED))
+    }
+}
+
 #[derive(StructOpt, Default, Debug, Clone, Serialize, Deserialize)]
 #[structopt(name = "zellij")]
 pub struct CliArgs {
@@ -60,13 +73,31 @@ pub enum Command {
     /// Explore existing zellij sessions
     #[structopt(flatten)]
     Sessions(Sessions),
+
+    /// Send actions to a running session
+    #[structopt(name = "action")]
+    Action(CliAction),
+
+    /// Play an asciicast file in the terminal
+    #[structopt(name = "replay")]
+    Replay {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Play the recording this many times faster
+        #[structopt(long, default_value = "1", parse(try_from_str = parse_replay_speed))]
+        speed: f64,
+    },
 }
 
 #[derive(Debug, StructOpt, Clone, Serialize, Deserialize)]
 pub enum Sessions {
     /// List active sessions
     #[structopt(alias = "ls")]
-    ListSessions,
+    ListSessions {
+        /// Print the sessions and their metadata as JSON
+        #[structopt(long)]
+        json: bool,
+    },
 
     /// Attach to session
     #[structopt(alias = "a")]
@@ -74,9 +105,154 @@ pub enum Sessions {
         /// Name of the session to attach to.
         session_name: Option<String>,
 
+        /// Attach as a spectator, the session can be watched but not changed
+        #[structopt(long)]
+        read_only: bool,
+
         /// Force attach- session will detach from the other
-        /// zellij client (if any) and attach to this.
-        #[structopt(long, short)]
+        /// zellij clients (if any) and attach to this.
+        #[structopt(long, short, conflicts_with = "read-only")]
         force: bool,
+
+        /// Start the session again from the state it was last saved in,
+        /// if it is no longer running
+        #[structopt(long, conflicts_with = "read-only")]
+        resurrect: bool,
+    },
+
+    /// Kill the specific session
+    #[structopt(alias = "k")]
+    KillSession {
+        /// Name of target session
+        target_session: String,
+    },
+
+    /// Kill all sessions
+    #[structopt(alias = "ka")]
+    KillAllSessions {
+        /// Automatic yes to prompts
+        #[structopt(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone, Serialize, Deserialize)]
+pub enum CliAction {
+    /// Write bytes to the focused pane
+    Write { bytes: Vec<u8> },
+    /// Write characters to the focused pane
+    WriteChars { chars: String },
+    /// Resize the focused pane in the specified direction [right|left|up|down]
+    Resize { direction: Direction },
+    /// Change focus to the next pane
+    FocusNextPane,
+    /// Change focus to the previous pane
+    FocusPreviousPane,
+    /// Move the focused pane in the specified direction [right|left|up|down]
+    MoveFocus { direction: Direction },
+    /// Move focus to the pane in the specified direction, or to the previous/next tab
+    /// if there is none [right|left]
+    MoveFocusOrTab { direction: Direction },
+    /// Scroll up in the focused pane
+    ScrollUp,
+    /// Scroll down in the focused pane
+    ScrollDown,
+    /// Scroll down to the bottom of the focused pane
+    ScrollToBottom,
+    /// Scroll up one page in the focused pane
+    PageScrollUp,
+    /// Scroll down one page in the focused pane
+    PageScrollDown,
+    /// Toggle between fullscreen focused pane and normal layout
+    ToggleFullscreen,
+    /// Toggle between sending text input to all panes in the current tab and just the focused pane
+    ToggleActiveSyncTab,
+    /// Open a new pane in the specified direction [right|left|up|down]
+    /// If no direction is specified, will try to use the biggest available space
+    NewPane {
+        #[structopt(short, long)]
+        direction: Option<Direction>,
+    },
+    /// Run a command in a new pane, eg. `zellij action run -- htop -d 10`
+    Run {
+        #[structopt(short, long)]
+        direction: Option<Direction>,
+        /// Directory to run the command in
+        #[structopt(long, parse(from_os_str))]
+        cwd: Option<PathBuf>,
+        #[structopt(required = true, last = true)]
+        command: Vec<String>,
+    },
+    /// Close the focused pane
+    ClosePane,
+    /// Create a new tab
+    NewTab {
+        /// Name of the tab in the session's layout to use as a template
+        #[structopt(short, long)]
+        template: Option<String>,
+    },
+    /// Go to the next tab
+    GoToNextTab,
+    /// Go to the previous tab
+    GoToPreviousTab,
+    /// Close the current tab
+    CloseTab,
+    /// Go to the tab with the given index, starting from 1
+    GoToTab { index: u32 },
+    /// Rename the current tab
+    RenameTab { name: String },
+    /// Save the pane arrangement of the current tab as a layout file
+    DumpLayout {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+    /// Open the scrollback of the focused pane in the default editor ($EDITOR or $VISUAL)
+    EditScrollback {
+        /// Keep the colors and styles of the text as ANSI escape sequences
+        #[structopt(long)]
+        ansi: bool,
+    },
+    /// Save the scrollback of the focused pane to a file
+    DumpScrollback {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Keep the colors and styles of the text as ANSI escape sequences
+        #[structopt(long)]
+        ansi: bool,
+    },
+    /// Copy everything the focused pane prints to a file or to the stdin of a command
+    PipePane {
+        /// Append the output to this file
+        #[structopt(
+            long,
+            parse(from_os_str),
+            conflicts_with = "command",
+            required_unless = "command"
+        )]
+        file: Option<PathBuf>,
+        /// Write the output to the stdin of this command, run with the shell
+        #[structopt(long)]
+        command: Option<String>,
+        /// Remove the ANSI escape sequences from the output, keeping only the text
+        #[structopt(long)]
+        strip_ansi: bool,
+    },
+    /// Stop copying the output of the focused pane
+    StopPipePane,
+    /// Record the focused pane to an asciicast file, which can be replayed
+    /// with `zellij action replay-recording`, `zellij replay` or asciinema
+    StartRecording {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+    /// Stop recording the focused pane
+    StopRecording,
+    /// Replay an asciicast file in a new read-only pane
+    ReplayRecording {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Play the recording this many times faster
+        #[structopt(long, default_value = "1", parse(try_from_str = parse_replay_speed))]
+        speed: f64,
     },
 }
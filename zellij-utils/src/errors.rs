@@ -177,9 +177,10 @@ impl Display for ContextType {
 /// Stack call representations corresponding to the different types of [`ScreenInstruction`]s.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ScreenContext {
-    HandlePtyBytes,
     Render,
+    ForceRender,
     NewPane,
+    NewReadOnlyPane,
     HorizontalSplit,
     VerticalSplit,
     WriteCharacter,
@@ -224,9 +225,30 @@ pub enum ScreenContext {
     TerminalResize,
     ChangeMode,
     LeftClick,
+    RightClick,
+    MiddleClick,
     MouseRelease,
     MouseHold,
+    MouseMotion,
     Copy,
+    QuerySessionInfo,
+    SerializeSession,
+    DumpLayout,
+    Paste,
+    OpenLink,
+    SearchInput,
+    SearchReset,
+    Search,
+    SearchToggleOption,
+    EditScrollback,
+    DumpScrollback,
+    CopyModeMove,
+    CopyModeSelect,
+    PipePane,
+    StopPipePane,
+    ShowNotice,
+    StartRecording,
+    StopRecording,
 }
 
 /// Stack call representations corresponding to the different types of [`PtyInstruction`]s.
@@ -236,8 +258,23 @@ pub enum PtyContext {
     SpawnTerminalVertically,
     SpawnTerminalHorizontally,
     NewTab,
+    NewTabFromTemplate,
+    StartSession,
     ClosePane,
     CloseTab,
+    SerializeSession,
+    ResurrectSession,
+    DumpLayout,
+    DumpScrollback,
+    EditScrollback,
+    UpdateActiveTerminal,
+    OpenLink,
+    PipePane,
+    StopPipePane,
+    StartRecording,
+    StopRecording,
+    ReplayRecording,
+    ShareGrid,
     Exit,
 }
 
@@ -268,7 +305,10 @@ pub enum ServerContext {
     Render,
     UnblockInputThread,
     ClientExit,
+    RemoveClient,
     Error,
     DetachSession,
     AttachClient,
+    SessionInfo,
+    KillSession,
 }
@@ -1,8 +1,11 @@
 //! Definition of the actions that can be bound to keys.
 
 use super::command::RunCommandAction;
+use crate::cli::CliAction;
 use crate::input::options::OnForceClose;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use zellij_tile::data::InputMode;
 
 use crate::position::Position;
@@ -16,6 +19,81 @@ pub enum Direction {
     Down,
 }
 
+impl FromStr for Direction {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" | "Left" => Ok(Direction::Left),
+            "right" | "Right" => Ok(Direction::Right),
+            "up" | "Up" => Ok(Direction::Up),
+            "down" | "Down" => Ok(Direction::Down),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
+/// The direction in which to look for the next match of a search, see [`Action::Search`].
+#[derive(Eq, Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum SearchDirection {
+    /// Towards the older output in the scrollback
+    Up,
+    Down,
+}
+
+/// The options of a search that can be toggled, see [`Action::SearchToggleOption`].
+#[derive(Eq, Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum SearchOption {
+    CaseSensitivity,
+    Regex,
+}
+
+/// A movement of the cursor of the copy mode, see [`Action::CopyModeMove`].
+#[derive(Eq, Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum CopyModeMotion {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// To the start of the next word
+    WordForward,
+    /// To the start of the previous word
+    WordBackward,
+    /// To the end of the current or next word
+    WordEnd,
+    LineStart,
+    /// To the last non blank character of the line
+    LineEnd,
+    HalfPageUp,
+    HalfPageDown,
+    PageUp,
+    PageDown,
+    /// To the first line of the scrollback
+    Top,
+    /// To the last line of the pane
+    Bottom,
+}
+
+/// The shape of a selection made in copy mode, see [`Action::CopyModeSelect`].
+#[derive(Eq, Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum SelectionKind {
+    /// From one character to another, like a mouse selection
+    Character,
+    /// Whole lines
+    Line,
+    /// The same columns on every line
+    Block,
+}
+
+/// Where the output of a pane is copied to, see [`Action::PipePane`].
+#[derive(Eq, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum PipeTarget {
+    /// Appended to a file
+    File(PathBuf),
+    /// Written to the stdin of a command, run with the shell
+    Command(String),
+}
+
 // As these actions are bound to the default config, please
 // do take care when refactoring - or renaming.
 // They might need to be adjusted in the default config
@@ -27,6 +105,8 @@ pub enum Action {
     Quit,
     /// Write to the terminal.
     Write(Vec<u8>),
+    /// Paste text into the terminal, bracketed if the application in it asked for it.
+    Paste(Vec<u8>),
     /// Switch to the specified input mode.
     SwitchToMode(InputMode),
     /// Resize focus pane in specified direction.
@@ -65,6 +145,8 @@ pub enum Action {
     CloseFocus,
     /// Create a new tab.
     NewTab,
+    /// Create a new tab from the tab with the given name in the layout.
+    NewTabFromTemplate(String),
     /// Do nothing.
     NoOp,
     /// Go to the next tab.
@@ -80,9 +162,45 @@ pub enum Action {
     /// Detach session and exit
     Detach,
     LeftClick(Position),
+    RightClick(Position),
+    MiddleClick(Position),
     MouseRelease(Position),
     MouseHold(Position),
+    /// The mouse moved with no button held.
+    MouseMotion(Position),
     Copy,
+    /// Save the pane arrangement of the current tab as a layout file.
+    DumpLayout(PathBuf),
+    /// Open the hyperlink under the last mouse click or the cursor of the focused pane.
+    OpenLink,
+    /// Edit the text searched for in the scrollback of the focused pane.
+    SearchInput(Vec<u8>),
+    /// Clear the text searched for in the scrollback of the focused pane, to start a new search.
+    SearchReset,
+    /// Scroll the focused pane to the next match of the search in the specified direction.
+    Search(SearchDirection),
+    /// Toggle an option of the search in the focused pane.
+    SearchToggleOption(SearchOption),
+    /// Open the scrollback of the focused pane in the default editor, with ANSI styles if `true`.
+    EditScrollback(bool),
+    /// Save the scrollback of the focused pane to a file, with ANSI styles if `true`.
+    DumpScrollback(PathBuf, bool),
+    /// Move the cursor of the copy mode in the focused pane.
+    CopyModeMove(CopyModeMotion),
+    /// Start a selection of the specified kind at the cursor of the copy mode, or stop it.
+    CopyModeSelect(SelectionKind),
+    /// Copy everything the focused pane prints to a file or command, without ANSI escape
+    /// sequences if `true`.
+    PipePane(PipeTarget, bool),
+    /// Stop copying the output of the focused pane.
+    StopPipePane,
+    /// Record the focused pane to an asciicast file.
+    StartRecording(PathBuf),
+    /// Stop recording the focused pane.
+    StopRecording,
+    /// Play an asciicast file in a new read-only pane, at the given percentage of the speed it
+    /// was recorded at.
+    ReplayRecording(PathBuf, u32),
 }
 
 impl From<OnForceClose> for Action {
@@ -93,3 +211,102 @@ impl From<OnForceClose> for Action {
         }
     }
 }
+
+impl Action {
+    /// Translates an action given on the command line to the actions sent to the session.
+    pub fn actions_from_cli(cli_action: CliAction) -> Vec<Action> {
+        let current_dir = std::env::current_dir().ok();
+        match cli_action {
+            CliAction::Write { bytes } => vec![Action::Write(bytes)],
+            CliAction::WriteChars { chars } => vec![Action::Write(chars.into_bytes())],
+            CliAction::Resize { direction } => vec![Action::Resize(direction)],
+            CliAction::FocusNextPane => vec![Action::FocusNextPane],
+            CliAction::FocusPreviousPane => vec![Action::FocusPreviousPane],
+            CliAction::MoveFocus { direction } => vec![Action::MoveFocus(direction)],
+            CliAction::MoveFocusOrTab { direction } => vec![Action::MoveFocusOrTab(direction)],
+            CliAction::ScrollUp => vec![Action::ScrollUp],
+            CliAction::ScrollDown => vec![Action::ScrollDown],
+            CliAction::ScrollToBottom => vec![Action::ScrollToBottom],
+            CliAction::PageScrollUp => vec![Action::PageScrollUp],
+            CliAction::PageScrollDown => vec![Action::PageScrollDown],
+            CliAction::ToggleFullscreen => vec![Action::ToggleFocusFullscreen],
+            CliAction::ToggleActiveSyncTab => vec![Action::ToggleActiveSyncTab],
+            CliAction::NewPane { direction } => vec![Action::NewPane(direction)],
+            CliAction::Run {
+                direction,
+                cwd,
+                command,
+            } => {
+                let mut command = command.into_iter();
+                vec![Action::Run(RunCommandAction {
+                    command: command.next().unwrap_or_default().into(),
+                    args: command.collect(),
+                    direction,
+                    cwd: cwd.map(|path| absolute_path(current_dir.as_deref(), path)),
+                    ..Default::default()
+                })]
+            }
+            CliAction::ClosePane => vec![Action::CloseFocus],
+            CliAction::NewTab { template: None } => vec![Action::NewTab],
+            CliAction::NewTab {
+                template: Some(template),
+            } => vec![Action::NewTabFromTemplate(template)],
+            CliAction::GoToNextTab => vec![Action::GoToNextTab],
+            CliAction::GoToPreviousTab => vec![Action::GoToPreviousTab],
+            CliAction::CloseTab => vec![Action::CloseTab],
+            CliAction::GoToTab { index } => vec![Action::GoToTab(index)],
+            CliAction::RenameTab { name } => vec![
+                Action::TabNameInput(vec![0]),
+                Action::TabNameInput(name.into_bytes()),
+            ],
+            CliAction::DumpLayout { path } => vec![Action::DumpLayout(absolute_path(
+                current_dir.as_deref(),
+                path,
+            ))],
+            CliAction::EditScrollback { ansi } => vec![Action::EditScrollback(ansi)],
+            CliAction::DumpScrollback { path, ansi } => vec![Action::DumpScrollback(
+                absolute_path(current_dir.as_deref(), path),
+                ansi,
+            )],
+            CliAction::PipePane {
+                file,
+                command,
+                strip_ansi,
+            } => {
+                let target = match (file, command) {
+                    (Some(path), _) => {
+                        PipeTarget::File(absolute_path(current_dir.as_deref(), path))
+                    }
+                    (None, command) => PipeTarget::Command(command.unwrap_or_default()),
+                };
+                vec![Action::PipePane(target, strip_ansi)]
+            }
+            CliAction::StopPipePane => vec![Action::StopPipePane],
+            CliAction::StartRecording { path } => vec![Action::StartRecording(absolute_path(
+                current_dir.as_deref(),
+                path,
+            ))],
+            CliAction::StopRecording => vec![Action::StopRecording],
+            CliAction::ReplayRecording { path, speed } => {
+                // the speed is validated by the cli, and is at least 1%
+                vec![Action::ReplayRecording(
+                    absolute_path(current_dir.as_deref(), path),
+                    (speed * 100.0).round() as u32,
+                )]
+            }
+        }
+    }
+}
+
+/// Resolves a path given on the command line in the working directory `cwd` of the cli, since the
+/// server does not run in it
+fn absolute_path(cwd: Option<&Path>, path: PathBuf) -> PathBuf {
+    match cwd {
+        Some(cwd) => cwd.join(path),
+        None => path,
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/actions_test.rs"]
+mod actions_test;
@@ -1,6 +1,7 @@
 //! Trigger a command
 use super::actions::Direction;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
@@ -15,6 +16,10 @@ pub struct RunCommand {
     pub command: PathBuf,
     #[serde(default)]
     pub args: Vec<String>,
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
 }
 
 /// Intermediate representation
@@ -26,6 +31,10 @@ pub struct RunCommandAction {
     pub args: Vec<String>,
     #[serde(default)]
     pub direction: Option<Direction>,
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
 }
 
 impl From<RunCommandAction> for RunCommand {
@@ -33,6 +42,8 @@ impl From<RunCommandAction> for RunCommand {
         RunCommand {
             command: action.command,
             args: action.args,
+            cwd: action.cwd,
+            env: action.env,
         }
     }
 }
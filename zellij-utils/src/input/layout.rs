@@ -26,6 +26,12 @@ pub enum Direction {
     Vertical,
 }
 
+impl Default for Direction {
+    fn default() -> Self {
+        Direction::Horizontal
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 #[serde(crate = "self::serde")]
 pub enum SplitSize {
@@ -42,14 +48,87 @@ pub enum Run {
     Command(RunCommand),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(crate = "self::serde")]
 pub struct Layout {
+    #[serde(default)]
     pub direction: Direction,
     #[serde(default)]
     pub parts: Vec<Layout>,
     pub split_size: Option<SplitSize>,
     pub run: Option<Run>,
+    /// The name of a tab, see `tabs`
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Whether this pane (or tab, see `tabs`) is focused when the layout is applied
+    #[serde(default)]
+    pub focus: bool,
+    /// The number of scrollback lines of this pane, overriding the `scroll_buffer_size` option
+    #[serde(default)]
+    pub scroll_buffer_size: Option<usize>,
+    /// Tabs opened when the session starts, each with its own layout.
+    /// The layout around them is then only used for tabs opened later on.
+    #[serde(default)]
+    pub tabs: Vec<Layout>,
+}
+
+/// A tab of a serialized session, see [`SessionSnapshot`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(crate = "self::serde")]
+pub struct TabSnapshot {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub active: bool,
+    pub layout: Layout,
+    /// The scrollback of each terminal pane, in the order they appear in the layout
+    #[serde(default)]
+    pub scrollback: Vec<String>,
+}
+
+/// The state of a session that is periodically written to disk,
+/// so that it can be resurrected after the server went away.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "self::serde")]
+pub struct SessionSnapshot {
+    pub tabs: Vec<TabSnapshot>,
+}
+
+impl SessionSnapshot {
+    /// The file a session with the name `session_name` is serialized to
+    pub fn path(data_dir: &Path, session_name: &str) -> PathBuf {
+        data_dir
+            .join("sessions")
+            .join(format!("{}.yaml", session_name))
+    }
+
+    pub fn from_path(path: &Path) -> Result<SessionSnapshot, ConfigError> {
+        let mut snapshot_file =
+            File::open(path).map_err(|e| ConfigError::IoPath(e, path.into()))?;
+        let mut snapshot = String::new();
+        snapshot_file.read_to_string(&mut snapshot)?;
+        let snapshot: SessionSnapshot = serde_yaml::from_str(&snapshot)?;
+        Ok(snapshot)
+    }
+
+    pub fn write_to_path(&self, path: &Path) -> Result<(), ConfigError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| ConfigError::IoPath(e, dir.into()))?;
+        }
+        let snapshot = serde_yaml::to_string(self)?;
+        write_atomically(path, &snapshot)
+    }
+}
+
+/// Writes to a temporary file first, then moves it to `path`, so a crash never leaves a
+/// truncated file behind
+fn write_atomically(path: &Path, contents: &str) -> Result<(), ConfigError> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    std::fs::write(&tmp_path, contents).map_err(|e| ConfigError::IoPath(e, tmp_path.clone()))?;
+    std::fs::rename(&tmp_path, path).map_err(|e| ConfigError::IoPath(e, path.into()))?;
+    Ok(())
 }
 
 type LayoutResult = Result<Layout, ConfigError>;
@@ -66,6 +145,11 @@ impl Layout {
         Ok(layout)
     }
 
+    pub fn write_to_path(&self, path: &Path) -> Result<(), ConfigError> {
+        let layout = serde_yaml::to_string(self)?;
+        write_atomically(path, &layout)
+    }
+
     // It wants to use Path here, but that doesn't compile.
     #[allow(clippy::ptr_arg)]
     pub fn from_dir(layout: &PathBuf, layout_dir: Option<&PathBuf>) -> LayoutResult {
@@ -127,6 +211,18 @@ impl Layout {
         Ok(layout)
     }
 
+    /// The position of the tab focused when the session starts, among `tabs`
+    pub fn focused_tab(&self) -> Option<usize> {
+        self.tabs.iter().position(|tab| tab.focus)
+    }
+
+    /// The layout of the tab with the given `name`, to open a new tab from
+    pub fn tab_template(&self, name: &str) -> Option<&Layout> {
+        self.tabs
+            .iter()
+            .find(|tab| tab.name.as_deref() == Some(name))
+    }
+
     pub fn total_terminal_panes(&self) -> usize {
         let mut total_panes = 0;
         total_panes += self.parts.len();
@@ -153,6 +249,20 @@ impl Layout {
         run_instructions
     }
 
+    /// Sets the run instructions of the terminal panes of this layout, in the
+    /// order in which [`Layout::extract_run_instructions`] returns them.
+    pub fn fill_terminal_run_instructions(&mut self, runs: &mut impl Iterator<Item = Option<Run>>) {
+        if self.parts.is_empty() {
+            if !matches!(self.run, Some(Run::Plugin(_))) {
+                self.run = runs.next().flatten();
+            }
+            return;
+        }
+        for part in self.parts.iter_mut() {
+            part.fill_terminal_run_instructions(runs);
+        }
+    }
+
     pub fn position_panes_in_space(
         &self,
         space: &PositionAndSize,
@@ -313,3 +423,7 @@ fn split_space(
     }
     pane_positions
 }
+
+#[cfg(test)]
+#[path = "./unit/layout_test.rs"]
+mod layout_test;
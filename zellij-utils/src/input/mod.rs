@@ -41,9 +41,23 @@ pub fn get_mode_info(
         InputMode::Scroll => vec![
             ("↓↑".to_string(), "Scroll".to_string()),
             ("PgUp/PgDn".to_string(), "Scroll Page".to_string()),
+            ("s".to_string(), "Search".to_string()),
+            ("e".to_string(), "Edit".to_string()),
+            ("c".to_string(), "Copy".to_string()),
         ],
         InputMode::RenameTab => vec![("Enter".to_string(), "when done".to_string())],
         InputMode::Session => vec![("d".to_string(), "Detach".to_string())],
+        InputMode::Search => vec![
+            ("↑/Enter".to_string(), "Search up".to_string()),
+            ("↓".to_string(), "Search down".to_string()),
+            ("Alt+c".to_string(), "Case sensitivity".to_string()),
+            ("Alt+r".to_string(), "Regex".to_string()),
+        ],
+        InputMode::Copy => vec![
+            ("hjkl/←↓↑→".to_string(), "Move".to_string()),
+            ("v/V/Ctrl+v".to_string(), "Select".to_string()),
+            ("y/Enter".to_string(), "Copy".to_string()),
+        ],
     };
 
     let session_name = std::env::var("ZELLIJ_SESSION_NAME").ok();
@@ -54,6 +68,8 @@ pub fn get_mode_info(
         palette,
         capabilities,
         session_name,
+        search: None,
+        notice: None,
     }
 }
 
@@ -17,6 +17,39 @@ pub enum MouseEvent {
     ///
     /// The coordinates are zero-based.
     Hold(Position),
+    /// The mouse moved to the given coordinates with no button held.
+    ///
+    /// The coordinates are zero-based.
+    Motion(Position),
+}
+
+impl MouseEvent {
+    /// Parses the sgr encoded mouse events that termion does not know of, which are the drags
+    /// with the right or middle button held and the motion with no button held. The modifier
+    /// keys held along with them are ignored.
+    pub fn from_unsupported(bytes: &[u8]) -> Option<Self> {
+        if !bytes.starts_with(b"\x1b[<") || !bytes.ends_with(b"M") {
+            return None;
+        }
+        let parameters = std::str::from_utf8(&bytes[3..bytes.len() - 1]).ok()?;
+        let mut parameters = parameters.split(';').map(|p| p.parse::<u16>().ok());
+        let (button, x, y) = match (
+            parameters.next(),
+            parameters.next(),
+            parameters.next(),
+            parameters.next(),
+        ) {
+            (Some(Some(button)), Some(Some(x)), Some(Some(y)), None) => (button, x, y),
+            _ => return None,
+        };
+        let position = Position::new((y.saturating_sub(1)) as i32, x.saturating_sub(1));
+        // shift, meta and control add 4, 8 and 16 to the button
+        match button & !0b11100 {
+            32..=34 => Some(Self::Hold(position)),
+            35 => Some(Self::Motion(position)),
+            _ => None,
+        }
+    }
 }
 
 impl From<termion::event::MouseEvent> for MouseEvent {
@@ -67,3 +100,7 @@ impl From<termion::event::MouseButton> for MouseButton {
         }
     }
 }
+
+#[cfg(test)]
+#[path = "./unit/mouse_test.rs"]
+mod mouse_test;
@@ -61,6 +61,35 @@ pub struct Options {
     /// Set behaviour on force close (quit or detach)
     #[structopt(long)]
     pub on_force_close: Option<OnForceClose>,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Do not periodically save the session layout for `attach --resurrect`
+    pub disable_session_serialization: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Include the scrollback of each pane when saving the session
+    pub serialize_pane_scrollback: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Start new panes in the server's working directory,
+    /// instead of the one of the focused pane
+    pub disable_cwd_inheritance: bool,
+    /// Set the command used to open hyperlinks, the link is passed
+    /// as its last argument (defaults to `xdg-open`, or `open` on macOS)
+    #[structopt(long)]
+    pub link_opener: Option<String>,
+    /// Set the number of lines kept in the scrollback of each pane (defaults to 10000),
+    /// layouts can override it for individual panes
+    #[structopt(long)]
+    pub scroll_buffer_size: Option<usize>,
+    /// Set the characters that end a word selected by double clicking,
+    /// besides whitespace (defaults to ``,│`|:"' ()[]{}<>``)
+    #[structopt(long)]
+    pub word_separators: Option<String>,
+    /// Set how many times per second at most the output of busy panes
+    /// is rendered (defaults to 60)
+    #[structopt(long)]
+    pub max_fps: Option<u16>,
 }
 
 impl Options {
@@ -80,12 +109,28 @@ impl Options {
 
         let simplified_ui = merge_bool(other.simplified_ui, self.simplified_ui);
         let disable_mouse_mode = merge_bool(other.disable_mouse_mode, self.disable_mouse_mode);
+        let disable_session_serialization = merge_bool(
+            other.disable_session_serialization,
+            self.disable_session_serialization,
+        );
+        let serialize_pane_scrollback = merge_bool(
+            other.serialize_pane_scrollback,
+            self.serialize_pane_scrollback,
+        );
+        let disable_cwd_inheritance =
+            merge_bool(other.disable_cwd_inheritance, self.disable_cwd_inheritance);
 
         let default_mode = other.default_mode.or(self.default_mode);
         let default_shell = other.default_shell.or_else(|| self.default_shell.clone());
         let layout_dir = other.layout_dir.or_else(|| self.layout_dir.clone());
         let theme = other.theme.or_else(|| self.theme.clone());
         let on_force_close = other.on_force_close.or(self.on_force_close);
+        let link_opener = other.link_opener.or_else(|| self.link_opener.clone());
+        let scroll_buffer_size = other.scroll_buffer_size.or(self.scroll_buffer_size);
+        let word_separators = other
+            .word_separators
+            .or_else(|| self.word_separators.clone());
+        let max_fps = other.max_fps.or(self.max_fps);
 
         Options {
             simplified_ui,
@@ -95,6 +140,13 @@ impl Options {
             layout_dir,
             disable_mouse_mode,
             on_force_close,
+            disable_session_serialization,
+            serialize_pane_scrollback,
+            disable_cwd_inheritance,
+            link_opener,
+            scroll_buffer_size,
+            word_separators,
+            max_fps,
         }
     }
 
@@ -0,0 +1,166 @@
+use super::super::actions::*;
+use super::super::command::RunCommandAction;
+use crate::cli::CliAction;
+use std::path::{Path, PathBuf};
+
+fn in_current_dir(path: &str) -> PathBuf {
+    std::env::current_dir().unwrap().join(path)
+}
+
+#[test]
+fn cli_actions_map_to_a_single_action() {
+    assert_eq!(
+        Action::actions_from_cli(CliAction::WriteChars { chars: "ls".into() }),
+        vec![Action::Write(b"ls".to_vec())]
+    );
+    assert_eq!(
+        Action::actions_from_cli(CliAction::ToggleFullscreen),
+        vec![Action::ToggleFocusFullscreen]
+    );
+    assert_eq!(
+        Action::actions_from_cli(CliAction::ClosePane),
+        vec![Action::CloseFocus]
+    );
+    assert_eq!(
+        Action::actions_from_cli(CliAction::GoToTab { index: 2 }),
+        vec![Action::GoToTab(2)]
+    );
+}
+
+#[test]
+fn new_tab_uses_the_template_if_any() {
+    assert_eq!(
+        Action::actions_from_cli(CliAction::NewTab { template: None }),
+        vec![Action::NewTab]
+    );
+    assert_eq!(
+        Action::actions_from_cli(CliAction::NewTab {
+            template: Some("editor".into())
+        }),
+        vec![Action::NewTabFromTemplate("editor".into())]
+    );
+}
+
+#[test]
+fn rename_tab_clears_the_name_before_writing_the_new_one() {
+    assert_eq!(
+        Action::actions_from_cli(CliAction::RenameTab {
+            name: "logs".into()
+        }),
+        vec![
+            Action::TabNameInput(vec![0]),
+            Action::TabNameInput(b"logs".to_vec())
+        ]
+    );
+}
+
+#[test]
+fn run_splits_the_command_from_its_arguments() {
+    let actions = Action::actions_from_cli(CliAction::Run {
+        direction: Some(Direction::Down),
+        cwd: Some(PathBuf::from("src")),
+        command: vec!["htop".into(), "-d".into(), "10".into()],
+    });
+    assert_eq!(
+        actions,
+        vec![Action::Run(RunCommandAction {
+            command: PathBuf::from("htop"),
+            args: vec!["-d".into(), "10".into()],
+            direction: Some(Direction::Down),
+            cwd: Some(in_current_dir("src")),
+            ..Default::default()
+        })]
+    );
+}
+
+#[test]
+fn relative_paths_are_resolved_in_the_current_directory() {
+    assert_eq!(
+        Action::actions_from_cli(CliAction::DumpLayout {
+            path: PathBuf::from("layout.yaml")
+        }),
+        vec![Action::DumpLayout(in_current_dir("layout.yaml"))]
+    );
+    assert_eq!(
+        Action::actions_from_cli(CliAction::DumpScrollback {
+            path: PathBuf::from("scrollback.txt"),
+            ansi: true
+        }),
+        vec![Action::DumpScrollback(
+            in_current_dir("scrollback.txt"),
+            true
+        )]
+    );
+    assert_eq!(
+        Action::actions_from_cli(CliAction::PipePane {
+            file: Some(PathBuf::from("pane.log")),
+            command: None,
+            strip_ansi: false
+        }),
+        vec![Action::PipePane(
+            PipeTarget::File(in_current_dir("pane.log")),
+            false
+        )]
+    );
+    assert_eq!(
+        Action::actions_from_cli(CliAction::StartRecording {
+            path: PathBuf::from("pane.cast")
+        }),
+        vec![Action::StartRecording(in_current_dir("pane.cast"))]
+    );
+}
+
+#[test]
+fn absolute_paths_are_kept() {
+    assert_eq!(
+        Action::actions_from_cli(CliAction::DumpLayout {
+            path: PathBuf::from("/tmp/layout.yaml")
+        }),
+        vec![Action::DumpLayout(PathBuf::from("/tmp/layout.yaml"))]
+    );
+}
+
+#[test]
+fn paths_are_resolved_in_the_given_directory() {
+    assert_eq!(
+        absolute_path(Some(Path::new("/home/zellij")), PathBuf::from("pane.cast")),
+        PathBuf::from("/home/zellij/pane.cast")
+    );
+    assert_eq!(
+        absolute_path(None, PathBuf::from("pane.cast")),
+        PathBuf::from("pane.cast")
+    );
+}
+
+#[test]
+fn pipe_pane_to_a_command() {
+    assert_eq!(
+        Action::actions_from_cli(CliAction::PipePane {
+            file: None,
+            command: Some("grep error >> errors.log".into()),
+            strip_ansi: true
+        }),
+        vec![Action::PipePane(
+            PipeTarget::Command("grep error >> errors.log".into()),
+            true
+        )]
+    );
+}
+
+#[test]
+fn recordings_are_replayed_at_a_percentage_of_their_speed() {
+    assert_eq!(
+        Action::actions_from_cli(CliAction::ReplayRecording {
+            path: PathBuf::from("pane.cast"),
+            speed: 1.5
+        }),
+        vec![Action::ReplayRecording(in_current_dir("pane.cast"), 150)]
+    );
+    assert_eq!(
+        Action::actions_from_cli(CliAction::ReplayRecording {
+            path: PathBuf::from("/tmp/pane.cast"),
+            speed: 0.01
+        }),
+        vec![Action::ReplayRecording(PathBuf::from("/tmp/pane.cast"), 1)]
+    );
+}
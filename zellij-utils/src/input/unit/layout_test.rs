@@ -0,0 +1,93 @@
+use super::super::layout::*;
+use crate::pane_size::PositionAndSize;
+use std::path::{Path, PathBuf};
+
+fn layout_fixture(name: &str) -> Layout {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src/input/unit/fixtures/layouts")
+        .join(name);
+    Layout::new(&path).unwrap()
+}
+
+fn command_of(layout: &Layout) -> Option<(PathBuf, Vec<String>)> {
+    match &layout.run {
+        Some(Run::Command(command)) => Some((command.command.clone(), command.args.clone())),
+        _ => None,
+    }
+}
+
+#[test]
+fn layout_with_tabs_is_parsed() {
+    let layout = layout_fixture("multiple-tabs.yaml");
+    let tab_names: Vec<Option<&str>> = layout.tabs.iter().map(|tab| tab.name.as_deref()).collect();
+    assert_eq!(
+        tab_names,
+        vec![Some("editor"), Some("servers"), Some("logs")]
+    );
+    assert_eq!(layout.parts.len(), 2, "the layout around the tabs is kept");
+
+    let servers = &layout.tabs[1];
+    assert_eq!(servers.parts.len(), 2);
+    assert_eq!(
+        command_of(&servers.parts[0]),
+        Some((PathBuf::from("make"), vec!["serve".to_string()]))
+    );
+    assert_eq!(layout.tabs[2].parts[0].scroll_buffer_size, Some(100000));
+}
+
+#[test]
+fn focused_tab_is_found() {
+    let layout = layout_fixture("multiple-tabs.yaml");
+    assert_eq!(layout.focused_tab(), Some(1));
+
+    let layout = layout_fixture("no-tabs.yaml");
+    assert!(layout.tabs.is_empty());
+    assert_eq!(layout.focused_tab(), None);
+}
+
+#[test]
+fn focused_pane_is_kept_when_positioning_panes() {
+    let layout = layout_fixture("multiple-tabs.yaml");
+    let editor = &layout.tabs[0];
+    let space = PositionAndSize {
+        cols: 100,
+        rows: 20,
+        ..Default::default()
+    };
+    let positions = editor.position_panes_in_space(&space);
+    let focused: Vec<bool> = positions.iter().map(|(pane, _)| pane.focus).collect();
+    assert_eq!(focused, vec![false, true]);
+    assert!(matches!(positions[0].0.run, Some(Run::Plugin(_))));
+    assert_eq!(
+        positions[0].1.cols, 29,
+        "30% of the columns, but for the gap"
+    );
+}
+
+#[test]
+fn tab_template_is_found_by_name() {
+    let layout = layout_fixture("multiple-tabs.yaml");
+    let logs = layout.tab_template("logs").unwrap();
+    assert_eq!(logs.name.as_deref(), Some("logs"));
+    assert_eq!(
+        command_of(&logs.parts[0]),
+        Some((
+            PathBuf::from("tail"),
+            vec!["-f".to_string(), "server.log".to_string()]
+        ))
+    );
+    assert!(layout.tab_template("missing").is_none());
+    assert!(layout_fixture("no-tabs.yaml")
+        .tab_template("logs")
+        .is_none());
+}
+
+#[test]
+fn terminal_panes_of_a_tab_template_are_counted() {
+    let layout = layout_fixture("multiple-tabs.yaml");
+    let editor = layout.tab_template("editor").unwrap();
+    let run_instructions = editor.extract_run_instructions();
+    assert_eq!(run_instructions.len(), 2);
+    assert!(matches!(run_instructions[0], Some(Run::Plugin(_))));
+    assert!(run_instructions[1].is_none());
+}
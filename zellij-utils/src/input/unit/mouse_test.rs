@@ -0,0 +1,42 @@
+use super::super::mouse::*;
+use crate::position::Position;
+
+#[test]
+fn drags_with_other_buttons_are_parsed() {
+    assert_eq!(
+        MouseEvent::from_unsupported(b"\x1b[<33;5;3M"),
+        Some(MouseEvent::Hold(Position::new(2, 4)))
+    );
+    assert_eq!(
+        MouseEvent::from_unsupported(b"\x1b[<34;5;3M"),
+        Some(MouseEvent::Hold(Position::new(2, 4)))
+    );
+}
+
+#[test]
+fn motion_is_parsed() {
+    assert_eq!(
+        MouseEvent::from_unsupported(b"\x1b[<35;1;1M"),
+        Some(MouseEvent::Motion(Position::new(0, 0)))
+    );
+    // with shift held
+    assert_eq!(
+        MouseEvent::from_unsupported(b"\x1b[<39;10;20M"),
+        Some(MouseEvent::Motion(Position::new(19, 9)))
+    );
+}
+
+#[test]
+fn other_sequences_are_not_parsed() {
+    for bytes in &[
+        &b"\x1b[<35;1;1m"[..],
+        b"\x1b[<35;1M",
+        b"\x1b[<35;1;1;1M",
+        b"\x1b[<a;1;1M",
+        b"\x1b[<0;1;1M",
+        b"\x1b[A",
+        b"",
+    ] {
+        assert_eq!(MouseEvent::from_unsupported(bytes), None, "{:?}", bytes);
+    }
+}
@@ -1,10 +1,14 @@
 //! IPC stuff for starting to split things into a client and server model.
 
 use crate::cli::CliArgs;
-use crate::pane_size::PositionAndSize;
+use crate::pane_size::{PositionAndSize, SizeInPixels};
 use crate::{
     errors::{get_current_ctx, ErrorContext},
-    input::{actions::Action, layout::Layout, options::Options},
+    input::{
+        actions::Action,
+        layout::{Layout, SessionSnapshot},
+        options::Options,
+    },
 };
 use interprocess::local_socket::LocalSocketStream;
 use nix::unistd::dup;
@@ -16,21 +20,31 @@ use std::os::unix::io::{AsRawFd, FromRawFd};
 
 use zellij_tile::data::Palette;
 
-type SessionId = u64;
-
-#[derive(PartialEq, Eq, Serialize, Deserialize, Hash)]
-pub struct Session {
-    // Unique ID for this session
-    id: SessionId,
-    // Identifier for the underlying IPC primitive (socket, pipe)
-    conn_name: String,
-    // User configured alias for the session
-    alias: String,
+/// Identifies a client connected to a session's server.
+pub type ClientId = u64;
+
+/// Metadata about a running session, as reported by its server.
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub name: String,
+    /// Creation time, in seconds since the unix epoch
+    pub created: u64,
+    /// Names of the tabs, in the order they are displayed
+    pub tabs: Vec<String>,
+    /// Number of terminal panes across all tabs
+    pub panes: usize,
+    /// Number of clients currently attached to the session
+    pub clients: usize,
+    /// The layout the session was started with
+    pub layout: Option<String>,
+    /// Set when the server did not answer, in which case only the name is known
+    pub unresponsive: bool,
 }
 
-// How do we want to connect to a session?
-#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// How do we want to connect to a session?
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClientType {
+    /// Receives renders, but cannot change the session
     Reader,
     Writer,
 }
@@ -39,46 +53,47 @@ pub enum ClientType {
 pub struct ClientAttributes {
     pub position_and_size: PositionAndSize,
     pub palette: Palette,
+    pub character_cell_size: Option<SizeInPixels>,
 }
 
 // Types of messages sent from the client to the server
 #[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ClientToServerMsg {
-    /*// List which sessions are available
-    ListSessions,
-    // Create a new session
-    CreateSession,
-    // Attach to a running session
-    AttachToSession(SessionId, ClientType),
-    // Force detach
-    DetachSession(SessionId),
-    // Disconnect from the session we're connected to
-    DisconnectFromSession,*/
-    TerminalResize(PositionAndSize),
-    NewClient(ClientAttributes, Box<CliArgs>, Box<Options>, Option<Layout>),
-    AttachClient(ClientAttributes, bool, Options),
+    /// The new size of the client's terminal, and of its character cells if it tells them
+    TerminalResize(PositionAndSize, Option<SizeInPixels>),
+    // Start the session, rebuilding it from the snapshot if there is one
+    NewClient(
+        ClientAttributes,
+        Box<CliArgs>,
+        Box<Options>,
+        Option<Layout>,
+        Option<SessionSnapshot>,
+    ),
+    // Attach to the running session, detaching its other clients if forced to
+    AttachClient(ClientAttributes, ClientType, bool, Options),
     Action(Action),
     ClientExited,
+    // Ask for the session's metadata, the server closes the connection after answering
+    QuerySessionInfo,
+    // Shut down the session, along with all of its clients
+    KillSession,
 }
 
 // Types of messages sent from the server to the client
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ServerToClientMsg {
-    /*// Info about a particular session
-    SessionInfo(Session),
-    // A list of sessions
-    SessionList(HashSet<Session>),*/
     Render(String),
     UnblockInputThread,
     Exit(ExitReason),
+    // Info about the session, in response to `QuerySessionInfo`
+    SessionInfo(SessionInfo),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ExitReason {
     Normal,
     ForceDetached,
-    CannotAttach,
     Error(String),
 }
 
@@ -88,11 +103,7 @@ impl Display for ExitReason {
             Self::Normal => write!(f, "Bye from Zellij!"),
             Self::ForceDetached => write!(
                 f,
-                "Session was detach from this client (possibly because another client connected)"
-            ),
-            Self::CannotAttach => write!(
-                f,
-                "Session attached to another client. Use --force flag to force connect."
+                "Session was detached from this client (possibly because another client connected)"
             ),
             Self::Error(e) => write!(f, "Error occured in server:\n{}", e),
         }
@@ -115,10 +126,11 @@ impl<T: Serialize> IpcSenderWithContext<T> {
     }
 
     /// Sends an event, along with the current [`ErrorContext`], on this [`IpcSenderWithContext`]'s socket.
-    pub fn send(&mut self, msg: T) {
+    /// Fails if the other end of the socket went away.
+    pub fn send(&mut self, msg: T) -> io::Result<()> {
         let err_ctx = get_current_ctx();
-        bincode::serialize_into(&mut self.sender, &(msg, err_ctx)).unwrap();
-        self.sender.flush().unwrap();
+        bincode::serialize_into(&mut self.sender, &(msg, err_ctx)).map_err(into_io_error)?;
+        self.sender.flush()
     }
 
     /// Returns an [`IpcReceiverWithContext`] with the same socket as this sender.
@@ -152,8 +164,9 @@ where
     }
 
     /// Receives an event, along with the current [`ErrorContext`], on this [`IpcReceiverWithContext`]'s socket.
-    pub fn recv(&mut self) -> (T, ErrorContext) {
-        bincode::deserialize_from(&mut self.receiver).unwrap()
+    /// Fails if the other end of the socket went away.
+    pub fn recv(&mut self) -> io::Result<(T, ErrorContext)> {
+        bincode::deserialize_from(&mut self.receiver).map_err(into_io_error)
     }
 
     /// Returns an [`IpcSenderWithContext`] with the same socket as this receiver.
@@ -164,3 +177,10 @@ where
         IpcSenderWithContext::new(socket)
     }
 }
+
+fn into_io_error(err: bincode::Error) -> io::Error {
+    match *err {
+        bincode::ErrorKind::Io(err) => err,
+        err => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
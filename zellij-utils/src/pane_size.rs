@@ -18,6 +18,29 @@ pub struct PositionAndSize {
     pub cols_fixed: bool,
 }
 
+/// The size of something (eg. a character cell of the terminal) in pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeInPixels {
+    pub height: usize,
+    pub width: usize,
+}
+
+impl SizeInPixels {
+    /// The size of a single character cell of a terminal, if the terminal reports its size in
+    /// pixels at all
+    pub fn character_cell_size(winsize: &Winsize) -> Option<SizeInPixels> {
+        if winsize.ws_col == 0 || winsize.ws_row == 0 {
+            return None;
+        }
+        let height = (winsize.ws_ypixel / winsize.ws_row) as usize;
+        let width = (winsize.ws_xpixel / winsize.ws_col) as usize;
+        if height == 0 || width == 0 {
+            return None;
+        }
+        Some(SizeInPixels { height, width })
+    }
+}
+
 impl From<Winsize> for PositionAndSize {
     fn from(winsize: Winsize) -> PositionAndSize {
         PositionAndSize {